@@ -1,5 +1,6 @@
 use std::error::Error;
 use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
 
 #[derive(Debug)]
 pub struct ArgumentError {
@@ -67,3 +68,117 @@ impl Error for ValidationError {
         &self.reason
     }
 }
+
+/// The ways that reading or packaging a presentation can fail, for code that uses
+/// mkrevealslides as a library and needs to tell them apart
+#[derive(Debug)]
+pub enum PresentationError {
+    /// The template file does not exist or cannot be read
+    MissingTemplate { path: PathBuf },
+    /// A slide cannot be read as a slide, such as one whose front matter is not valid
+    InvalidSlide { path: PathBuf, reason: String },
+    /// A slide references a local image that does not exist
+    ImageNotFound { slide: PathBuf, image: PathBuf },
+    /// The template, or a slide rendered as a template, could not be rendered
+    Render(tera::Error),
+    /// A file could not be read or written
+    Io(std::io::Error),
+    /// An option was given a value that cannot be used
+    Argument(ArgumentError),
+    /// A value, such as the path of a slide, is not valid
+    Validation(ValidationError),
+    /// Any other failure, along with the context it happened in
+    Other(anyhow::Error),
+}
+
+impl Display for PresentationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PresentationError::MissingTemplate { path } => write!(
+                f,
+                "Template file `{}` does not exist or cannot be read (configured in `template_file`)",
+                path.display()
+            ),
+            PresentationError::InvalidSlide { path, reason } => {
+                write!(f, "Invalid slide `{}`: {}", path.display(), reason)
+            }
+            PresentationError::ImageNotFound { slide, image } => write!(
+                f,
+                "Could not find image `{}` referenced in `{}`",
+                image.display(),
+                slide.display()
+            ),
+            PresentationError::Render(e) => e.fmt(f),
+            PresentationError::Io(e) => e.fmt(f),
+            PresentationError::Argument(e) => e.fmt(f),
+            PresentationError::Validation(e) => e.fmt(f),
+            PresentationError::Other(e) => e.fmt(f),
+        }
+    }
+}
+
+impl Error for PresentationError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PresentationError::MissingTemplate { .. }
+            | PresentationError::InvalidSlide { .. }
+            | PresentationError::ImageNotFound { .. } => None,
+            PresentationError::Render(e) => e.source(),
+            PresentationError::Io(e) => e.source(),
+            PresentationError::Argument(e) => e.source(),
+            PresentationError::Validation(e) => e.source(),
+            // the rest of the chain, as the outermost context is displayed already
+            PresentationError::Other(e) => AsRef::<dyn Error>::as_ref(e).source(),
+        }
+    }
+}
+
+impl From<std::io::Error> for PresentationError {
+    fn from(e: std::io::Error) -> Self {
+        PresentationError::Io(e)
+    }
+}
+
+impl From<tera::Error> for PresentationError {
+    fn from(e: tera::Error) -> Self {
+        PresentationError::Render(e)
+    }
+}
+
+impl From<ArgumentError> for PresentationError {
+    fn from(e: ArgumentError) -> Self {
+        PresentationError::Argument(e)
+    }
+}
+
+impl From<ValidationError> for PresentationError {
+    fn from(e: ValidationError) -> Self {
+        PresentationError::Validation(e)
+    }
+}
+
+/// Recovers the kind of error that an `anyhow::Error` was raised as. A `PresentationError`
+/// is recovered even if context was added to it, as it says what went wrong on its own.
+/// Other errors are only recovered if no context was added, so that none is lost
+impl From<anyhow::Error> for PresentationError {
+    fn from(e: anyhow::Error) -> Self {
+        let e = match e.downcast::<PresentationError>() {
+            Ok(e) => return e,
+            Err(e) => e,
+        };
+        let outermost: &(dyn Error + Send + Sync + 'static) = e.as_ref();
+        if outermost.is::<std::io::Error>() {
+            return PresentationError::Io(e.downcast().expect("error to be an io::Error"));
+        }
+        if outermost.is::<tera::Error>() {
+            return PresentationError::Render(e.downcast().expect("error to be a tera::Error"));
+        }
+        if outermost.is::<ArgumentError>() {
+            return PresentationError::Argument(e.downcast().expect("error to be an ArgumentError"));
+        }
+        if outermost.is::<ValidationError>() {
+            return PresentationError::Validation(e.downcast().expect("error to be a ValidationError"));
+        }
+        PresentationError::Other(e)
+    }
+}
@@ -2,6 +2,8 @@
 pub mod errors;
 /// Utilities to work with Presentations
 pub mod presentation;
+/// Serving presentations over HTTP while they are worked on
+pub mod serve;
 /// UI utilities
 /// The UI accepts input from the command line, and
 /// can also read a config file
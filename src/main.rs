@@ -1,14 +1,255 @@
-use clap::Parser;
+use anyhow::bail;
+use chrono::Utc;
+use clap::{CommandFactory, ErrorKind, Parser};
+use std::env;
+use std::io::{stderr, stdout};
+use std::net::SocketAddr;
+use std::process::ExitCode;
+use std::sync::Mutex;
+use mkrevealslides::presentation::batch::{build_all, init_thread_pool};
+use mkrevealslides::presentation::check::{check_images_exist, check_remote_urls, HttpLinkChecker};
+use mkrevealslides::presentation::incremental::{
+    read_build_timestamp, write_build_timestamp, write_dependency_file,
+};
+use mkrevealslides::presentation::watch::watch;
 use mkrevealslides::presentation::PresentationConfig;
+use mkrevealslides::serve::serve;
 
-use mkrevealslides::ui::cli::CliArgs;
+use mkrevealslides::ui::annotations::{AnnotationFormat, GithubAnnotations};
+use mkrevealslides::ui::cli::{CliArgs, Commands};
+use mkrevealslides::ui::color::{color_enabled, error, success, warning};
+use mkrevealslides::ui::conf::{find_default_config, PresentationConfigFile, DEFAULT_CONFIG_FILES};
+use mkrevealslides::ui::init::init_project;
 
-fn main() -> Result<(), anyhow::Error> {
+fn main() -> ExitCode {
     let cli_args = CliArgs::parse();
-    tracing_subscriber::fmt()
+    let no_color = cli_args.no_color;
+    // logs go to stderr so that stdout can carry the output, such as with `output_dir: -`
+    let subscriber = tracing_subscriber::fmt()
         .with_max_level(cli_args.get_log_level())
-        .init();
+        .with_ansi(color_enabled(no_color, &stderr()))
+        .with_writer(stderr);
+    match cli_args.annotations {
+        AnnotationFormat::Plain => subscriber.init(),
+        AnnotationFormat::Github => subscriber.event_format(GithubAnnotations).init(),
+    }
+    match run(cli_args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", error(&format!("Error: {:?}", e), color_enabled(no_color, &stderr())));
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Runs the command given on the command line, once logging has been set up
+fn run(mut cli_args: CliArgs) -> Result<(), anyhow::Error> {
+    if cli_args.command.is_none() {
+        let Some(config_path) = find_default_config(&env::current_dir()?) else {
+            // exits like any other invalid invocation, with the usage and clap's exit code
+            CliArgs::command()
+                .error(
+                    ErrorKind::MissingSubcommand,
+                    format!(
+                        "No command given, and no `{}` in the current directory",
+                        DEFAULT_CONFIG_FILES.join("` or `")
+                    ),
+                )
+                .exit();
+        };
+        cli_args.command = Some(Commands::FromConfig {
+            config_paths: vec![config_path],
+            jobs: None,
+            slide_dir: None,
+        });
+    }
+    init_thread_pool(cli_args.threads)?;
+    let build_start = Utc::now();
+    let last_build = match &cli_args.since_build {
+        Some(stamp) => read_build_timestamp(stamp)?,
+        None => None,
+    };
+    // whether a presentation should be built, given `--since-build`
+    let needs_build = |ppt_config: &PresentationConfig| -> Result<bool, anyhow::Error> {
+        let Some(last_build) = last_build else {
+            return Ok(true);
+        };
+        let changed = ppt_config.changed_since(last_build)?;
+        if !changed && !ppt_config.quiet {
+            let message = format!("`{}` has not changed since the last build, skipping", ppt_config.title);
+            eprintln!("{}", warning(&message, color_enabled(ppt_config.no_color, &stderr())));
+        }
+        Ok(changed)
+    };
+    if let Some(Commands::FromConfig { config_paths, jobs, .. }) = &cli_args.command {
+        if config_paths.len() > 1 {
+            let dependency_rules = Mutex::new(Vec::new());
+            let failures = build_all(config_paths, *jobs, |config_path| {
+                let ppt_config = PresentationConfig::from_config_path(config_path, &cli_args)?;
+                if cli_args.print_config {
+                    print!("{}", ppt_config.to_yaml()?);
+                    return Ok(());
+                }
+                if cli_args.check_links {
+                    return check_links(&ppt_config);
+                }
+                if !needs_build(&ppt_config)? {
+                    return Ok(());
+                }
+                let report = ppt_config.package()?;
+                if !ppt_config.quiet {
+                    report.print_summary(ppt_config.no_color);
+                }
+                if ppt_config.dry_run {
+                    return Ok(());
+                }
+                if let Some(rule) = ppt_config.dependency_rule() {
+                    dependency_rules
+                        .lock()
+                        .expect("no build to have panicked")
+                        .push((config_path.to_path_buf(), rule));
+                }
+                Ok(())
+            })?;
+            let color = color_enabled(cli_args.no_color, &stderr());
+            for failure in &failures {
+                let message = format!(
+                    "Failed to build `{}`: {:#}",
+                    failure.config_path.display(),
+                    failure.error
+                );
+                eprintln!("{}", error(&message, color));
+            }
+            if !failures.is_empty() {
+                bail!(
+                    "{} of {} presentations failed to build",
+                    failures.len(),
+                    config_paths.len()
+                );
+            }
+            if cli_args.dry_run {
+                return Ok(());
+            }
+            if let Some(stamp) = &cli_args.since_build {
+                write_build_timestamp(stamp, build_start)?;
+            }
+            if let Some(deps_file) = &cli_args.emit_deps {
+                // builds finish in any order, so sort for a stable file
+                let mut dependency_rules = dependency_rules.into_inner().expect("no build to have panicked");
+                dependency_rules.sort();
+                let rules = dependency_rules.into_iter().map(|(_, rule)| rule).collect::<Vec<String>>();
+                write_dependency_file(deps_file, &rules)?;
+            }
+            return Ok(());
+        }
+    }
+
+    if let Some(Commands::Init { dir, force }) = &cli_args.command {
+        let created = init_project(dir, *force)?;
+        if !cli_args.quiet {
+            for file in &created {
+                println!("Created `{}`", file.display());
+            }
+            let message = format!(
+                "Build the presentation with `mkrevealslides from-config {}`",
+                created[0].display()
+            );
+            println!("{}", success(&message, color_enabled(cli_args.no_color, &stdout())));
+        }
+        return Ok(());
+    }
+
+    if let Some(Commands::Watch { config_path }) = &cli_args.command {
+        return watch(config_path, &cli_args, |_| Ok(()));
+    }
+
+    if let Some(Commands::Serve {
+        config_path,
+        port,
+        bind,
+        no_open,
+    }) = &cli_args.command
+    {
+        return serve(config_path, &cli_args, SocketAddr::new(*bind, *port), !no_open);
+    }
+
+    if let Some(Commands::Validate {
+        config_path,
+        fast: true,
+    }) = &cli_args.command
+    {
+        let config = PresentationConfigFile::read_config_file(config_path.clone())?;
+        check_images_exist(&config)?;
+        if !cli_args.quiet {
+            let message = format!("No problems found in `{}`", config_path.display());
+            println!("{}", success(&message, color_enabled(cli_args.no_color, &stdout())));
+        }
+        return Ok(());
+    }
+
+    let print_config = cli_args.print_config;
+    let check_links_only = cli_args.check_links;
+    let validated_config = match &cli_args.command {
+        Some(Commands::Validate { config_path, .. }) => Some(config_path.clone()),
+        _ => None,
+    };
+    let since_build = cli_args.since_build.clone();
+    let emit_deps = cli_args.emit_deps.clone();
+    let markdown_file = match &cli_args.command {
+        Some(Commands::ExportMd {
+            output_file,
+            handout,
+            ..
+        }) => Some((output_file.clone(), *handout)),
+        _ => None,
+    };
     let ppt_config = PresentationConfig::try_from(cli_args)?;
-    ppt_config.package()?;
+    if print_config {
+        print!("{}", ppt_config.to_yaml()?);
+        return Ok(());
+    }
+    if check_links_only {
+        return check_links(&ppt_config);
+    }
+    if let Some(config_path) = validated_config {
+        // the config has been validated while loading it
+        if !ppt_config.quiet {
+            let message = format!("No problems found in `{}`", config_path.display());
+            println!("{}", success(&message, color_enabled(ppt_config.no_color, &stdout())));
+        }
+        return Ok(());
+    }
+    match markdown_file {
+        Some((markdown_file, handout)) => ppt_config.package_markdown(&markdown_file, handout)?,
+        None => {
+            if !needs_build(&ppt_config)? {
+                return Ok(());
+            }
+            let report = ppt_config.package()?;
+            if !ppt_config.quiet {
+                report.print_summary(ppt_config.no_color);
+            }
+            if ppt_config.dry_run {
+                return Ok(());
+            }
+            if let Some(stamp) = &since_build {
+                write_build_timestamp(stamp, build_start)?;
+            }
+            if let Some(deps_file) = &emit_deps {
+                let rules = ppt_config.dependency_rule().into_iter().collect::<Vec<String>>();
+                write_dependency_file(deps_file, &rules)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the remote images and links of the presentation can be reached
+fn check_links(ppt_config: &PresentationConfig) -> Result<(), anyhow::Error> {
+    let checked = check_remote_urls(&ppt_config.slides, &HttpLinkChecker::default())?;
+    if !ppt_config.quiet {
+        let message = format!("All {} remote URLs of `{}` are reachable", checked, ppt_config.title);
+        println!("{}", success(&message, color_enabled(ppt_config.no_color, &stdout())));
+    }
     Ok(())
 }
@@ -0,0 +1,83 @@
+use rayon::prelude::*;
+use rayon::ThreadPoolBuilder;
+use std::path::{Path, PathBuf};
+use tracing::{debug, info};
+
+/// Sets up the thread pool that slides are read and images are copied with.
+/// Must be called before anything is built in parallel
+///
+/// # Arguments
+/// * `threads` - Maximum number of threads to use, or `None` to use one per CPU
+///
+/// # Errors
+/// If the thread pool could not be created, or has already been set up
+pub fn init_thread_pool(threads: Option<usize>) -> Result<(), anyhow::Error> {
+    ThreadPoolBuilder::new()
+        .num_threads(threads.unwrap_or(0))
+        .build_global()?;
+    info!("Using {} threads", rayon::current_num_threads());
+    Ok(())
+}
+
+/// A presentation that failed to build
+#[derive(Debug)]
+pub struct BuildFailure {
+    /// Path to the config file of the presentation
+    pub config_path: PathBuf,
+    /// Why it failed to build
+    pub error: anyhow::Error,
+}
+
+/// Builds several presentations concurrently.
+/// A presentation that fails to build does not stop the others.
+///
+/// # Arguments
+/// * `config_paths` - Paths to the config files of the presentations to build
+/// * `jobs` - Maximum number of presentations to build at once,
+///   or `None` to build them on the thread pool set up by `init_thread_pool`.
+///   This is capped at the number of threads of that pool
+/// * `build` - Builds the presentation with the given config file
+///
+/// # Returns
+/// Every presentation that failed to build, in the order they were given
+///
+/// # Errors
+/// If the thread pool could not be created
+pub fn build_all<F>(
+    config_paths: &[PathBuf],
+    jobs: Option<usize>,
+    build: F,
+) -> Result<Vec<BuildFailure>, anyhow::Error>
+where
+    F: Fn(&Path) -> Result<(), anyhow::Error> + Sync,
+{
+    let build_each = || {
+        config_paths
+            .par_iter()
+            .filter_map(|config_path| {
+                build(config_path).err().map(|error| BuildFailure {
+                    config_path: config_path.clone(),
+                    error,
+                })
+            })
+            .collect()
+    };
+    // the slides of each presentation are read on whichever pool it is built on,
+    // so a pool of its own must not have more threads than the current one
+    let failures = match jobs {
+        Some(jobs) if jobs < rayon::current_num_threads() => {
+            let pool = ThreadPoolBuilder::new().num_threads(jobs).build()?;
+            debug!("Building {} presentations with {} jobs", config_paths.len(), jobs);
+            pool.install(build_each)
+        }
+        _ => {
+            debug!(
+                "Building {} presentations with {} jobs",
+                config_paths.len(),
+                rayon::current_num_threads()
+            );
+            build_each()
+        }
+    };
+    Ok(failures)
+}
@@ -0,0 +1,251 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::errors::PresentationError;
+use crate::presentation::io::{find_slide_paths, is_stdout, output_exclusions};
+use crate::presentation::reveal::{RevealConfig, RevealOptions, SlideNumber, DEFAULT_REVEAL_THEME};
+use crate::presentation::slide::{SlideFile, SlideOptions};
+use crate::presentation::{filter_drafts, split_slides, PresentationConfig};
+use crate::ui::conf::{default_slide_join, ContextCollision};
+use tracing::trace;
+
+/// Where slides added to a `PresentationConfigBuilder` come from
+#[derive(Debug, Clone)]
+enum SlideSource {
+    /// A single slide file
+    File(PathBuf),
+    /// Every slide in a directory, in the same order as `from-cli` finds them
+    Dir(PathBuf),
+}
+
+/// Builds a `PresentationConfig` in code, for using mkrevealslides as a library
+/// without a config file or the command line.
+/// Relative paths are relative to the current directory when `build()` is called
+#[derive(Debug, Clone)]
+pub struct PresentationConfigBuilder {
+    /// The presentation so far, without its slides
+    config: PresentationConfig,
+    /// Where the slides come from, in order
+    slides: Vec<SlideSource>,
+    /// Whether slides marked as drafts are kept
+    include_drafts: bool,
+}
+
+impl PresentationConfigBuilder {
+    /// Starts a presentation with the given title, written to `index.html` in `output/`
+    /// with the built-in template
+    pub fn new(title: impl Into<String>) -> Self {
+        PresentationConfigBuilder {
+            config: PresentationConfig {
+                title: title.into(),
+                subtitle: None,
+                title_slide: false,
+                title_suffix: None,
+                title_separator: None,
+                output_dir: PathBuf::from("output"),
+                create_output_dir: true,
+                no_overwrite: false,
+                output_filename: PathBuf::from("index.html"),
+                template_file: None,
+                template_dir: None,
+                outputs: Vec::new(),
+                slides: Vec::new(),
+                stacks: Vec::new(),
+                max_image_bytes: None,
+                static_assets: Vec::new(),
+                themes: Vec::new(),
+                theme: DEFAULT_REVEAL_THEME.to_string(),
+                quiet: false,
+                no_color: false,
+                source_comments: false,
+                dry_run: false,
+                slide_join: default_slide_join(),
+                locale: None,
+                extra_context: BTreeMap::new(),
+                context_collision: ContextCollision::default(),
+                config_path: None,
+                strict: false,
+                slide_options: SlideOptions::default(),
+                render_slides: false,
+                no_remote_images: false,
+                download_remote_images: false,
+                minify: false,
+                reveal: RevealOptions::default(),
+                reveal_config: RevealConfig::default(),
+                zip_output: false,
+                continue_on_copy_error: false,
+                thumbnails: false,
+            },
+            slides: Vec::new(),
+            include_drafts: false,
+        }
+    }
+
+    /// Sets the title of the presentation
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.config.title = title.into();
+        self
+    }
+
+    /// Sets the subtitle of the presentation
+    pub fn subtitle(mut self, subtitle: impl Into<String>) -> Self {
+        self.config.subtitle = Some(subtitle.into());
+        self
+    }
+
+    /// Sets the directory the presentation is written to, or `-` to write a tar archive to stdout
+    pub fn output_dir(mut self, output_dir: impl Into<PathBuf>) -> Self {
+        self.config.output_dir = output_dir.into();
+        self
+    }
+
+    /// Sets the filename of the presentation, relative to the output directory
+    pub fn output_file(mut self, output_file: impl Into<PathBuf>) -> Self {
+        self.config.output_filename = output_file.into();
+        self
+    }
+
+    /// Sets the template the presentation is rendered with, instead of the built-in one.
+    /// Relative to `template_dir` if it is set
+    pub fn template_file(mut self, template_file: impl Into<PathBuf>) -> Self {
+        self.config.template_file = Some(template_file.into());
+        self
+    }
+
+    /// Sets a directory of templates that the template can include and extend
+    pub fn template_dir(mut self, template_dir: impl Into<PathBuf>) -> Self {
+        self.config.template_dir = Some(template_dir.into());
+        self
+    }
+
+    /// Adds a slide after those added so far
+    pub fn add_slide(mut self, path: impl Into<PathBuf>) -> Self {
+        self.slides.push(SlideSource::File(path.into()));
+        self
+    }
+
+    /// Adds every slide in a directory after those added so far, ordered by their leading
+    /// number and then by name. The output directory is skipped if it is inside it,
+    /// as are slides that cannot be read, with a warning
+    pub fn slides_from_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.slides.push(SlideSource::Dir(dir.into()));
+        self
+    }
+
+    /// Splits each slide file into several slides at every line containing only `separator`,
+    /// such as `---`, outside of code
+    pub fn slide_separator(mut self, separator: impl Into<String>) -> Self {
+        self.config.slide_options.slide_separator = Some(separator.into());
+        self
+    }
+
+    /// Adds a file or directory to copy as it is into the output directory, under its own name
+    pub fn static_asset(mut self, asset: impl Into<PathBuf>) -> Self {
+        self.config.static_assets.push(asset.into());
+        self
+    }
+
+    /// Adds a theme to package the presentation with, each into its own subdirectory
+    pub fn theme(mut self, theme: impl Into<String>) -> Self {
+        self.config.themes.push(theme.into());
+        self
+    }
+
+    /// Sets the theme of reveal.js in the `theme` template variable, `black` by default.
+    /// Themes added with `theme()` take its place in their own copies of the presentation
+    pub fn reveal_theme(mut self, theme: impl Into<String>) -> Self {
+        self.config.theme = theme.into();
+        self
+    }
+
+    /// Fails validation if any local image is larger than this many bytes
+    pub fn max_image_bytes(mut self, max_image_bytes: u64) -> Self {
+        self.config.max_image_bytes = Some(max_image_bytes);
+        self
+    }
+
+    /// Sets the locale that `build_date` is formatted for, such as `de_DE`
+    pub fn locale(mut self, locale: impl Into<String>) -> Self {
+        self.config.locale = Some(locale.into());
+        self
+    }
+
+    /// Sets the directory, relative to the output directory, that images are placed in
+    pub fn image_root(mut self, image_root: impl Into<PathBuf>) -> Self {
+        self.config.slide_options.image_root = image_root.into();
+        self
+    }
+
+    /// Shows the number of the current slide
+    pub fn slide_numbers(mut self, slide_numbers: SlideNumber) -> Self {
+        self.config.reveal_config.slide_number = Some(slide_numbers);
+        self
+    }
+
+    /// Makes a variable available to the template
+    pub fn context(mut self, key: impl Into<String>, value: impl Into<serde_yaml::Value>) -> Self {
+        self.config.extra_context.insert(key.into(), value.into());
+        self
+    }
+
+    /// Turns likely misconfigurations, such as an empty template, into errors instead of warnings
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.config.strict = strict;
+        self
+    }
+
+    /// Keeps slides marked as `draft: true` in their front matter
+    pub fn include_drafts(mut self, include_drafts: bool) -> Self {
+        self.include_drafts = include_drafts;
+        self
+    }
+
+    /// Fails validation if any slide references an image that is not bundled
+    pub fn no_remote_images(mut self, no_remote_images: bool) -> Self {
+        self.config.no_remote_images = no_remote_images;
+        self
+    }
+
+    /// Fails validation if the output file exists already, instead of overwriting it
+    pub fn no_overwrite(mut self, no_overwrite: bool) -> Self {
+        self.config.no_overwrite = no_overwrite;
+        self
+    }
+
+    /// Reads the slides and validates the presentation
+    ///
+    /// # Errors
+    /// If a slide cannot be read, a directory of slides does not exist,
+    /// or the presentation is not valid, such as when its template does not exist
+    pub fn build(self) -> Result<PresentationConfig, PresentationError> {
+        trace!("Building PresentationConfig");
+        let cwd = fs::canonicalize(env::current_dir()?)?;
+        let mut cfg = self.config;
+        if !is_stdout(&cfg.output_dir) {
+            cfg.output_dir = cwd.join(&cfg.output_dir);
+        }
+        cfg.template_dir = cfg.template_dir.map(|d| cwd.join(d));
+        cfg.static_assets = cfg.static_assets.iter().map(|a| cwd.join(a)).collect();
+        let template_base = cfg.template_dir.as_ref().unwrap_or(&cwd);
+        cfg.template_file = cfg.template_file.map(|t| template_base.join(t));
+
+        // slides found in a directory are skipped if they cannot be read, but not those added one by one
+        let mut paths = Vec::new();
+        for source in self.slides {
+            match source {
+                SlideSource::File(path) => paths.push((cwd.join(path), false)),
+                SlideSource::Dir(dir) => {
+                    let dir = cwd.join(dir);
+                    let exclude = output_exclusions(&dir, &cfg.output_dir, &cfg.output_filename);
+                    paths.extend(find_slide_paths(&dir, &exclude)?.into_iter().map(|p| (p, true)));
+                }
+            }
+        }
+        let slides = SlideFile::read_all(paths, &cfg.slide_options)?;
+        cfg.slides = split_slides(filter_drafts(slides, self.include_drafts));
+        cfg.validate()?;
+        Ok(cfg)
+    }
+}
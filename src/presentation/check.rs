@@ -0,0 +1,271 @@
+use std::time::Duration;
+
+use anyhow::bail;
+use rayon::prelude::*;
+use tracing::{debug, trace};
+
+use crate::presentation::io::{
+    append_discovered, expand_include_file, find_slide_paths, missing_slide_error, output_exclusions,
+};
+use crate::presentation::slide::{SlideFile, SlideOptions};
+use crate::ui::conf::PresentationConfigFile;
+
+/// Checks that every local image referenced by the slides of a config exists.
+/// This is quicker than a build since images are neither read nor canonicalized,
+/// and nothing is rendered
+///
+/// # Arguments
+/// * `config` - The config file of the presentation
+///
+/// # Errors
+/// - If a slide could not be found or read
+/// - If any referenced image does not exist, listing every such image
+pub fn check_images_exist(config: &PresentationConfigFile) -> Result<(), anyhow::Error> {
+    let slide_dir = config.working_dir.join(&config.slide_dir);
+    let exclude = output_exclusions(
+        &slide_dir,
+        &config.working_dir.join(&config.output_dir),
+        &config.output_file,
+    );
+    let slide_paths = if config.include_files.is_empty() {
+        find_slide_paths(&slide_dir, &exclude)?
+    } else {
+        let mut include_files = Vec::new();
+        for include_file in &config.include_files {
+            include_files.extend(expand_include_file(&slide_dir, include_file.path())?);
+        }
+        if config.discover_remaining {
+            append_discovered(include_files, find_slide_paths(&slide_dir, &exclude)?)
+        } else {
+            include_files
+        }
+    };
+    let options = SlideOptions {
+        extensions: config.slide_extensions.clone(),
+        ..SlideOptions::default()
+    };
+
+    let mut missing = Vec::new();
+    for slide_path in &slide_paths {
+        trace!("Checking images of `{}`", slide_path.display());
+        if !slide_path.exists() {
+            return Err(missing_slide_error(slide_path).into());
+        }
+        // the slides of a slideshow are the images themselves
+        if config.image_slideshow {
+            continue;
+        }
+        SlideFile::validate_path(slide_path, &options)?;
+        for img_path in SlideFile::missing_local_images(slide_path)? {
+            missing.push(format!(
+                "`{}` referenced by `{}`",
+                img_path.display(),
+                slide_path.display()
+            ));
+        }
+    }
+    if !missing.is_empty() {
+        bail!(
+            "{} referenced images do not exist:\n  {}",
+            missing.len(),
+            missing.join("\n  ")
+        );
+    }
+    Ok(())
+}
+
+/// How long a remote URL may take to respond before it is reported as unreachable
+pub const LINK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Checks whether remote URLs can be reached
+pub trait LinkChecker: Sync {
+    /// Checks the URL, returning why it cannot be reached if it cannot
+    fn check(&self, url: &str) -> Result<(), String>;
+}
+
+/// Checks remote URLs with HEAD requests over HTTP
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HttpLinkChecker {
+    /// How long each request may take
+    pub timeout: Duration,
+}
+
+impl Default for HttpLinkChecker {
+    fn default() -> Self {
+        HttpLinkChecker { timeout: LINK_TIMEOUT }
+    }
+}
+
+impl LinkChecker for HttpLinkChecker {
+    fn check(&self, url: &str) -> Result<(), String> {
+        let head = ureq::head(url)
+            .config()
+            .timeout_global(Some(self.timeout))
+            .build()
+            .call();
+        match head {
+            Ok(_) => Ok(()),
+            // some servers do not support HEAD requests, so try a GET instead
+            Err(ureq::Error::StatusCode(405)) => ureq::get(url)
+                .config()
+                .timeout_global(Some(self.timeout))
+                .build()
+                .call()
+                .map(|_| ())
+                .map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
+
+/// Checks that every remote image and link of the slides can be reached,
+/// without modifying anything. Each URL is only checked once
+///
+/// # Arguments
+/// * `slides` - The slides whose URLs are checked
+/// * `checker` - Checks each URL
+///
+/// # Returns
+/// The number of URLs that were checked
+///
+/// # Errors
+/// If any URL cannot be reached, listing every such URL along with the slide it is in
+pub fn check_remote_urls(slides: &[SlideFile], checker: &impl LinkChecker) -> Result<usize, anyhow::Error> {
+    let mut urls = Vec::new();
+    for slide in slides {
+        for url in slide.remote_images.iter().chain(&slide.remote_links) {
+            if !urls.iter().any(|(u, _)| *u == url) {
+                urls.push((url, slide));
+            }
+        }
+    }
+    let failures = urls
+        .par_iter()
+        .filter_map(|(url, slide)| {
+            debug!("Checking `{}`", url);
+            checker.check(url).err().map(|reason| {
+                format!("`{}` in `{}`: {}", url, slide.path.display(), reason)
+            })
+        })
+        .collect::<Vec<String>>();
+    if !failures.is_empty() {
+        bail!(
+            "{} of {} remote URLs are unreachable:\n  {}",
+            failures.len(),
+            urls.len(),
+            failures.join("\n  ")
+        );
+    }
+    Ok(urls.len())
+}
+
+/// Checks that no two slides have the same first level 1 heading,
+/// such as for decks whose headings are used as anchors or in a table of contents.
+/// Slides without such a heading are not checked
+///
+/// # Arguments
+/// * `slides` - The slides whose headings are checked
+///
+/// # Errors
+/// If any heading is the first of more than one slide, listing each such heading along with its slides
+pub fn check_unique_titles(slides: &[SlideFile]) -> Result<(), anyhow::Error> {
+    let mut titles: Vec<(String, Vec<&SlideFile>)> = Vec::new();
+    for slide in slides {
+        let Some(title) = slide.first_heading() else {
+            continue;
+        };
+        match titles.iter_mut().find(|(t, _)| *t == title) {
+            Some((_, with_title)) => with_title.push(slide),
+            None => titles.push((title, vec![slide])),
+        }
+    }
+    let duplicates = titles
+        .iter()
+        .filter(|(_, with_title)| with_title.len() > 1)
+        .map(|(title, with_title)| {
+            let paths = with_title
+                .iter()
+                .map(|slide| format!("`{}`", slide.path.display()))
+                .collect::<Vec<String>>();
+            format!("`{}` in {}", title, paths.join(", "))
+        })
+        .collect::<Vec<String>>();
+    if !duplicates.is_empty() {
+        bail!(
+            "{} slide titles are used more than once:\n  {}",
+            duplicates.len(),
+            duplicates.join("\n  ")
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    /// Reports the URLs containing `dead` as unreachable
+    struct StubChecker;
+
+    impl LinkChecker for StubChecker {
+        fn check(&self, url: &str) -> Result<(), String> {
+            if url.contains("dead") {
+                Err("connection refused".to_string())
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    #[test]
+    fn test_check_remote_urls() {
+        let tmp_dir = tempdir().unwrap();
+        let slide_path = tmp_dir.path().join("1_links.md");
+        fs::write(
+            &slide_path,
+            "![alive](https://example.com/alive.png)\n\n[docs](https://example.com/docs) [local](./2_next.md)",
+        )
+        .unwrap();
+        let alive = SlideFile::read_and_parse(&slide_path).unwrap();
+        assert_eq!(check_remote_urls(std::slice::from_ref(&alive), &StubChecker).unwrap(), 2);
+
+        let slide_path = tmp_dir.path().join("2_dead.md");
+        fs::write(&slide_path, "[gone](https://dead.example.com/)\n[docs](https://example.com/docs)").unwrap();
+        let dead = SlideFile::read_and_parse(&slide_path).unwrap();
+        let err = check_remote_urls(&[alive, dead], &StubChecker).unwrap_err().to_string();
+        assert!(err.starts_with("1 of 3 remote URLs are unreachable"));
+        assert!(err.contains("`https://dead.example.com/` in"));
+        assert!(err.contains("connection refused"));
+        tmp_dir.close().unwrap();
+    }
+
+    #[test]
+    fn test_check_unique_titles() {
+        let tmp_dir = tempdir().unwrap();
+        let mut slides = Vec::new();
+        for (name, contents) in [
+            ("1_intro.md", "# Intro\n\nHello"),
+            ("2_agenda.md", "## Subheading\n\n# Agenda"),
+            ("3_untitled.md", "No heading"),
+            ("4_untitled.md", "No heading either"),
+        ] {
+            let slide_path = tmp_dir.path().join(name);
+            fs::write(&slide_path, contents).unwrap();
+            slides.push(SlideFile::read_and_parse(&slide_path).unwrap());
+        }
+        assert_eq!(slides[1].first_heading().as_deref(), Some("Agenda"));
+        check_unique_titles(&slides).unwrap();
+
+        let slide_path = tmp_dir.path().join("5_intro_again.md");
+        fs::write(&slide_path, "# *Intro*").unwrap();
+        slides.push(SlideFile::read_and_parse(&slide_path).unwrap());
+        let err = check_unique_titles(&slides).unwrap_err().to_string();
+        assert!(err.starts_with("1 slide titles are used more than once"));
+        assert!(err.contains("`Intro` in `"));
+        assert!(err.contains("1_intro.md`, `"));
+        assert!(err.contains("5_intro_again.md`"));
+        tmp_dir.close().unwrap();
+    }
+}
@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use pulldown_cmark::{html, Options, Parser};
+use tera::{try_get_value, Context, Tera, Value};
+
+/// Name that templates rendered with `render_one_off` are known by in errors
+const ONE_OFF_TEMPLATE: &str = "__tera_one_off";
+/// What `truncate_words` appends to text it has shortened unless told otherwise
+const DEFAULT_TRUNCATE_END: &str = "…";
+
+/// Creates a template engine with the filters that mkrevealslides provides
+/// on top of those built into Tera. Nothing is escaped automatically
+pub fn template_engine() -> Tera {
+    let mut tera = Tera::default();
+    with_filters(&mut tera);
+    tera
+}
+
+/// Adds the filters of `template_engine` to a template engine, and turns off escaping
+fn with_filters(tera: &mut Tera) {
+    tera.autoescape_on(Vec::new());
+    tera.register_filter("slugify", slugify);
+    tera.register_filter("truncate_words", truncate_words);
+    tera.register_filter("markdown", markdown);
+}
+
+/// Renders a template on its own, like `Tera::one_off` but with the filters of `template_engine`
+///
+/// # Errors
+/// If the template cannot be parsed or rendered
+pub fn render_one_off(template: &str, context: &Context) -> Result<String, tera::Error> {
+    let mut tera = template_engine();
+    tera.add_raw_template(ONE_OFF_TEMPLATE, template)?;
+    tera.render(ONE_OFF_TEMPLATE, context)
+}
+
+/// Renders a template of a directory of templates, which may include, import and extend
+/// the others. Every `.html` file in the directory and its subdirectories is loaded,
+/// named by its path relative to the directory, such as `partials/header.html`
+///
+/// # Arguments
+/// * `dir` - The directory of templates
+/// * `entry` - The name of the template to render
+///
+/// # Errors
+/// If any template in the directory cannot be parsed, or the entry template cannot be rendered
+pub fn render_in_dir(dir: &Path, entry: &str, context: &Context) -> Result<String, tera::Error> {
+    let mut tera = Tera::new(&format!("{}/**/*.html", dir.display()))?;
+    with_filters(&mut tera);
+    tera.render(entry, context)
+}
+
+/// Turns text into something that can be used in a URL or as an `id`,
+/// such as `Hello, World!` into `hello-world`
+fn slugify(value: &Value, _: &HashMap<String, Value>) -> tera::Result<Value> {
+    let text = try_get_value!("slugify", "value", String, value);
+    let slug = text
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<&str>>()
+        .join("-");
+    Ok(Value::String(slug))
+}
+
+/// Keeps the first `count` words of text, followed by `end` if any were left out
+fn truncate_words(value: &Value, args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let text = try_get_value!("truncate_words", "value", String, value);
+    let count = match args.get("count") {
+        Some(count) => try_get_value!("truncate_words", "count", usize, count),
+        None => return Err(tera::Error::msg("Filter `truncate_words` expected an arg called `count`")),
+    };
+    let end = match args.get("end") {
+        Some(end) => try_get_value!("truncate_words", "end", String, end),
+        None => DEFAULT_TRUNCATE_END.to_string(),
+    };
+    let words = text.split_whitespace().collect::<Vec<&str>>();
+    if words.len() <= count {
+        return Ok(Value::String(text));
+    }
+    Ok(Value::String(format!("{}{}", words[..count].join(" "), end)))
+}
+
+/// Renders markdown into HTML, the same way slides are
+fn markdown(value: &Value, _: &HashMap<String, Value>) -> tera::Result<Value> {
+    let text = try_get_value!("markdown", "value", String, value);
+    let mut rendered = String::new();
+    html::push_html(&mut rendered, Parser::new_ext(&text, Options::all()));
+    Ok(Value::String(rendered))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_filters() {
+        let mut ctx = Context::new();
+        ctx.insert("title", "Hello, World! Ünïcode 2");
+        ctx.insert("notes", "Some *emphasis* and `code`");
+        assert_eq!(
+            render_one_off("{{ title | slugify }}", &ctx).unwrap(),
+            "hello-world-ünïcode-2"
+        );
+        assert_eq!(
+            render_one_off("{{ notes | markdown }}", &ctx).unwrap(),
+            "<p>Some <em>emphasis</em> and <code>code</code></p>\n"
+        );
+        assert_eq!(
+            render_one_off("{{ notes | truncate_words(count=2) }}", &ctx).unwrap(),
+            "Some *emphasis*…"
+        );
+        assert_eq!(
+            render_one_off("{{ title | truncate_words(count=3, end=\"...\") }}", &ctx).unwrap(),
+            "Hello, World! Ünïcode..."
+        );
+        assert_eq!(
+            render_one_off("{{ title | truncate_words(count=10) }}", &ctx).unwrap(),
+            "Hello, World! Ünïcode 2"
+        );
+        assert!(render_one_off("{{ title | truncate_words }}", &ctx).is_err());
+    }
+}
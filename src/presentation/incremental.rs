@@ -0,0 +1,144 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use chrono::{DateTime, SecondsFormat, Utc};
+
+/// Reads the time of the last successful build from a timestamp file
+///
+/// # Arguments
+/// * `path` - Path to the timestamp file
+///
+/// # Returns
+/// The time of the last build, or `None` if the timestamp file does not exist yet
+///
+/// # Errors
+/// If the timestamp file could not be read, or does not contain an RFC 3339 timestamp
+pub fn read_build_timestamp(path: &Path) -> Result<Option<DateTime<Utc>>, anyhow::Error> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(e) => {
+            return Err(e)
+                .with_context(|| format!("Could not read timestamp file `{}`", path.display()))
+        }
+    };
+    let timestamp = DateTime::parse_from_rfc3339(contents.trim())
+        .with_context(|| format!("Invalid timestamp in `{}`", path.display()))?;
+    Ok(Some(timestamp.with_timezone(&Utc)))
+}
+
+/// Records the time of a successful build in a timestamp file
+///
+/// # Arguments
+/// * `path` - Path to the timestamp file
+/// * `timestamp` - When the build started, so that changes made during it are picked up next time
+///
+/// # Errors
+/// If the timestamp file could not be written
+pub fn write_build_timestamp(path: &Path, timestamp: DateTime<Utc>) -> Result<(), anyhow::Error> {
+    fs::write(
+        path,
+        format!("{}\n", timestamp.to_rfc3339_opts(SecondsFormat::Nanos, true)),
+    )
+    .with_context(|| format!("Could not write timestamp file `{}`", path.display()))
+}
+
+/// Checks if the file at the given path was modified after the given time
+///
+/// # Errors
+/// If the modification time of the file could not be read
+pub fn modified_since(path: &Path, since: DateTime<Utc>) -> Result<bool, anyhow::Error> {
+    let modified = fs::metadata(path)
+        .and_then(|m| m.modified())
+        .with_context(|| format!("Could not read modification time of `{}`", path.display()))?;
+    Ok(DateTime::<Utc>::from(modified) > since)
+}
+
+/// Escapes a path for use in a Makefile rule
+fn escape_make_path(path: &Path) -> String {
+    path.display()
+        .to_string()
+        .replace('$', "$$")
+        .replace('#', "\\#")
+        .replace(' ', "\\ ")
+}
+
+/// Formats a Makefile rule, as understood by Make and Ninja's `depfile`,
+/// that has no recipe and is only used to track dependencies
+///
+/// # Arguments
+/// * `targets` - Files that are built
+/// * `prerequisites` - Files that the targets are built from
+pub fn make_rule(targets: &[PathBuf], prerequisites: &[&PathBuf]) -> String {
+    let mut rule = targets
+        .iter()
+        .map(|t| escape_make_path(t))
+        .collect::<Vec<String>>()
+        .join(" ");
+    rule.push(':');
+    for prerequisite in prerequisites {
+        rule.push_str(" \\\n  ");
+        rule.push_str(&escape_make_path(prerequisite));
+    }
+    rule.push('\n');
+    rule
+}
+
+/// Writes a dependency file made of the given Makefile rules
+///
+/// # Arguments
+/// * `path` - Path to the dependency file
+/// * `rules` - Rules as given by `make_rule`
+///
+/// # Errors
+/// If the dependency file could not be written
+pub fn write_dependency_file(path: &Path, rules: &[String]) -> Result<(), anyhow::Error> {
+    fs::write(path, rules.concat())
+        .with_context(|| format!("Could not write dependency file `{}`", path.display()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::Duration;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_build_timestamp_round_trip() {
+        let tmp_dir = tempdir().unwrap();
+        let stamp = tmp_dir.path().join("stamp");
+        assert_eq!(read_build_timestamp(&stamp).unwrap(), None);
+
+        let now = Utc::now();
+        write_build_timestamp(&stamp, now).unwrap();
+        assert_eq!(read_build_timestamp(&stamp).unwrap(), Some(now));
+
+        fs::write(&stamp, "yesterday").unwrap();
+        assert!(read_build_timestamp(&stamp).is_err());
+    }
+
+    #[test]
+    fn test_make_rule() {
+        let target = PathBuf::from("/out/index.html");
+        let slide = PathBuf::from("/my slides/1_intro.md");
+        let image = PathBuf::from("/img/#1$.png");
+        assert_eq!(make_rule(std::slice::from_ref(&target), &[]), "/out/index.html:\n");
+        assert_eq!(
+            make_rule(&[target], &[&slide, &image]),
+            "/out/index.html: \\\n  /my\\ slides/1_intro.md \\\n  /img/\\#1$$.png\n"
+        );
+    }
+
+    #[test]
+    fn test_modified_since() {
+        let tmp_dir = tempdir().unwrap();
+        let file = tmp_dir.path().join("file");
+        fs::write(&file, "contents").unwrap();
+        let now = Utc::now();
+        assert!(modified_since(&file, now - Duration::hours(1)).unwrap());
+        assert!(!modified_since(&file, now + Duration::hours(1)).unwrap());
+        assert!(modified_since(&tmp_dir.path().join("missing"), now).is_err());
+    }
+}
@@ -1,35 +1,436 @@
 use std::fs;
 
-use std::io::Error;
+use std::io::{Error, ErrorKind, Write};
+use std::thread;
+use std::time::Duration;
 
 
 use std::path::{Path, PathBuf};
 
 
-use crate::presentation::slide::SlideFile;
-use tracing::trace;
+use crate::errors::ValidationError;
+use crate::presentation::ordering::{order_slides, OrderMode};
+use crate::presentation::slide::{is_image, SlideFile, SlideGroup, SlideOptions};
+use tracing::{trace, warn};
+
+/// Output directory that streams the build to stdout as a tar archive instead
+pub const STDOUT_OUTPUT_DIR: &str = "-";
+
+/// Checks if the output directory is `-`, meaning the build goes to stdout
+pub fn is_stdout(output_dir: &Path) -> bool {
+    output_dir == Path::new(STDOUT_OUTPUT_DIR)
+}
+
+/// Writes every file in `dir` and its subdirectories into a zip archive at `zip_path`,
+/// with paths relative to `dir` so that the archive extracts into the same layout.
+/// `zip_path` itself is left out if it is inside `dir`
+///
+/// # Errors
+/// If `dir` could not be read or the archive could not be written
+pub fn zip_dir(dir: &Path, zip_path: &Path) -> Result<(), anyhow::Error> {
+    let mut files = Vec::new();
+    collect_files(dir, &mut files)?;
+    files.retain(|f| f != zip_path);
+    files.sort();
+    let zip_file = fs::File::create(zip_path)?;
+    let mut archive = zip::ZipWriter::new(zip_file);
+    let options = zip::write::SimpleFileOptions::default();
+    for file in files {
+        let name = file
+            .strip_prefix(dir)?
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        trace!("Adding `{}` to `{}`", name, zip_path.display());
+        archive.start_file(name, options)?;
+        archive.write_all(&fs::read(&file)?)?;
+    }
+    archive.finish()?;
+    Ok(())
+}
+
+/// Appends every file in `dir` and its subdirectories to `files`
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), Error> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
 
 /// Checks if the file at the given path has an extension of .md
 pub fn is_markdown_file(fp: &Path) -> bool {
-    fp.extension().unwrap_or_default().to_ascii_lowercase() == "md"
+    fp.extension().unwrap_or_default().eq_ignore_ascii_case("md")
+}
+
+/// Checks if the file at the given path has one of the given extensions, ignoring case.
+/// The extensions may be given with or without a leading `.`
+pub fn has_extension(fp: &Path, extensions: &[String]) -> bool {
+    let ext = fp.extension().unwrap_or_default();
+    extensions
+        .iter()
+        .any(|e| ext.eq_ignore_ascii_case(e.trim_start_matches('.')))
 }
 
 /// Attempts to find slides in the given directory
 ///
 /// # Arguments
 /// * slide_dir: The directory that contains your slides
+/// * exclude: Paths to skip during discovery, along with anything inside them.
+///   Used to keep generated output from being picked up as slides
+/// * options: Options that control how the slides are read
+///
+/// # Returns
+/// A vector of slides in the given directory, ordered as by `SlideFile`'s `Ord` impl
+///
+/// # Errors
+/// Returns an error if the slide directory could not be read
+pub fn find_slides(
+    slide_dir: &Path,
+    exclude: &[PathBuf],
+    options: &SlideOptions,
+) -> Result<Vec<SlideFile>, anyhow::Error> {
+    let files = find_slide_paths(slide_dir, exclude)?;
+    SlideFile::from_paths(files, options)
+}
+
+/// Attempts to find slides in the given directory, like `find_slides`, but skips the slides
+/// that cannot be read, such as those whose filename or contents are not UTF-8, with a warning
+///
+/// # Errors
+/// Returns an error if the slide directory could not be read, or a slide that could be read is not valid
+pub fn find_slides_lenient(
+    slide_dir: &Path,
+    exclude: &[PathBuf],
+    options: &SlideOptions,
+) -> Result<Vec<SlideFile>, anyhow::Error> {
+    let files = find_slide_paths(slide_dir, exclude)?;
+    SlideFile::from_paths_lenient(files, options)
+}
+
+/// Finds the paths of slides in the given directory, like `find_slides`, without reading them
+///
+/// # Arguments
+/// * slide_dir: The directory that contains your slides
+/// * exclude: Paths to skip during discovery, along with anything inside them
 ///
 /// # Returns
-/// A sorted vector of paths to slides in the given directory, sorted by alphabetical order
+/// Paths to every file in the given directory, ordered as by `SlideFile`'s `Ord` impl
 ///
 /// # Errors
 /// Returns an error if the slide directory could not be read
-pub fn find_slides(slide_dir: &PathBuf) -> Result<Vec<SlideFile>, anyhow::Error> {
+pub fn find_slide_paths(slide_dir: &Path, exclude: &[PathBuf]) -> Result<Vec<PathBuf>, Error> {
     trace!("Finding slides in {}", slide_dir.display());
-    let files = list_directory(slide_dir, true)?;
-    let mut slide_files = SlideFile::from_paths(files)?;
-    slide_files.sort();
-    Ok(slide_files)
+    check_slide_dir(slide_dir)?;
+    let exclude = exclude.iter().map(|p| normalize_path(p)).collect::<Vec<PathBuf>>();
+    let files = list_directory(slide_dir, true)?
+        .into_iter()
+        .filter(|f| {
+            let f = normalize_path(f);
+            let is_excluded = exclude.iter().any(|e| f.starts_with(e));
+            if is_excluded {
+                trace!("Skipping `{}` as it is excluded", f.display());
+            }
+            !is_excluded
+        })
+        .collect();
+    Ok(order_slides(files, OrderMode::NumericPrefix))
+}
+
+/// Checks that the slide directory exists before it is searched, so that a mistyped
+/// `slide_dir` is reported as such rather than as the directory failing to be read
+///
+/// # Errors
+/// If the slide directory does not exist or is not a directory
+fn check_slide_dir(slide_dir: &Path) -> Result<(), Error> {
+    if !slide_dir.exists() {
+        return Err(Error::new(
+            ErrorKind::NotFound,
+            format!(
+                "Slide directory `{}` does not exist (configured in `slide_dir`)",
+                slide_dir.display()
+            ),
+        ));
+    }
+    if !slide_dir.is_dir() {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "Slide directory `{}` is not a directory (configured in `slide_dir`)",
+                slide_dir.display()
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Appends the discovered slides that are not among the explicitly listed ones
+/// after the explicitly listed ones
+///
+/// # Arguments
+/// * explicit: Paths to the slides that are listed explicitly, in order
+/// * discovered: Paths to the slides that were discovered, in order
+///
+/// # Returns
+/// The explicit slides followed by the remaining discovered ones
+pub fn append_discovered(mut explicit: Vec<PathBuf>, discovered: Vec<PathBuf>) -> Vec<PathBuf> {
+    let listed = explicit.iter().map(|p| normalize_path(p)).collect::<Vec<PathBuf>>();
+    explicit.extend(
+        discovered
+            .into_iter()
+            .filter(|p| !listed.contains(&normalize_path(p))),
+    );
+    explicit
+}
+
+/// Checks whether an `include_files` entry is a glob pattern, such as `chapter1/*.md`,
+/// rather than the path of a single slide
+pub fn is_glob_pattern(entry: &Path) -> bool {
+    entry
+        .to_str()
+        .is_some_and(|e| e.contains(['*', '?', '[']))
+}
+
+/// Expands an `include_files` entry into the paths of the slides it stands for.
+/// A glob pattern is matched against the files in the slide directory, and its matches
+/// are placed in natural order. Any other entry is the path of a single slide
+///
+/// # Arguments
+/// * slide_dir: The directory that entries are relative to
+/// * entry: The entry, as listed in `include_files`
+///
+/// # Errors
+/// If the entry is not a valid glob pattern, or a glob pattern that matches no files
+pub fn expand_include_file(slide_dir: &Path, entry: &Path) -> Result<Vec<PathBuf>, ValidationError> {
+    if !is_glob_pattern(entry) {
+        return Ok(vec![slide_dir.join(entry)]);
+    }
+    let pattern = Path::new(&glob::Pattern::escape(&slide_dir.to_string_lossy())).join(entry);
+    let matches = glob::glob(&pattern.to_string_lossy())
+        .map_err(|e| ValidationError::new(&entry.display().to_string(), format!("Invalid glob pattern: {}", e)))?
+        .filter_map(Result::ok)
+        .filter(|p| p.is_file())
+        .collect::<Vec<PathBuf>>();
+    if matches.is_empty() {
+        return Err(ValidationError::new(
+            &entry.display().to_string(),
+            "Glob pattern does not match any files".to_string(),
+        ));
+    }
+    trace!("Expanded `{}` to {} slides", entry.display(), matches.len());
+    Ok(order_slides(matches, OrderMode::Natural))
+}
+
+/// Checks if a file has been copied to `dst` by `copy_file` already, and is unchanged since:
+/// both have the same size and modification time
+pub fn is_up_to_date(src: &Path, dst: &Path) -> bool {
+    match (fs::metadata(src), fs::metadata(dst)) {
+        (Ok(src), Ok(dst)) => {
+            src.len() == dst.len() && matches!((src.modified(), dst.modified()), (Ok(a), Ok(b)) if a == b)
+        }
+        _ => false,
+    }
+}
+
+/// Copies a file like `fs::copy`, and gives the copy the modification time of the original,
+/// so that `is_up_to_date` can tell that it does not need copying again
+///
+/// # Returns
+/// The number of bytes copied
+///
+/// # Errors
+/// If the file could not be copied
+pub fn copy_file(src: &Path, dst: &Path) -> Result<u64, Error> {
+    // fs::copy closes both files before it returns
+    let bytes = fs::copy(src, dst)?;
+    let modified = fs::metadata(src)?.modified()?;
+    fs::File::options().write(true).open(dst)?.set_modified(modified)?;
+    Ok(bytes)
+}
+
+/// Lists the files of a static asset, which is a file or a directory, along with where each is
+/// placed relative to the output directory: under the name of the asset, with the files inside
+/// a directory laid out as they are in it
+///
+/// # Returns
+/// The path to each file, and where it is placed, in order of where it is placed
+///
+/// # Errors
+/// Returns an error if the asset does not exist, or a directory inside it could not be read
+pub fn static_asset_files(asset: &Path) -> Result<Vec<(PathBuf, PathBuf)>, Error> {
+    let name = asset.file_name().map(PathBuf::from).ok_or_else(|| {
+        Error::new(ErrorKind::InvalidInput, format!("Static asset `{}` does not have a name", asset.display()))
+    })?;
+    if !asset.is_dir() {
+        fs::metadata(asset)?;
+        return Ok(vec![(asset.to_path_buf(), name)]);
+    }
+    let mut files = Vec::new();
+    let mut dirs = vec![(asset.to_path_buf(), name)];
+    while let Some((dir, dst)) = dirs.pop() {
+        for entry in list_directory(&dir, false)? {
+            let entry_dst = dst.join(entry.file_name().expect("directory entry to have a name"));
+            if entry.is_dir() {
+                dirs.push((entry, entry_dst));
+            } else {
+                files.push((entry, entry_dst));
+            }
+        }
+    }
+    files.sort_by(|(_, a), (_, b)| a.cmp(b));
+    Ok(files)
+}
+
+/// Finds the images in the given directory for an image slideshow, in natural order.
+/// Files that are not images are skipped
+///
+/// # Arguments
+/// * image_dir: The directory that contains the images
+/// * exclude: Paths to skip during discovery, along with anything inside them
+///
+/// # Errors
+/// Returns an error if the directory could not be read
+pub fn find_image_paths(image_dir: &Path, exclude: &[PathBuf]) -> Result<Vec<PathBuf>, Error> {
+    trace!("Finding images in {}", image_dir.display());
+    let images = find_slide_paths(image_dir, exclude)?
+        .into_iter()
+        .filter(|f| {
+            if !is_image(f) {
+                trace!("Skipping `{}` as it is not an image", f.display());
+            }
+            is_image(f)
+        })
+        .collect();
+    Ok(order_slides(images, OrderMode::Natural))
+}
+
+/// Attempts to find slides in the given directory, like `find_slides`,
+/// where each subdirectory is a vertical stack of the slides inside it.
+/// Subdirectories are ordered among the slides by their names, and are not searched any deeper
+///
+/// # Arguments
+/// * slide_dir: The directory that contains your slides
+/// * exclude: Paths to skip during discovery, along with anything inside them
+/// * options: Options that control how the slides are read
+///
+/// # Returns
+/// The slides in the given directory, with one stack per subdirectory that has any slides
+///
+/// # Errors
+/// Returns an error if the slide directory or any of its subdirectories could not be read
+pub fn find_slide_groups(
+    slide_dir: &Path,
+    exclude: &[PathBuf],
+    options: &SlideOptions,
+) -> Result<Vec<SlideGroup>, anyhow::Error> {
+    trace!("Finding slide groups in {}", slide_dir.display());
+    check_slide_dir(slide_dir)?;
+    let normalized_exclude = exclude.iter().map(|p| normalize_path(p)).collect::<Vec<PathBuf>>();
+    let entries = list_directory(slide_dir, false)?
+        .into_iter()
+        .filter(|e| {
+            let is_excluded = normalized_exclude.iter().any(|x| normalize_path(e).starts_with(x));
+            if is_excluded {
+                trace!("Skipping `{}` as it is excluded", e.display());
+            }
+            !is_excluded
+        })
+        .collect();
+    // the paths of each group, to be read all at once so that slides are numbered across groups
+    let mut group_paths = Vec::new();
+    for entry in order_slides(entries, OrderMode::NumericPrefix) {
+        if entry.is_dir() {
+            let stack = find_slide_paths(&entry, exclude)?;
+            if !stack.is_empty() {
+                group_paths.push((true, stack));
+            }
+        } else {
+            group_paths.push((false, vec![entry]));
+        }
+    }
+    let mut slides = SlideFile::from_paths(
+        group_paths.iter().flat_map(|(_, paths)| paths.clone()).collect(),
+        options,
+    )?
+    .into_iter();
+    Ok(group_paths
+        .into_iter()
+        .map(|(is_stack, paths)| {
+            let group = slides.by_ref().take(paths.len()).collect::<Vec<SlideFile>>();
+            if is_stack {
+                SlideGroup::Stack(group)
+            } else {
+                SlideGroup::Single(Box::new(group.into_iter().next().expect("group to have a slide")))
+            }
+        })
+        .collect())
+}
+
+/// Number of times `canonicalize_retry` attempts to canonicalize a path
+const CANONICALIZE_ATTEMPTS: usize = 3;
+/// How long `canonicalize_retry` waits between attempts
+const CANONICALIZE_RETRY_DELAY: Duration = Duration::from_millis(50);
+
+/// Canonicalizes a path, retrying a couple of times if it is not found.
+/// On some networked filesystems, a file may briefly not be found right after it is created
+///
+/// # Errors
+/// If the path still cannot be canonicalized after retrying
+pub fn canonicalize_retry(path: &Path) -> Result<PathBuf, Error> {
+    retry_io(CANONICALIZE_ATTEMPTS, CANONICALIZE_RETRY_DELAY, || {
+        fs::canonicalize(path)
+    })
+}
+
+/// Calls `f` until it succeeds, or until it has been attempted `attempts` times.
+/// Only `NotFound` errors are retried, since those are the ones that can be transient
+fn retry_io<T, F>(attempts: usize, delay: Duration, mut f: F) -> Result<T, Error>
+where
+    F: FnMut() -> Result<T, Error>,
+{
+    let mut attempt = 1;
+    loop {
+        match f() {
+            Err(e) if e.kind() == ErrorKind::NotFound && attempt < attempts => {
+                trace!("Attempt {} of {} failed: {}, retrying", attempt, attempts, e);
+                attempt += 1;
+                thread::sleep(delay);
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Canonicalizes the path if it exists, otherwise returns it unchanged
+pub fn normalize_path(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Returns the paths that slide discovery should skip so that generated output
+/// is never picked up as slides.
+///
+/// If the output directory is nested inside the slide directory, a warning is logged
+/// and the whole output directory is excluded.
+/// If they are the same directory, only the output file is excluded.
+pub fn output_exclusions(slide_dir: &Path, output_dir: &Path, output_filename: &Path) -> Vec<PathBuf> {
+    let slide_dir = normalize_path(slide_dir);
+    let output_dir = normalize_path(output_dir);
+    let mut exclusions = vec![output_dir.join(output_filename)];
+    if output_dir != slide_dir && output_dir.starts_with(&slide_dir) {
+        warn!(
+            file = %output_dir.display(),
+            "Output directory `{}` is inside the slide directory `{}`, its contents will not be treated as slides",
+            output_dir.display(),
+            slide_dir.display()
+        );
+        exclusions.push(output_dir);
+    }
+    exclusions
 }
 
 /// Lists a given directory
@@ -56,6 +457,59 @@ fn list_directory<Pth: AsRef<Path>>(path: Pth, only_files: bool) -> Result<Vec<P
     Ok(paths)
 }
 
+/// Returns the number of single character insertions, deletions or substitutions
+/// needed to turn one string into the other
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<char>>();
+    // distances from the prefix of `a` seen so far to every prefix of `b`
+    let mut row = (0..=b.len()).collect::<Vec<usize>>();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Finds the file in the given directory whose name is closest to the given one,
+/// if any is close enough to likely be what was meant
+///
+/// # Arguments
+/// * dir: The directory to look in
+/// * file_name: The file name that was not found
+///
+/// # Returns
+/// The name of the closest file, or `None` if the directory cannot be read or has no close match
+pub fn closest_file_name(dir: &Path, file_name: &str) -> Option<String> {
+    let max_distance = (file_name.chars().count() / 3).max(1);
+    list_directory(dir, true)
+        .ok()?
+        .iter()
+        .filter_map(|p| p.file_name()?.to_str().map(|f| f.to_string()))
+        .map(|f| (edit_distance(file_name, &f), f))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min()
+        .map(|(_, f)| f)
+}
+
+/// Builds the error for a slide file that does not exist,
+/// suggesting the closest file name in its directory if there is one
+pub fn missing_slide_error(path: &Path) -> ValidationError {
+    let suggestion = path
+        .parent()
+        .zip(path.file_name().and_then(|f| f.to_str()))
+        .and_then(|(dir, file_name)| closest_file_name(dir, file_name));
+    let reason = match suggestion {
+        Some(suggestion) => format!("File does not exist, did you mean `{}`?", suggestion),
+        None => "File does not exist".to_string(),
+    };
+    ValidationError::new(&path.display().to_string(), reason)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -73,6 +527,76 @@ mod test {
         assert!(!is_markdown_file(&definitely_not_md));
     }
 
+    #[test]
+    fn test_retry_io() {
+        let mut calls = 0;
+        let result = retry_io(3, Duration::ZERO, || {
+            calls += 1;
+            if calls < 3 {
+                Err(Error::from(ErrorKind::NotFound))
+            } else {
+                Ok(calls)
+            }
+        });
+        assert_eq!(result.unwrap(), 3);
+
+        let mut calls = 0;
+        let result: Result<(), Error> = retry_io(3, Duration::ZERO, || {
+            calls += 1;
+            Err(Error::from(ErrorKind::NotFound))
+        });
+        assert_eq!(result.unwrap_err().kind(), ErrorKind::NotFound);
+        assert_eq!(calls, 3);
+
+        // other errors are not transient, so they are not retried
+        let mut calls = 0;
+        let result: Result<(), Error> = retry_io(3, Duration::ZERO, || {
+            calls += 1;
+            Err(Error::from(ErrorKind::PermissionDenied))
+        });
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn test_canonicalize_retry() {
+        let dir = tempdir().unwrap();
+        let expected = fs::canonicalize(dir.path()).unwrap();
+        assert_eq!(canonicalize_retry(dir.path()).unwrap(), expected);
+        assert!(canonicalize_retry(&dir.path().join("missing")).is_err());
+    }
+
+    #[test]
+    fn test_has_extension() {
+        let extensions = vec!["md".to_string(), ".mdx".to_string()];
+        assert!(has_extension(Path::new("/a/file.md"), &extensions));
+        assert!(has_extension(Path::new("/a/file.MDX"), &extensions));
+        assert!(!has_extension(Path::new("/a/file.txt"), &extensions));
+        assert!(!has_extension(Path::new("/a/file"), &extensions));
+    }
+
+    #[test]
+    fn test_find_slides_with_extensions() {
+        let slides_dir = tempdir().unwrap();
+        let slides_dir = fs::canonicalize(slides_dir.path()).unwrap();
+        let slide_file_1 = slides_dir.join("1_slide1.mdx");
+        let slide_file_2 = slides_dir.join("2_slide2.md");
+        File::create(&slide_file_1).unwrap();
+        File::create(&slide_file_2).unwrap();
+
+        assert!(find_slides(&slides_dir, &[], &SlideOptions::default()).is_err());
+
+        let options = SlideOptions {
+            extensions: vec!["md".to_string(), "mdx".to_string()],
+            ..SlideOptions::default()
+        };
+        let slides = find_slides(&slides_dir, &[], &options).unwrap();
+        assert_eq!(
+            slides.iter().map(|s| s.path.clone()).collect::<Vec<PathBuf>>(),
+            vec![slide_file_1, slide_file_2]
+        );
+    }
+
     #[test]
     fn test_find_included_slides() {
         let slides_dir = tempdir().unwrap();
@@ -83,7 +607,7 @@ mod test {
         File::create(&slide_file_1).unwrap();
         File::create(&slide_file_2).unwrap();
         File::create(&slide_file_3).unwrap();
-        let slides = find_slides(&slides_dir).unwrap();
+        let slides = find_slides(&slides_dir, &[], &SlideOptions::default()).unwrap();
         assert_eq!(
             slides,
             vec![
@@ -101,10 +625,160 @@ mod test {
         let bad_slide_file = slides_dir.path().join("slide2_2.txt");
         File::create(&good_slide_file).unwrap();
         File::create(&bad_slide_file).unwrap();
-        let slides = find_slides(&slides_dir.into_path());
+        let slides = find_slides(&slides_dir.into_path(), &[], &SlideOptions::default());
         assert!(slides.is_err());
     }
 
+    #[test]
+    fn test_find_slides_lenient() {
+        let slides_dir = tempdir().unwrap();
+        let slides_dir = fs::canonicalize(slides_dir.path()).unwrap();
+        let good_slide_file = slides_dir.join("1_slide1.md");
+        let bad_slide_file = slides_dir.join("2_slide2.md");
+        fs::write(&good_slide_file, "Slide 1").unwrap();
+        fs::write(&bad_slide_file, b"Slide \xff\xfe").unwrap();
+        #[cfg(unix)]
+        {
+            use std::ffi::OsStr;
+            use std::os::unix::ffi::OsStrExt;
+            fs::write(slides_dir.join(OsStr::from_bytes(b"3_\xffslide3.md")), "Slide 3").unwrap();
+        }
+
+        assert!(find_slides(&slides_dir, &[], &SlideOptions::default()).is_err());
+        let slides = find_slides_lenient(&slides_dir, &[], &SlideOptions::default()).unwrap();
+        assert_eq!(slides, vec![SlideFile::read_and_parse(&good_slide_file).unwrap()]);
+
+        // slides that can be read but are not valid still fail
+        fs::write(&bad_slide_file, "---\nauto_slide_ms: soon\n---\nSlide 2").unwrap();
+        assert!(find_slides_lenient(&slides_dir, &[], &SlideOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_expand_include_file() {
+        let slides_dir = tempdir().unwrap();
+        let slides_dir = slides_dir.path().canonicalize().unwrap();
+        let chapter = slides_dir.join("chapter1");
+        fs::create_dir(&chapter).unwrap();
+        for name in ["10_end.md", "2_middle.md", "1_start.md", "notes.txt"] {
+            File::create(chapter.join(name)).unwrap();
+        }
+
+        assert_eq!(
+            expand_include_file(&slides_dir, Path::new("chapter1/*.md")).unwrap(),
+            vec![
+                chapter.join("1_start.md"),
+                chapter.join("2_middle.md"),
+                chapter.join("10_end.md")
+            ]
+        );
+        assert_eq!(
+            expand_include_file(&slides_dir, Path::new("chapter1/notes.txt")).unwrap(),
+            vec![chapter.join("notes.txt")]
+        );
+        assert_eq!(
+            expand_include_file(&slides_dir, Path::new("chapter2/*.md")).unwrap_err().value,
+            "chapter2/*.md"
+        );
+        assert!(expand_include_file(&slides_dir, Path::new("chapter1/[.md")).is_err());
+    }
+
+    #[test]
+    fn test_find_slides_in_missing_dir() {
+        let slides_dir = tempdir().unwrap();
+        let missing = slides_dir.path().join("sildes");
+        let err = find_slides(&missing, &[], &SlideOptions::default()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("Slide directory `{}` does not exist (configured in `slide_dir`)", missing.display())
+        );
+        let err = find_slide_groups(&missing, &[], &SlideOptions::default()).unwrap_err();
+        assert!(err.to_string().contains(&missing.display().to_string()));
+
+        let file = slides_dir.path().join("1_slide1.md");
+        File::create(&file).unwrap();
+        let err = find_slide_paths(&file, &[]).unwrap_err();
+        assert!(err.to_string().contains("is not a directory"));
+    }
+
+    #[test]
+    fn test_find_slide_groups() {
+        let slides_dir = tempdir().unwrap();
+        let slides_dir = fs::canonicalize(slides_dir.path()).unwrap();
+        fs::create_dir_all(slides_dir.join("2_stack")).unwrap();
+        fs::create_dir_all(slides_dir.join("4_empty")).unwrap();
+        fs::create_dir_all(slides_dir.join("output")).unwrap();
+        for f in ["1_intro.md", "2_stack/1_a.md", "2_stack/2_b.md", "3_outro.md", "output/index.md"] {
+            File::create(slides_dir.join(f)).unwrap();
+        }
+
+        let groups = find_slide_groups(&slides_dir, &[slides_dir.join("output")], &SlideOptions::default()).unwrap();
+        let read = |f: &str| SlideFile::read_and_parse(slides_dir.join(f)).unwrap();
+        assert_eq!(
+            groups,
+            vec![
+                SlideGroup::Single(Box::new(read("1_intro.md"))),
+                SlideGroup::Stack(vec![read("2_stack/1_a.md"), read("2_stack/2_b.md")]),
+                SlideGroup::Single(Box::new(read("3_outro.md"))),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("slide.md", "slide.md"), 0);
+        assert_eq!(edit_distance("slide.md", "silde.md"), 2);
+        assert_eq!(edit_distance("1_intro.md", "1_intro.mdx"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn test_closest_file_name() {
+        let slides_dir = tempdir().unwrap();
+        File::create(slides_dir.path().join("1_intro.md")).unwrap();
+        File::create(slides_dir.path().join("2_outro.md")).unwrap();
+        assert_eq!(
+            closest_file_name(slides_dir.path(), "1_inrto.md"),
+            Some("1_intro.md".to_string())
+        );
+        assert_eq!(
+            closest_file_name(slides_dir.path(), "2_outro.markdown"),
+            None
+        );
+        assert_eq!(closest_file_name(&slides_dir.path().join("missing"), "1_intro.md"), None);
+    }
+
+    #[test]
+    fn test_find_slides_skips_excluded() {
+        let slides_dir = tempdir().unwrap();
+        let slides_dir = fs::canonicalize(slides_dir.path()).unwrap();
+        let slide_file = slides_dir.join("1_slide1.md");
+        File::create(&slide_file).unwrap();
+        File::create(slides_dir.join("index.html")).unwrap();
+
+        let exclude = output_exclusions(&slides_dir, &slides_dir, Path::new("index.html"));
+        let slides = find_slides(&slides_dir, &exclude, &SlideOptions::default()).unwrap();
+        assert_eq!(slides, vec![SlideFile::read_and_parse(slide_file).unwrap()]);
+    }
+
+    #[test]
+    fn test_output_exclusions() {
+        let slide_dir = PathBuf::from("/nonexistent/slides");
+        assert_eq!(
+            output_exclusions(&slide_dir, &slide_dir.join("out"), Path::new("index.html")),
+            vec![slide_dir.join("out/index.html"), slide_dir.join("out")]
+        );
+        assert_eq!(
+            output_exclusions(&slide_dir, &slide_dir, Path::new("index.html")),
+            vec![slide_dir.join("index.html")]
+        );
+        assert_eq!(
+            output_exclusions(&slide_dir, Path::new("/nonexistent/out"), Path::new("index.html")),
+            vec![PathBuf::from("/nonexistent/out/index.html")]
+        );
+    }
+
     #[test]
     #[cfg(target_os = "windows")]
     fn test_grab_file_names_from_path_bufs_windows() {
@@ -1,35 +1,222 @@
-use crate::errors::ArgumentError;
-use io::find_slides;
-use crate::presentation::slide::SlideFile;
+use crate::errors::{ArgumentError, PresentationError};
+use anyhow::{anyhow, bail};
+use pulldown_cmark::escape::escape_href;
+use builder::PresentationConfigBuilder;
+use check::check_unique_titles;
+use filters::{render_in_dir, render_one_off};
+use incremental::{make_rule, modified_since};
+use remote::{download_image, DownloadedImage};
+use reveal::{
+    fetch_reveal, read_reveal_files, RevealConfig, RevealFile, RevealOptions, REVEAL_CACHE_DIR,
+    ViewMode, REVEAL_THEMES, SLIDE_NUMBER_FORMATS,
+};
+use thumbnails::{
+    find_browser, overview_html, render_contact_sheet, render_thumbnail, slide_url, BROWSER_ENV_VAR,
+    CONTACT_SHEET_FILE, OVERVIEW_FILE, THUMBNAIL_DIR,
+};
+use io::{
+    append_discovered, canonicalize_retry, expand_include_file, find_image_paths, find_slide_groups, find_slide_paths,
+    copy_file, find_slides_lenient, is_stdout, is_up_to_date, missing_slide_error, static_asset_files,
+    output_exclusions, zip_dir,
+};
+use crate::presentation::slide::cache::SlideCache;
+use crate::presentation::slide::front_matter::looks_like_color;
+use crate::presentation::slide::{ImageLayout, SlideFile, SlideGroup, SlideOptions, DEFAULT_IMAGE_ROOT};
 use crate::ui::cli::{CliArgs, Commands};
-use crate::ui::conf::PresentationConfigFile;
-
-
-use std::path::PathBuf;
+use crate::ui::color::{color_enabled, success};
+use crate::ui::conf::{
+    find_default_config, ContextCollision, OutputTarget,
+    PresentationConfigFile, DEFAULT_CONFIG_FILES,
+};
+use chrono::{DateTime, Locale, Utc};
+use rayon::prelude::*;
+use serde::Serialize;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::Write;
+use std::ops::Range;
+use std::ffi::OsStr;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use std::{env, fs};
-use tera::Tera;
 use tracing::{debug, trace, warn};
 
 /// Utilities to work with Slides
 pub mod slide;
 /// Functions that work with the disk
 pub mod io;
+/// Ordering of slides
+pub mod ordering;
+/// Building presentations in parallel
+pub mod batch;
+/// Downloading remote images
+pub mod remote;
+/// Skipping builds when nothing has changed
+pub mod incremental;
+/// Checking presentations without building them
+pub mod check;
+/// Bundling reveal.js with presentations
+pub mod reveal;
+/// Rendering thumbnails of slides with a headless browser
+pub mod thumbnails;
+/// Rebuilding presentations when their files change
+pub mod watch;
+/// Filters available to templates
+pub mod filters;
+/// Building presentations in code, without a config file
+pub mod builder;
+
+/// The template used when no template file is configured.
+/// It loads reveal.js from a CDN
+pub const DEFAULT_TEMPLATE: &str = include_str!("../../templates/slides.html");
+
+/// Largest number of images copied at once. Each copy holds two files open,
+/// so this keeps a presentation with many images well within the open file limit
+/// however many threads there are
+const MAX_CONCURRENT_COPIES: usize = 64;
+
+/// Placed between the title and its suffix in the `page_title` template variable unless configured otherwise
+pub const DEFAULT_TITLE_SEPARATOR: &str = " — ";
+
+/// Names of the variables that are always provided to the template
+const BUILTIN_CONTEXT_VARS: [&str; 11] = [
+    "slide_title",
+    "page_title",
+    "subtitle",
+    "ingested_files",
+    "slides",
+    "slides_html",
+    "build_date",
+    "theme",
+    "config_path",
+    "reveal_path",
+    "reveal_config",
+];
+
+/// A slide as exposed to the template in the `slides` context variable
+#[derive(Debug, Serialize)]
+struct SlideContext<'a> {
+    /// Rendered HTML of the slide. Empty for a vertical stack
+    contents: &'a str,
+    /// Space-prefixed attributes to place on the slide's `<section>`
+    attributes: String,
+    /// Transition of the slide, from its front matter or its `include_files` entry
+    transition: Option<&'a str>,
+    /// Background color of the slide, from its front matter or its `include_files` entry
+    background: Option<&'a str>,
+    /// Slides of the vertical stack, if this is one
+    stack: Vec<SlideContext<'a>>,
+}
+
+impl SlideContext<'_> {
+    /// Returns the `<section>` of this slide, with one nested `<section>` per slide if it is a stack
+    fn to_html(&self, slide_join: &str) -> String {
+        if self.stack.is_empty() {
+            return format!("<section{}>{}</section>", self.attributes, self.contents);
+        }
+        let stack = self
+            .stack
+            .iter()
+            .map(|s| s.to_html(slide_join))
+            .collect::<Vec<String>>()
+            .join(slide_join);
+        format!("<section>{}</section>", stack)
+    }
+}
 
 /// The logical representation of a presentation configuration
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct PresentationConfig {
     /// Title of the presentation
     pub title: String,
+    /// Subtitle of the presentation, shown on the title slide
+    pub subtitle: Option<String>,
+    /// Adds a slide with the title and subtitle before all others
+    pub title_slide: bool,
+    /// Appended to the title, after `title_separator`, in the `page_title` template variable
+    pub title_suffix: Option<String>,
+    /// Placed between the title and `title_suffix`. `DEFAULT_TITLE_SEPARATOR` if not set
+    pub title_separator: Option<String>,
     /// Output directory of the presentation.
     /// Does not need to exist if using `package()`
     pub output_dir: PathBuf,
-    /// Output filename of the final presentation file, with extension
+    /// Creates `output_dir` when packaging if it does not exist. If false, it must exist already
+    pub create_output_dir: bool,
+    /// Fails validation if the output file exists already, instead of overwriting it
+    pub no_overwrite: bool,
+    /// Output filename of the final presentation file, with extension.
+    /// The first of `outputs` if there are any
     pub output_filename: PathBuf,
-    /// Absolute path to the template file
-    pub template_file: PathBuf,
+    /// Absolute path to the template file.
+    /// The built-in template is used if this is not set
+    pub template_file: Option<PathBuf>,
+    /// Absolute path to a directory of templates, which `template_file` and the templates
+    /// of `outputs` must be in. They are loaded together so that templates can include and
+    /// extend each other. Each template is rendered on its own if this is not set
+    pub template_dir: Option<PathBuf>,
+    /// Files to render the slides into, each with the absolute path to its template.
+    /// Only `output_filename` is rendered, with `template_file`, if empty
+    pub outputs: Vec<OutputTarget>,
     /// Slides to be included in the presentation
     /// in the order that they appear in
     pub slides: Vec<SlideFile>,
+    /// Ranges of `slides` that are vertical stacks, in order
+    pub stacks: Vec<Range<usize>>,
+    /// Largest size in bytes that any single referenced image may have
+    pub max_image_bytes: Option<u64>,
+    /// Themes to package the presentation with, each into its own
+    /// subdirectory of `output_dir`. Packages once without a theme if empty
+    pub themes: Vec<String>,
+    /// Absolute paths to files and directories that are copied as they are into the output
+    /// directory, each under its own name
+    pub static_assets: Vec<PathBuf>,
+    /// Theme of reveal.js in the `theme` template variable when packaging without `themes`
+    pub theme: String,
+    /// Suppresses informational output, such as the build summary
+    pub quiet: bool,
+    /// Disables colored output, which is otherwise used when output goes to a terminal
+    pub no_color: bool,
+    /// Starts each slide with an HTML comment naming the file it was made from
+    pub source_comments: bool,
+    /// Renders the presentation when packaging, but prints what would be written instead of writing it
+    pub dry_run: bool,
+    /// String placed between slides in the `slides_html` template variable
+    pub slide_join: String,
+    /// Locale used to format `build_date`, such as `de_DE`.
+    /// `build_date` is formatted as an ISO-8601 date if unset
+    pub locale: Option<String>,
+    /// Additional variables to make available to the template
+    pub extra_context: BTreeMap<String, serde_yaml::Value>,
+    /// What to do when a key in `extra_context` has the same name as a built-in variable
+    pub context_collision: ContextCollision,
+    /// Absolute path to the config file this presentation was configured by, if any
+    pub config_path: Option<PathBuf>,
+    /// Turns likely misconfigurations, such as an empty template, into errors instead of warnings
+    pub strict: bool,
+    /// Options the slides were read with
+    pub slide_options: SlideOptions,
+    /// Renders each slide as a template before it is placed in the presentation template
+    pub render_slides: bool,
+    /// Fails validation if any slide references an image that is not bundled, such as an `https://` one
+    pub no_remote_images: bool,
+    /// Downloads remote images when packaging and bundles them like local ones
+    pub download_remote_images: bool,
+    /// Minifies the rendered presentation before it is written,
+    /// keeping whitespace inside `<pre>` blocks
+    pub minify: bool,
+    /// How reveal.js is made available to the presentation
+    pub reveal: RevealOptions,
+    /// Options passed to reveal.js, such as the size of the presentation
+    pub reveal_config: RevealConfig,
+    /// Bundles the output directory into a zip archive named after `output_filename`
+    /// once the presentation is packaged
+    pub zip_output: bool,
+    /// Copies the rest of the images when one cannot be copied, failing once the presentation is packaged
+    pub continue_on_copy_error: bool,
+    /// Renders a thumbnail of every slide with a headless browser when packaging,
+    /// along with an overview page that shows them all
+    pub thumbnails: bool,
 }
 
 impl PresentationConfig {
@@ -37,94 +224,1640 @@ impl PresentationConfig {
     /// In particular, it checks that any paths
     /// specified are valid, and those that need to be
     /// accessed can be accessed.
-    fn validate(&self) -> Result<(), ArgumentError> {
+    fn validate(&self) -> Result<(), PresentationError> {
         trace!("Validating PresentationConfig");
         trace!("Checking output_file");
         // todo:
+        if self.output_filename.as_os_str().is_empty() {
+            return Err(ArgumentError::new(
+                "output_file".to_string(),
+                "",
+                "Output file must be set unless `outputs` is".to_string(),
+            )
+            .into());
+        }
 
         let output_file = self.output_dir.join(&self.output_filename);
 
         // does it exist and is it a file?
         if output_file.is_file() {
+            if self.no_overwrite {
+                return Err(ArgumentError::new(
+                    "output_file".to_string(),
+                    output_file.to_str().unwrap_or("<invalid path>"),
+                    "Output file already exists, and `no_overwrite` is set".to_string(),
+                )
+                .into());
+            }
             // if it exists, we will warn about overwriting it
             warn!(
+                file = %output_file.display(),
                 "Output file at `{}` already exists, will overwrite",
                 output_file.display()
             );
         }
         trace!("Checking template_file");
-        if !self.template_file.is_absolute() {
+        let targets = self.output_targets();
+        if let Some(template_dir) = &self.template_dir {
+            if !template_dir.is_dir() {
+                return Err(ArgumentError::new(
+                    "template_dir".to_string(),
+                    template_dir.to_str().unwrap_or("<invalid path>"),
+                    "Template directory does not exist".to_string(),
+                )
+                .into());
+            }
+            if targets.iter().any(|t| t.template_file.is_none()) {
+                return Err(ArgumentError::new(
+                    "template_dir".to_string(),
+                    template_dir.to_str().unwrap_or("<invalid path>"),
+                    "`template_file` must name the template in `template_dir` to render".to_string(),
+                )
+                .into());
+            }
+        }
+        for template_file in targets.iter().filter_map(|t| t.template_file.as_ref()) {
+            if !template_file.is_absolute() {
+                return Err(ArgumentError::new(
+                    "template_file".to_string(),
+                    template_file.to_str().unwrap_or("<invalid path>"),
+                    "Template file must be an absolute path".to_string(),
+                )
+                .into());
+            }
+
+            if !template_file.is_file() {
+                return Err(PresentationError::MissingTemplate {
+                    path: template_file.clone(),
+                });
+            }
+            if let Some(template_dir) = &self.template_dir {
+                if template_entry_name(template_dir, template_file).is_none() {
+                    return Err(ArgumentError::new(
+                        "template_file".to_string(),
+                        template_file.to_str().unwrap_or("<invalid path>"),
+                        "Template file must be an `.html` file inside `template_dir`".to_string(),
+                    )
+                    .into());
+                }
+            }
+
+            let template = fs::read_to_string(template_file).map_err(|e| {
+                ArgumentError::new(
+                    "template_file".to_string(),
+                    template_file.to_str().unwrap_or("<invalid path>"),
+                    format!("Template file cannot be read: {}", e),
+                )
+            })?;
+            if template.trim().is_empty() {
+                if self.strict {
+                    return Err(ArgumentError::new(
+                        "template_file".to_string(),
+                        template_file.to_str().unwrap_or("<invalid path>"),
+                        "Template file is empty".to_string(),
+                    )
+                    .into());
+                }
+                warn!(
+                    file = %template_file.display(),
+                    "Template file `{}` is empty, the presentation will be empty too",
+                    template_file.display()
+                );
+            } else if self.template_dir.is_none() {
+                // a template in a directory may leave variables to the templates it extends or includes
+                let missing = missing_template_variables(&template);
+                if !missing.is_empty() {
+                    let reason = format!(
+                        "Template file does not use {}, so the presentation will leave it out",
+                        missing.join(" or ")
+                    );
+                    if self.strict {
+                        return Err(ArgumentError::new(
+                            "template_file".to_string(),
+                            template_file.to_str().unwrap_or("<invalid path>"),
+                            reason,
+                        )
+                        .into());
+                    }
+                    warn!(file = %template_file.display(), "`{}`: {}", template_file.display(), reason);
+                }
+            }
+        }
+
+        trace!("Checking static_assets");
+        for asset in &self.static_assets {
+            if !asset.exists() {
+                return Err(ArgumentError::new(
+                    "static_assets".to_string(),
+                    asset.to_str().unwrap_or("<invalid path>"),
+                    "Static asset does not exist".to_string(),
+                )
+                .into());
+            }
+        }
+
+        if let Some(max_image_bytes) = self.max_image_bytes {
+            trace!("Checking image sizes against {} bytes", max_image_bytes);
+            for slide in &self.slides {
+                for (img_src_path, _) in &slide.local_images {
+                    let img_size = fs::metadata(img_src_path)
+                        .map_err(|e| {
+                            ArgumentError::new(
+                                "max_image_bytes".to_string(),
+                                &max_image_bytes.to_string(),
+                                format!(
+                                    "Could not read size of image `{}`: {}",
+                                    img_src_path.display(),
+                                    e
+                                ),
+                            )
+                        })?
+                        .len();
+                    if img_size > max_image_bytes {
+                        return Err(ArgumentError::new(
+                            "max_image_bytes".to_string(),
+                            &max_image_bytes.to_string(),
+                            format!(
+                                "Image `{}` referenced in `{}` is {} bytes, which exceeds the limit",
+                                img_src_path.display(),
+                                slide.path.display(),
+                                img_size
+                            ),
+                        )
+                        .into());
+                    }
+                }
+            }
+        }
+
+        trace!("Checking presentation size");
+        for (name, value) in [
+            ("width", self.reveal_config.width),
+            ("height", self.reveal_config.height),
+            ("margin", self.reveal_config.margin),
+        ] {
+            if let Some(value) = value {
+                // also rejects NaN
+                if !(value > 0.0 && value.is_finite()) {
+                    return Err(ArgumentError::new(
+                        name.to_string(),
+                        &value.to_string(),
+                        format!("`{}` must be a positive number", name),
+                    )
+                    .into());
+                }
+            }
+        }
+
+        let themes = std::iter::once(("theme", &self.theme)).chain(self.themes.iter().map(|t| ("themes", t)));
+        for (key, theme) in themes {
+            if !REVEAL_THEMES.contains(&theme.as_str()) {
+                return Err(ArgumentError::new(
+                    key.to_string(),
+                    theme,
+                    format!("Theme must be one of the themes of reveal.js: {}", REVEAL_THEMES.join(", ")),
+                )
+                .into());
+            }
+        }
+
+        if let Some(slide_separator) = &self.slide_options.slide_separator {
+            if slide_separator.trim().is_empty() {
+                return Err(ArgumentError::new(
+                    "slide_separator".to_string(),
+                    slide_separator,
+                    "Slide separator must not be empty".to_string(),
+                )
+                .into());
+            }
+        }
+
+        if let Some(slide_number) = &self.reveal_config.slide_number {
+            if !slide_number.is_valid() {
+                return Err(ArgumentError::new(
+                    "slide_numbers".to_string(),
+                    &slide_number.to_string(),
+                    format!(
+                        "Slide numbers must be `true`, `false` or one of the formats: {}",
+                        SLIDE_NUMBER_FORMATS.join(", ")
+                    ),
+                )
+                .into());
+            }
+        }
+
+        trace!("Checking reveal_version");
+        if !self.reveal.is_valid_version() {
             return Err(ArgumentError::new(
-                "template_file".to_string(),
-                self.template_file.to_str().unwrap_or("<invalid path>"),
-                "Template file must be an absolute path".to_string(),
-            ));
+                "reveal_version".to_string(),
+                &self.reveal.version,
+                "Version must only contain letters, digits, `.` and `-`, and may not start with `.`".to_string(),
+            )
+            .into());
+        }
+
+        if self.reveal_config.view == ViewMode::Scroll {
+            let major = self.reveal.version.split('.').next().and_then(|v| v.parse::<u32>().ok());
+            if matches!(major, Some(major) if major < 5) {
+                return Err(ArgumentError::new(
+                    "view_mode".to_string(),
+                    "scroll",
+                    format!(
+                        "The scroll view needs reveal.js 5 or later, but `reveal_version` is {}",
+                        self.reveal.version
+                    ),
+                )
+                .into());
+            }
         }
 
-        if !self.template_file.is_file() {
+        trace!("Checking image_root");
+        let image_root = &self.slide_options.image_root;
+        if image_root.is_absolute() || image_root.components().any(|c| c == Component::ParentDir) {
             return Err(ArgumentError::new(
-                "template_file".to_string(),
-                self.template_file.to_str().unwrap_or("<invalid path>"),
-                "Template file does not exist or cannot be read".to_string(),
-            ));
+                "image_root".to_string(),
+                &image_root.display().to_string(),
+                "Image root must be a directory inside the output directory".to_string(),
+            )
+            .into());
+        }
+
+        trace!("Checking image destinations");
+        self.check_image_collisions()?;
+
+        trace!("Checking slide front matter");
+        for slide in &self.slides {
+            if let Some(background_color) = &slide.meta.background_color {
+                if !looks_like_color(background_color) {
+                    warn!(
+                        file = %slide.path.display(),
+                        "`background_color` of `{}` does not look like a color: `{}`",
+                        slide.path.display(),
+                        background_color
+                    );
+                }
+            }
+        }
+
+        // remote images are checked again once they have been downloaded
+        if !self.download_remote_images {
+            self.check_no_remote_images()?;
+        }
+
+        trace!("Checking locale");
+        build_date(Utc::now(), self.locale.as_deref())?;
+
+        trace!("Checking extra_context");
+        if self.context_collision == ContextCollision::Error {
+            if let Some(key) = self.colliding_context_keys().next() {
+                return Err(ArgumentError::new(
+                    "extra_context".to_string(),
+                    key,
+                    "Key is reserved for a built-in template variable".to_string(),
+                )
+                .into());
+            }
         }
         Ok(())
     }
 
+    /// Fails if different images of different slides would be placed at the same path,
+    /// as can happen with an `img_path_template` that does not tell slides apart, or with
+    /// the `mirror` image layout when slides in different directories link to images the same way.
+    /// Same-named images of one slide are told apart while it is read, and images placed
+    /// according to a hash of their contents only share a path if their contents are the same
+    fn check_image_collisions(&self) -> Result<(), ArgumentError> {
+        let (arg, value) = match (&self.slide_options.img_path_template, self.slide_options.image_layout) {
+            (Some(img_path_template), _) => ("img_path_template", img_path_template.as_str()),
+            (None, ImageLayout::Mirror) => ("image_layout", "mirror"),
+            (None, ImageLayout::Hashed) => return Ok(()),
+        };
+        let mut sources: HashMap<&PathBuf, (&PathBuf, &SlideFile)> = HashMap::new();
+        for slide in &self.slides {
+            for (img_src_path, img_dst_path) in &slide.local_images {
+                match sources.get(img_dst_path) {
+                    Some((other_src_path, other_slide)) if *other_src_path != img_src_path => {
+                        return Err(ArgumentError::new(
+                            arg.to_string(),
+                            value,
+                            format!(
+                                "`{}` referenced in `{}` and `{}` referenced in `{}` would both be placed at `{}`",
+                                other_src_path.display(),
+                                other_slide.path.display(),
+                                img_src_path.display(),
+                                slide.path.display(),
+                                img_dst_path.display()
+                            ),
+                        ));
+                    }
+                    Some(_) => {}
+                    None => {
+                        sources.insert(img_dst_path, (img_src_path, slide));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Fails if `no_remote_images` is set and any slide references a remote image
+    fn check_no_remote_images(&self) -> Result<(), ArgumentError> {
+        if !self.no_remote_images {
+            return Ok(());
+        }
+        trace!("Checking for remote images");
+        for slide in &self.slides {
+            if let Some(url) = slide.remote_images.first() {
+                return Err(ArgumentError::new(
+                    "no_remote_images".to_string(),
+                    url,
+                    format!(
+                        "Slide `{}` references a remote image, which is not bundled",
+                        slide.path.display()
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the keys of `extra_context` that have the same name as a built-in variable
+    fn colliding_context_keys(&self) -> impl Iterator<Item = &String> {
+        self.extra_context
+            .keys()
+            .filter(|k| BUILTIN_CONTEXT_VARS.contains(&k.as_str()))
+    }
+
+    /// Serializes the fully resolved configuration to YAML
+    ///
+    /// # Returns
+    /// The configuration with absolute paths and the resolved slide list
+    ///
+    /// # Errors
+    /// If the configuration cannot be serialized
+    pub fn to_yaml(&self) -> Result<String, serde_yaml::Error> {
+        serde_yaml::to_string(self)
+    }
+
+    /// Returns the files the presentation is rendered into: `outputs`,
+    /// or else `output_filename` rendered with `template_file`
+    pub fn output_targets(&self) -> Cow<'_, [OutputTarget]> {
+        if self.outputs.is_empty() {
+            Cow::Owned(vec![OutputTarget {
+                template_file: self.template_file.clone(),
+                output_file: self.output_filename.clone(),
+            }])
+        } else {
+            Cow::Borrowed(&self.outputs)
+        }
+    }
+
+    /// Reads a template the presentation is rendered with
+    ///
+    /// # Returns
+    /// The contents of `template_file`, or the built-in template if it is not set
+    ///
+    /// # Errors
+    /// If the template file cannot be read
+    fn read_template(template_file: Option<&Path>) -> Result<Cow<'static, str>, std::io::Error> {
+        match template_file {
+            Some(template_file) => Ok(Cow::Owned(fs::read_to_string(template_file)?)),
+            None => Ok(Cow::Borrowed(DEFAULT_TEMPLATE)),
+        }
+    }
+
+    /// Renders the presentation into a string, as `package()` would write it, without writing anything.
+    /// Images are not copied, or downloaded, so slides link to where `package()` would place them.
+    /// If there are several `outputs` or `themes`, the first of each is used
+    ///
+    /// # Errors
+    /// If the template cannot be read, or it or a slide cannot be rendered
+    pub fn render_to_string(&self) -> Result<String, PresentationError> {
+        let targets = self.output_targets();
+        let theme = self.themes.first().map(String::as_str);
+        let template_file = targets.first().and_then(|t| t.template_file.as_deref());
+        Ok(self.minified(self.render(theme, template_file, &mut |_| {})?))
+    }
+
     /// Renders the presentation into a string
     ///
+    /// # Arguments
+    /// * `theme` - The theme to expose to the template as `theme`, if any
+    /// * `template_file` - The template to render, the built-in one if not set
+    /// * `progress` - Called with the progress of rendering
+    ///
     /// # Returns
     /// Returns the contents of the presentation as a String
     ///
     /// # Errors
-    /// If the template engine fails to render the presentation.
-    fn render(&self) -> Result<String, tera::Error> {
+    /// If the template engine fails to render the presentation, naming the template,
+    /// or a slide, naming the slide file, if `render_slides` is set
+    fn render(
+        &self,
+        theme: Option<&str>,
+        template_file: Option<&Path>,
+        progress: &mut dyn FnMut(BuildEvent),
+    ) -> Result<String, tera::Error> {
         let mut ctx = tera::Context::new();
-        let template = fs::read_to_string(&self.template_file)?;
 
-        let slide_contents = self
-            .slides
-            .iter()
-            .map(| s| &s.contents)
-            .collect::<Vec<&String>>();
         ctx.insert("slide_title", &self.title);
+        ctx.insert("page_title", &self.page_title());
+        if let Some(subtitle) = &self.subtitle {
+            ctx.insert("subtitle", subtitle);
+        }
+        let build_date = build_date(Utc::now(), self.locale.as_deref())
+            .map_err(|e| tera::Error::msg(e.to_string()))?;
+        ctx.insert("build_date", &build_date);
+        ctx.insert("theme", theme.unwrap_or(&self.theme));
+        if let Some(config_path) = &self.config_path {
+            ctx.insert("config_path", config_path);
+        }
+        ctx.insert("reveal_path", &self.reveal.base_path());
+        ctx.insert("reveal_config", &self.reveal_config.to_json());
+        for (key, value) in &self.extra_context {
+            if BUILTIN_CONTEXT_VARS.contains(&key.as_str()) {
+                match self.context_collision {
+                    ContextCollision::Error => {
+                        return Err(tera::Error::msg(format!(
+                            "extra_context key `{}` is reserved for a built-in template variable",
+                            key
+                        )))
+                    }
+                    ContextCollision::Warn => {
+                        let config_path = self.config_path.as_ref().map(|p| p.display().to_string());
+                        warn!(
+                            file = config_path.as_deref(),
+                            "extra_context key `{}` is reserved for a built-in template variable, ignoring it",
+                            key
+                        );
+                        continue;
+                    }
+                    ContextCollision::Override => {
+                        debug!("extra_context key `{}` overrides a built-in template variable", key)
+                    }
+                }
+            }
+            ctx.insert(key.as_str(), value);
+        }
+
+        let slide_contents = self.render_slides(&ctx, progress)?;
+        let slide_context = |i: usize| SlideContext {
+            contents: &slide_contents[i],
+            attributes: self.slides[i].meta.section_attributes(),
+            transition: self.slides[i].meta.transition.as_deref(),
+            background: self.slides[i].meta.background_color.as_deref(),
+            stack: Vec::new(),
+        };
+        let title_slide_html = self.title_slide_html();
+        let mut slides = Vec::new();
+        if self.title_slide {
+            slides.push(SlideContext {
+                contents: &title_slide_html,
+                attributes: String::new(),
+                transition: None,
+                background: None,
+                stack: Vec::new(),
+            });
+        }
+        let mut i = 0;
+        while i < self.slides.len() {
+            match self.stacks.iter().find(|stack| stack.start == i) {
+                Some(stack) => {
+                    slides.push(SlideContext {
+                        contents: "",
+                        attributes: String::new(),
+                        transition: None,
+                        background: None,
+                        stack: stack.clone().map(slide_context).collect(),
+                    });
+                    i = stack.end;
+                }
+                None => {
+                    slides.push(slide_context(i));
+                    i += 1;
+                }
+            }
+        }
+        let slides_html = slides
+            .iter()
+            .map(|s| s.to_html(&self.slide_join))
+            .collect::<Vec<String>>()
+            .join(&self.slide_join);
         ctx.insert("ingested_files", &slide_contents);
+        ctx.insert("slides", &slides);
+        ctx.insert("slides_html", &slides_html);
+        // extra_context may override the slide variables too
+        for (key, value) in &self.extra_context {
+            if self.context_collision == ContextCollision::Override
+                && ["ingested_files", "slides", "slides_html"].contains(&key.as_str())
+            {
+                ctx.insert(key.as_str(), value);
+            }
+        }
 
-        let result = Tera::one_off(&template, &ctx, false);
+        let rendered = match (&self.template_dir, template_file) {
+            (Some(template_dir), Some(template_file)) => {
+                let entry = template_entry_name(template_dir, template_file).ok_or_else(|| {
+                    tera::Error::msg(format!(
+                        "Template `{}` is not inside `{}`",
+                        template_file.display(),
+                        template_dir.display()
+                    ))
+                })?;
+                render_in_dir(template_dir, &entry, &ctx)
+            }
+            _ => render_one_off(&Self::read_template(template_file)?, &ctx),
+        };
+        let result = rendered.map_err(|e| {
+            let template_name = match template_file {
+                Some(template_file) => format!("template `{}`", template_file.display()),
+                None => "built-in template".to_string(),
+            };
+            tera::Error::chain(format!("Failed to render {}", template_name), e)
+        });
         trace!("Render template succeeded: {}", result.is_ok());
+        if result.is_ok() {
+            progress(BuildEvent::RenderingDone {
+                theme: theme.map(str::to_string),
+            });
+        }
         result
     }
 
+    /// Returns the title of the page, which is the title followed by `title_suffix` if there is one
+    pub fn page_title(&self) -> String {
+        match &self.title_suffix {
+            Some(suffix) => format!(
+                "{}{}{}",
+                self.title,
+                self.title_separator.as_deref().unwrap_or(DEFAULT_TITLE_SEPARATOR),
+                suffix
+            ),
+            None => self.title.clone(),
+        }
+    }
+
+    /// Returns the HTML of the title slide, with the title and the subtitle if there is one
+    fn title_slide_html(&self) -> String {
+        let mut html = format!("<h1>{}</h1>\n", tera::escape_html(&self.title));
+        if let Some(subtitle) = &self.subtitle {
+            html.push_str(&format!("<p>{}</p>\n", tera::escape_html(subtitle)));
+        }
+        html
+    }
+
+    /// Returns the HTML of each slide. If `render_slides` is set, each slide is
+    /// rendered as a template on its own, so that a slide which fails to render
+    /// is reported by its path. If `source_comments` is set, each slide starts
+    /// with a comment naming the file it was made from
+    ///
+    /// # Arguments
+    /// * `ctx` - The variables available to each slide
+    /// * `progress` - Called as each slide is prepared
+    ///
+    /// # Errors
+    /// If any slide fails to render
+    fn render_slides(
+        &self,
+        ctx: &tera::Context,
+        progress: &mut dyn FnMut(BuildEvent),
+    ) -> Result<Vec<String>, tera::Error> {
+        self.slides
+            .iter()
+            .map(|s| {
+                progress(BuildEvent::ParsingSlide { path: s.path.clone() });
+                let contents = if self.render_slides {
+                    render_one_off(&s.contents, ctx).map_err(|e| {
+                        tera::Error::chain(format!("Failed to render slide `{}`", s.path.display()), e)
+                    })?
+                } else {
+                    s.contents.clone()
+                };
+                if self.source_comments {
+                    Ok(format!("{}\n{}", self.source_comment(&s.path), contents))
+                } else {
+                    Ok(contents)
+                }
+            })
+            .collect()
+    }
+
+    /// Returns an HTML comment naming the slide file, relative to the directory
+    /// of the config file, or to the current directory if there is none
+    fn source_comment(&self, path: &Path) -> String {
+        let base_dir = match self.config_path.as_ref().and_then(|p| p.parent()) {
+            Some(config_dir) => Some(config_dir.to_path_buf()),
+            None => env::current_dir().ok(),
+        };
+        let relative = base_dir
+            .and_then(|dir| path.strip_prefix(dir).ok())
+            .unwrap_or(path);
+        // `--` may not appear inside a comment
+        format!("<!-- slide: {} -->", relative.display().to_string().replace("--", "%2D%2D"))
+    }
+
     /// Packages the presentation to a file.
     /// This will copy all local images referenced in slides into the output directory
     ///
-    /// Optionally, downloads revealJS libs and generates the zip too
-    pub fn package(&self) -> Result<(), anyhow::Error> {
+    /// If `themes` is non-empty, one copy of the presentation is packaged per theme
+    /// into `<output_dir>/<theme>/`
+    ///
+    /// If `output_dir` is `-`, the build is streamed to stdout as a tar archive instead
+    ///
+    /// If `create_output_dir` is false, `output_dir` must exist already
+    ///
+    /// If `reveal.bundle` is set, reveal.js is downloaded, or taken from the cache, and its
+    /// `dist/` and `plugin/` directories are placed alongside the presentation
+    ///
+    /// If `thumbnails` is set, each slide is rendered to `thumbnails/<n>.png` with a headless
+    /// browser, and `overview.html` shows them all. This is skipped when streaming to stdout
+    ///
+    /// If `zip_output` is set, the output directory is then bundled into `<output_filename>.zip`
+    /// inside it, such as `index.zip`. This is skipped when streaming to stdout
+    ///
+    /// If `dry_run` is set, the presentation is still rendered, so that template errors are caught,
+    /// but nothing is written. The files that would be written and the images that would be
+    /// copied are printed instead
+    ///
+    /// # Returns
+    /// A report of what was built, which `BuildReport::summary()` describes in a line
+    pub fn package(&self) -> Result<BuildReport, PresentationError> {
+        self.package_with_progress(|_| {})
+    }
+
+    /// Packages the presentation like `package()`, calling `progress` as the build goes on.
+    /// Events are always delivered on the calling thread, and `BuildEvent::Finished` is
+    /// the last event of a successful build
+    ///
+    /// # Errors
+    /// The same as `package()`
+    pub fn package_with_progress(&self, mut progress: impl FnMut(BuildEvent)) -> Result<BuildReport, PresentationError> {
+        let start = Instant::now();
+        let to_stdout = is_stdout(&self.output_dir);
+        if !to_stdout && !self.create_output_dir && !self.output_dir.is_dir() {
+            return Err(ArgumentError::new(
+                "output_dir".to_string(),
+                &self.output_dir.display().to_string(),
+                "Output directory does not exist, and `create_output_dir` is false".to_string(),
+            )
+            .into());
+        }
+        if self.dry_run {
+            return Ok(self.package_dry_run(&mut progress, start)?);
+        }
+        let downloads = if self.download_remote_images {
+            self.download_remote_images()
+        } else {
+            Vec::new()
+        };
+        let cfg = if downloads.is_empty() {
+            Cow::Borrowed(self)
+        } else {
+            Cow::Owned(self.with_downloaded_images(&downloads))
+        };
+        cfg.check_no_remote_images()?;
+        let reveal_files = if self.reveal.bundle {
+            read_reveal_files(&fetch_reveal(&self.reveal, &self.reveal_cache_dir()?)?)?
+        } else {
+            Vec::new()
+        };
+        if self.thumbnails && to_stdout {
+            warn!("Thumbnails are not rendered when the build goes to stdout");
+        }
+        let stats = if to_stdout {
+            cfg.package_tar(std::io::stdout().lock(), &downloads, &reveal_files, &mut progress)?
+        } else {
+            let mut stats = PackageStats::default();
+            if self.themes.is_empty() {
+                stats.add(cfg.package_to(&self.output_dir, None, &downloads, &reveal_files, &mut progress)?);
+            }
+            for theme in &self.themes {
+                debug!("Packaging theme `{}`", theme);
+                stats.add(cfg.package_to(
+                    &self.output_dir.join(theme),
+                    Some(theme),
+                    &downloads,
+                    &reveal_files,
+                    &mut progress,
+                )?);
+            }
+            if self.zip_output {
+                let zip_path = self.zip_path();
+                debug!("Zipping `{}` into `{}`", self.output_dir.display(), zip_path.display());
+                zip_dir(&self.output_dir, &zip_path)?;
+            }
+            stats
+        };
+        progress(BuildEvent::Finished {
+            images_copied: stats.images_copied,
+            bytes_written: stats.bytes_written,
+        });
+        stats.check_copy_failures()?;
+        let output_path = if to_stdout || !self.themes.is_empty() {
+            self.output_dir.clone()
+        } else {
+            self.output_dir.join(&self.output_filename)
+        };
+        Ok(BuildReport {
+            output_path,
+            slides: self.slides.len(),
+            images_copied: stats.images_copied,
+            bytes_written: stats.bytes_written,
+            html_bytes: stats.html_bytes,
+            elapsed: start.elapsed(),
+            dry_run: false,
+        })
+    }
+
+    /// Renders the presentation like `package_with_progress()`, but prints the files it would
+    /// write and the images it would copy instead of writing anything. Remote images are not
+    /// downloaded, and reveal.js is not bundled
+    fn package_dry_run(&self, progress: &mut dyn FnMut(BuildEvent), start: Instant) -> Result<BuildReport, anyhow::Error> {
+        if !self.download_remote_images {
+            self.check_no_remote_images()?;
+        }
+        // an archive on stdout is laid out like the output directory, from its root
+        let base_dir = if is_stdout(&self.output_dir) {
+            PathBuf::new()
+        } else {
+            self.output_dir.clone()
+        };
+        let themes = if self.themes.is_empty() {
+            vec![None]
+        } else {
+            self.themes.iter().map(|t| Some(t.as_str())).collect()
+        };
+        let mut bytes_rendered = 0;
+        for theme in themes {
+            let output_dir = base_dir.join(theme.unwrap_or_default());
+            for target in self.output_targets().iter() {
+                let output = self.minified(self.render(theme, target.template_file.as_deref(), progress)?);
+                bytes_rendered += output.len() as u64;
+                println!(
+                    "Would write {} bytes to `{}`",
+                    output.len(),
+                    output_dir.join(&target.output_file).display()
+                );
+            }
+            for (_, img_src_path, img_dst_path) in self.unique_images() {
+                println!(
+                    "Would copy `{}` -> `{}`",
+                    img_src_path.display(),
+                    output_dir.join(img_dst_path).display()
+                );
+            }
+            for (src, dst) in self.static_files()? {
+                println!("Would copy `{}` -> `{}`", src.display(), output_dir.join(dst).display());
+            }
+        }
+        progress(BuildEvent::Finished {
+            images_copied: 0,
+            bytes_written: 0,
+        });
+        Ok(BuildReport {
+            output_path: if self.themes.is_empty() {
+                base_dir.join(&self.output_filename)
+            } else {
+                base_dir
+            },
+            slides: self.slides.len(),
+            images_copied: 0,
+            bytes_written: 0,
+            html_bytes: bytes_rendered,
+            elapsed: start.elapsed(),
+            dry_run: true,
+        })
+    }
+
+    /// Returns every file of `static_assets`, along with where it is placed relative to
+    /// the output directory
+    ///
+    /// # Errors
+    /// If an asset no longer exists, or a directory of assets could not be read
+    fn static_files(&self) -> Result<Vec<(PathBuf, PathBuf)>, std::io::Error> {
+        let mut files = Vec::new();
+        for asset in &self.static_assets {
+            files.extend(static_asset_files(asset)?);
+        }
+        Ok(files)
+    }
+
+    /// Returns the directory that reveal.js releases are cached in,
+    /// next to the config file if there is one, or in the current directory otherwise
+    fn reveal_cache_dir(&self) -> Result<PathBuf, std::io::Error> {
+        let base_dir = match self.config_path.as_ref().and_then(|p| p.parent()) {
+            Some(config_dir) => config_dir.to_path_buf(),
+            None => env::current_dir()?,
+        };
+        Ok(base_dir.join(REVEAL_CACHE_DIR))
+    }
+
+    /// Downloads the remote images of every slide. An image that cannot be
+    /// downloaded is warned about and left out, so that its slide keeps linking to it
+    fn download_remote_images(&self) -> Vec<DownloadedImage> {
+        let mut seen = HashSet::new();
+        self.slides
+            .iter()
+            .flat_map(|slide| slide.remote_images.iter().map(move |url| (slide, url)))
+            .filter(|(_, url)| seen.insert(*url))
+            .filter_map(|(slide, url)| match download_image(url, &self.slide_options.image_root) {
+                Ok(image) => Some(image),
+                Err(e) => {
+                    warn!(
+                        file = %slide.path.display(),
+                        "Leaving remote image as is in `{}`: {:#}",
+                        slide.path.display(),
+                        e
+                    );
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Returns a copy of this PresentationConfig whose slides link to
+    /// where the downloaded images are placed instead of their URLs
+    fn with_downloaded_images(&self, downloads: &[DownloadedImage]) -> Self {
+        let mut cfg = self.clone();
+        for slide in &mut cfg.slides {
+            for image in downloads {
+                if !slide.remote_images.contains(&image.url) {
+                    continue;
+                }
+                // the same way the markdown renderer writes out links
+                let mut escaped_url = String::new();
+                escape_href(&mut escaped_url, &image.url).expect("writing to a String to succeed");
+                slide.contents = slide.contents.replace(
+                    &format!("src=\"{}\"", escaped_url),
+                    &format!("src=\"{}\"", image.dst_path.display()),
+                );
+            }
+            slide
+                .remote_images
+                .retain(|url| !downloads.iter().any(|image| &image.url == url));
+        }
+        cfg
+    }
+
+    /// Packages the presentation as a tar archive written to `writer`.
+    /// The archive is laid out the same way as the output directory would be,
+    /// with one subdirectory per theme if there are any
+    fn package_tar<W: Write>(
+        &self,
+        writer: W,
+        downloads: &[DownloadedImage],
+        reveal_files: &[RevealFile],
+        progress: &mut dyn FnMut(BuildEvent),
+    ) -> Result<PackageStats, anyhow::Error> {
+        let mut stats = PackageStats::default();
+        let mut archive = tar::Builder::new(writer);
+        let themes = if self.themes.is_empty() {
+            vec![None]
+        } else {
+            self.themes.iter().map(|t| Some(t.as_str())).collect()
+        };
+        for theme in themes {
+            let prefix = theme.map(PathBuf::from).unwrap_or_default();
+            for target in self.output_targets().iter() {
+                let output = self.minified(self.render(theme, target.template_file.as_deref(), progress)?);
+                debug!("Rendered {} bytes", output.len());
+
+                let mut header = tar::Header::new_gnu();
+                header.set_size(output.len() as u64);
+                header.set_mode(0o644);
+                header.set_mtime(Utc::now().timestamp() as u64);
+                header.set_cksum();
+                archive.append_data(&mut header, prefix.join(&target.output_file), output.as_bytes())?;
+                stats.bytes_written += output.len() as u64;
+                stats.html_bytes += output.len() as u64;
+            }
+
+            for (slide, img_src_path, img_dst_path) in self.unique_images() {
+                debug!("Slide `{}`: Archiving `{}` as `{}`",
+                    slide.path.display(),
+                    img_src_path.display(), prefix.join(img_dst_path).display());
+                let appended = fs::metadata(img_src_path).and_then(|metadata| {
+                    archive.append_path_with_name(img_src_path, prefix.join(img_dst_path))?;
+                    Ok(metadata.len())
+                });
+                match appended {
+                    Ok(bytes) => stats.bytes_written += bytes,
+                    Err(e) => {
+                        stats.copy_failures += self.copy_failed(slide, img_src_path, e)?;
+                        continue;
+                    }
+                }
+                stats.images_copied += 1;
+                progress(BuildEvent::CopyingImage {
+                    src: img_src_path.clone(),
+                    dst: prefix.join(img_dst_path),
+                });
+            }
+            for image in downloads {
+                let mut header = tar::Header::new_gnu();
+                header.set_size(image.bytes.len() as u64);
+                header.set_mode(0o644);
+                header.set_mtime(Utc::now().timestamp() as u64);
+                header.set_cksum();
+                archive.append_data(&mut header, prefix.join(&image.dst_path), image.bytes.as_slice())?;
+                stats.bytes_written += image.bytes.len() as u64;
+                stats.images_copied += 1;
+            }
+            for file in reveal_files {
+                let mut header = tar::Header::new_gnu();
+                header.set_size(file.bytes.len() as u64);
+                header.set_mode(0o644);
+                header.set_mtime(Utc::now().timestamp() as u64);
+                header.set_cksum();
+                archive.append_data(&mut header, prefix.join(&file.dst_path), file.bytes.as_slice())?;
+                stats.bytes_written += file.bytes.len() as u64;
+            }
+            for (src, dst) in self.static_files()? {
+                archive.append_path_with_name(&src, prefix.join(dst))?;
+                stats.bytes_written += fs::metadata(&src)?.len();
+            }
+        }
+        archive.into_inner()?.flush()?;
+        Ok(stats)
+    }
+
+    /// Minifies the rendered HTML if `minify` is set, otherwise returns it as is
+    fn minified(&self, html: String) -> String {
+        if !self.minify {
+            return html;
+        }
+        let cfg = minify_html::Cfg {
+            keep_comments: self.source_comments,
+            ..minify_html::Cfg::new()
+        };
+        let minified = minify_html::minify(html.as_bytes(), &cfg);
+        debug!("Minified {} bytes to {}", html.len(), minified.len());
+        String::from_utf8(minified).expect("minified HTML to be UTF-8")
+    }
+
+    /// Renders the presentation with the given theme and writes it,
+    /// along with any local images, to `output_dir`
+    fn package_to(
+        &self,
+        output_dir: &Path,
+        theme: Option<&str>,
+        downloads: &[DownloadedImage],
+        reveal_files: &[RevealFile],
+        progress: &mut dyn FnMut(BuildEvent),
+    ) -> Result<PackageStats, anyhow::Error> {
         // todo: clean up code here
-        let output = self.render()?;
-        debug!("Rendered {} bytes", output.len());
-        trace!("Output dir: `{}`", self.output_dir.display());
-        trace!("Attempting to create output_directory at `{}`, if it does not exist", &self.output_dir.display());
+        let mut stats = PackageStats::default();
+        trace!("Output dir: `{}`", output_dir.display());
+        trace!("Attempting to create output_directory at `{}`, if it does not exist", output_dir.display());
+        fs::create_dir_all(output_dir)?;
+        let output_directory = canonicalize_retry(output_dir)?;
+        for target in self.output_targets().iter() {
+            let output = self.minified(self.render(theme, target.template_file.as_deref(), progress)?);
+            debug!("Rendered {} bytes", output.len());
+            let output_path = output_directory.join(&target.output_file);
+
+            debug!("Writing to `{}`", output_path.display());
+            fs::write(&output_path, &output)?;
+            stats.bytes_written += output.len() as u64;
+            stats.html_bytes += output.len() as u64;
+            if !self.quiet {
+                let message = format!("Slides written to `{}`", output_path.display());
+                println!("{}", success(&message, color_enabled(self.no_color, &std::io::stdout())));
+            }
+        }
+        stats.add(self.copy_images(&output_directory, progress)?);
+        for image in downloads {
+            let img_path = output_directory.join(&image.dst_path);
+            fs::create_dir_all(img_path.parent().expect("image to have a parent"))?;
+            debug!("Writing `{}` to `{}`", image.url, img_path.display());
+            fs::write(&img_path, &image.bytes)?;
+            stats.bytes_written += image.bytes.len() as u64;
+            stats.images_copied += 1;
+        }
+        for file in reveal_files {
+            let file_path = output_directory.join(&file.dst_path);
+            fs::create_dir_all(file_path.parent().expect("file to have a parent"))?;
+            fs::write(&file_path, &file.bytes)?;
+            stats.bytes_written += file.bytes.len() as u64;
+        }
+        for (src, dst) in self.static_files()? {
+            let file_path = output_directory.join(&dst);
+            fs::create_dir_all(file_path.parent().expect("file to have a parent"))?;
+            debug!("Copying `{}` to `{}`", src.display(), file_path.display());
+            stats.bytes_written += fs::copy(&src, &file_path)?;
+        }
+        if self.thumbnails {
+            stats.bytes_written += self.write_thumbnails(&output_directory)?;
+        }
+        Ok(stats)
+    }
+
+    /// Returns the horizontal and vertical index of every slide, as reveal.js counts them,
+    /// in the order they appear in. The title slide comes first if there is one
+    pub fn slide_positions(&self) -> Vec<(usize, usize)> {
+        let mut positions = Vec::new();
+        let mut h = 0;
+        if self.title_slide {
+            positions.push((0, 0));
+            h += 1;
+        }
+        let mut i = 0;
+        while i < self.slides.len() {
+            match self.stacks.iter().find(|stack| stack.start == i) {
+                Some(stack) => {
+                    positions.extend((0..stack.len()).map(|v| (h, v)));
+                    i = stack.end;
+                }
+                None => {
+                    positions.push((h, 0));
+                    i += 1;
+                }
+            }
+            h += 1;
+        }
+        positions
+    }
+
+    /// Renders a thumbnail of every slide of the presentation in `output_directory`
+    /// into `thumbnails/` of it, and writes an overview page showing them all along with
+    /// a contact sheet image of that page.
+    /// With `outputs`, the slides are rendered from the first file the presentation is rendered into
+    ///
+    /// # Returns
+    /// The number of bytes written
+    ///
+    /// # Errors
+    /// If no browser could be found, or it could not render a slide or the contact sheet
+    fn write_thumbnails(&self, output_directory: &Path) -> Result<u64, anyhow::Error> {
+        let Some(browser) = find_browser() else {
+            bail!(
+                "Rendering thumbnails needs Chromium or Chrome on the PATH, or `{}` set to a browser",
+                BROWSER_ENV_VAR
+            );
+        };
+        let output_file = self.output_targets()[0].output_file.clone();
+        let output_path = output_directory.join(&output_file);
+        let thumbnail_dir = output_directory.join(THUMBNAIL_DIR);
+        fs::create_dir_all(&thumbnail_dir)?;
+        let mut bytes_written = 0;
+        let mut thumbnails = Vec::new();
+        for (i, position) in self.slide_positions().into_iter().enumerate() {
+            let file_name = format!("{}.png", i + 1);
+            let dst = thumbnail_dir.join(&file_name);
+            render_thumbnail(&browser, &slide_url(&output_path, position), &dst)?;
+            bytes_written += fs::metadata(&dst)?.len();
+            let link = format!(
+                "{}#/{}/{}",
+                output_file.display(),
+                position.0,
+                position.1
+            );
+            thumbnails.push((Path::new(THUMBNAIL_DIR).join(file_name), link));
+        }
+        let overview = overview_html(&self.title, &thumbnails);
+        let overview_path = output_directory.join(OVERVIEW_FILE);
+        debug!("Writing overview to `{}`", overview_path.display());
+        fs::write(&overview_path, &overview)?;
+        bytes_written += overview.len() as u64;
+        let contact_sheet = output_directory.join(CONTACT_SHEET_FILE);
+        render_contact_sheet(&browser, &overview_path, thumbnails.len(), &contact_sheet)?;
+        bytes_written += fs::metadata(&contact_sheet)?.len();
+        Ok(bytes_written)
+    }
+
+    /// Checks if the presentation needs to be built again since the given time.
+    /// That is the case if any slide, image, the template or the config file has been
+    /// modified since then, or if any output file is missing
+    ///
+    /// # Errors
+    /// If the modification time of any of those files could not be read
+    pub fn changed_since(&self, since: DateTime<Utc>) -> Result<bool, anyhow::Error> {
+        if is_stdout(&self.output_dir) {
+            return Ok(true);
+        }
+        if let Some(missing) = self.output_files().iter().find(|f| !f.is_file()) {
+            debug!("Output file `{}` is missing", missing.display());
+            return Ok(true);
+        }
+
+        for input in self.input_files() {
+            if modified_since(input, since)? {
+                debug!("`{}` has changed since the last build", input.display());
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Returns the path of the zip archive that `package()` writes if `zip_output` is set
+    fn zip_path(&self) -> PathBuf {
+        self.output_dir.join(&self.output_filename).with_extension("zip")
+    }
+
+    /// Returns the presentation files that `package()` writes, one per theme,
+    /// and the zip archive if `zip_output` is set.
+    /// Empty if the build goes to stdout
+    fn output_files(&self) -> Vec<PathBuf> {
+        if is_stdout(&self.output_dir) {
+            return Vec::new();
+        }
+        let dirs = if self.themes.is_empty() {
+            vec![self.output_dir.clone()]
+        } else {
+            self.themes.iter().map(|theme| self.output_dir.join(theme)).collect()
+        };
+        let targets = self.output_targets();
+        let mut files = dirs
+            .iter()
+            .flat_map(|dir| targets.iter().map(|target| dir.join(&target.output_file)))
+            .collect::<Vec<PathBuf>>();
+        if self.zip_output {
+            files.push(self.zip_path());
+        }
+        files
+    }
+
+    /// Returns every file the presentation is built from:
+    /// the slides, their local images, the template and the config file
+    fn input_files(&self) -> Vec<&PathBuf> {
+        let mut seen = HashSet::new();
+        self.slides
+            .iter()
+            .flat_map(|slide| {
+                std::iter::once(&slide.path)
+                    .chain(slide.local_images.iter().map(|(src, _)| src))
+                    .chain(&slide.embedded_images)
+            })
+            .chain(&self.template_file)
+            .chain(self.outputs.iter().filter_map(|t| t.template_file.as_ref()))
+            .chain(&self.config_path)
+            .filter(|path| seen.insert(*path))
+            .collect()
+    }
+
+    /// Returns a Makefile rule with the presentation files as targets,
+    /// and every file they are built from as prerequisites
+    ///
+    /// # Returns
+    /// The rule, or `None` if the build goes to stdout and so has no files to target
+    pub fn dependency_rule(&self) -> Option<String> {
+        let targets = self.output_files();
+        if targets.is_empty() {
+            return None;
+        }
+        Some(make_rule(&targets, &self.input_files()))
+    }
+
+    /// Joins the markdown of every slide with `slide_join`.
+    /// Links to local images point to where `package()` places them
+    pub fn export_markdown(&self) -> String {
+        self.slides
+            .iter()
+            .map(|s| s.markdown.as_str())
+            .collect::<Vec<&str>>()
+            .join(&self.slide_join)
+    }
+
+    /// Returns the markdown of every slide as a handout, where each slide is headed
+    /// by `## Slide <N>` and separated from the next by `---`.
+    /// Links to local images point to where `package()` places them
+    pub fn export_handout(&self) -> String {
+        self.slides
+            .iter()
+            .enumerate()
+            .map(|(i, s)| format!("## Slide {}\n\n{}", i + 1, s.markdown.trim_end()))
+            .collect::<Vec<String>>()
+            .join("\n\n---\n\n")
+            + "\n"
+    }
+
+    /// Writes the markdown of the presentation, as given by `export_markdown()`,
+    /// or `export_handout()` if `handout` is set, to `output_filename` in `output_dir`,
+    /// along with any local images
+    ///
+    /// # Errors
+    /// If the markdown or the images could not be written
+    pub fn package_markdown(&self, output_filename: &Path, handout: bool) -> Result<(), PresentationError> {
         fs::create_dir_all(&self.output_dir)?;
-        let output_directory = fs::canonicalize(&self.output_dir)?;
-        let output_path = output_directory.join(&self.output_filename);
+        let output_directory = canonicalize_retry(&self.output_dir)?;
+        let output_path = output_directory.join(output_filename);
+        debug!("Writing markdown to `{}`", output_path.display());
+        let markdown = if handout {
+            self.export_handout()
+        } else {
+            self.export_markdown()
+        };
+        fs::write(&output_path, markdown)?;
+        if !self.quiet {
+            let message = format!("Markdown written to `{}`", output_path.display());
+            println!("{}", success(&message, color_enabled(self.no_color, &std::io::stdout())));
+        }
+        Ok(self.copy_images(&output_directory, &mut |_| {})?.check_copy_failures()?)
+    }
 
-        debug!("Writing to `{}`", output_path.display());
-        fs::write(&output_path, output)?;
-        println!("Slides written to `{}`", output_path.display());
+    /// Returns the local images of every slide along with the slide that references them.
+    /// An image referenced by several slides is only returned for the first of them
+    fn unique_images(&self) -> Vec<(&SlideFile, &PathBuf, &PathBuf)> {
+        let mut seen = HashSet::new();
+        self.slides
+            .iter()
+            .flat_map(|slide| {
+                slide
+                    .local_images
+                    .iter()
+                    .map(move |(img_src_path, img_dst_path)| (slide, img_src_path, img_dst_path))
+            })
+            .filter(|(_, _, img_dst_path)| seen.insert(*img_dst_path))
+            .collect()
+    }
 
-        for slide in &self.slides {
-            if slide.local_images.is_empty() {
-                continue;
+    /// Copies the local images of every slide into `output_directory`, in parallel,
+    /// with at most `MAX_CONCURRENT_COPIES` copies at once.
+    /// `progress` is called for each image once they have all been copied.
+    /// If `continue_on_copy_error` is set, an image that cannot be copied is warned
+    /// about and counted in `copy_failures` instead of stopping the copy
+    fn copy_images(
+        &self,
+        output_directory: &Path,
+        progress: &mut dyn FnMut(BuildEvent),
+    ) -> Result<PackageStats, anyhow::Error> {
+        let images = self.unique_images();
+        let mut copies = Vec::with_capacity(images.len());
+        for chunk in images.chunks(MAX_CONCURRENT_COPIES) {
+            let chunk_copies = chunk
+                .par_iter()
+                .map(|(slide, img_src_path, img_dst_path)| {
+                    // src is absolute, dst is relative to output directory
+                    let dst = output_directory.join(img_dst_path);
+                    if is_up_to_date(img_src_path, &dst) {
+                        debug!("Slide `{}`: `{}` is up to date", slide.path.display(), dst.display());
+                        return Ok(None);
+                    }
+                    fs::create_dir_all(dst.parent().expect("image to have a parent"))?;
+                    debug!("Slide `{}`: Copying `{}` to `{}`",
+                        slide.path.display(),
+                        img_src_path.display(), dst.display());
+                    copy_file(img_src_path, &dst).map(Some)
+                })
+                .collect::<Vec<Result<Option<u64>, std::io::Error>>>();
+            copies.extend(chunk_copies);
+        }
+
+        let mut stats = PackageStats::default();
+        for ((slide, img_src_path, img_dst_path), copy) in images.into_iter().zip(copies) {
+            match copy {
+                Ok(None) => progress(BuildEvent::ImageUpToDate {
+                    src: img_src_path.clone(),
+                    dst: img_dst_path.clone(),
+                }),
+                Ok(Some(bytes)) => {
+                    stats.images_copied += 1;
+                    stats.bytes_written += bytes;
+                    progress(BuildEvent::CopyingImage {
+                        src: img_src_path.clone(),
+                        dst: img_dst_path.clone(),
+                    });
+                }
+                Err(e) => stats.copy_failures += self.copy_failed(slide, img_src_path, e)?,
             }
-            for (img_src_path, img_dst_path) in &slide.local_images {
-                // src is absolute, dst is relative to output directory
-                fs::create_dir_all(output_directory.join(img_dst_path.parent().expect("image to have a parent")))?;
-                debug!("Slide `{}`: Copying `{}` to `{}`",
-                    slide.path.display(),
-                    img_src_path.display(), output_directory.join(img_dst_path).display());
-                fs::copy(img_src_path, output_directory.join(img_dst_path))?;
+        }
+        Ok(stats)
+    }
+
+    /// Handles an image of a slide that could not be copied
+    ///
+    /// # Returns
+    /// The number of failures to count, if `continue_on_copy_error` is set
+    ///
+    /// # Errors
+    /// The copy error, with the image it is about, if `continue_on_copy_error` is not set
+    fn copy_failed(&self, slide: &SlideFile, img_src_path: &Path, e: std::io::Error) -> Result<usize, anyhow::Error> {
+        let e = anyhow::Error::new(e).context(format!(
+            "Could not copy `{}` referenced in `{}`",
+            img_src_path.display(),
+            slide.path.display()
+        ));
+        if !self.continue_on_copy_error {
+            return Err(e);
+        }
+        warn!(file = %slide.path.display(), "{:#}", e);
+        Ok(1)
+    }
+}
+
+/// Formats a count of something, such as `1 slide` or `2 slides`
+fn count_of(count: u64, noun: &str) -> String {
+    if count == 1 {
+        format!("{} {}", count, noun)
+    } else {
+        format!("{} {}s", count, noun)
+    }
+}
+
+/// Formats a number of bytes in kilobytes, such as `1.5 KB` for 1536 bytes
+fn format_kb(bytes: u64) -> String {
+    format!("{:.1} KB", bytes as f64 / 1024.0)
+}
+
+/// Formats the date a presentation is built on
+///
+/// # Arguments
+/// * `now` - The current time
+/// * `locale` - Locale to format the date for, such as `de_DE`
+///
+/// # Returns
+/// The localized date if a locale is given, otherwise an ISO-8601 date in UTC
+///
+/// # Errors
+/// If the locale is not known
+fn build_date(now: DateTime<Utc>, locale: Option<&str>) -> Result<String, ArgumentError> {
+    match locale {
+        Some(locale) => {
+            let chrono_locale = Locale::try_from(locale).map_err(|_| {
+                ArgumentError::new("locale".to_string(), locale, "Unknown locale".to_string())
+            })?;
+            Ok(now.format_localized("%x", chrono_locale).to_string())
+        }
+        None => Ok(now.format("%Y-%m-%d").to_string()),
+    }
+}
+
+/// Returns the name that a template in a directory of templates is known by,
+/// its path relative to the directory with `/` between components, such as `partials/header.html`.
+/// `None` if it is not an `.html` file inside the directory, where it would not be loaded
+fn template_entry_name(template_dir: &Path, template_file: &Path) -> Option<String> {
+    if template_file.extension().and_then(OsStr::to_str) != Some("html") {
+        return None;
+    }
+    let relative = template_file.strip_prefix(template_dir).ok()?;
+    let components = relative
+        .components()
+        .map(|c| match c {
+            Component::Normal(name) => name.to_str(),
+            _ => None,
+        })
+        .collect::<Option<Vec<&str>>>()?;
+    Some(components.join("/"))
+}
+
+/// Checks which of the variables a presentation needs the template does not mention:
+/// the title, and the slides in any of the forms they are provided in
+///
+/// This only looks for the names, so a template that mentions one without using it passes
+///
+/// # Returns
+/// A description of each missing variable, such as `` `slide_title` ``
+fn missing_template_variables(template: &str) -> Vec<&'static str> {
+    let mentions = |name: &str| {
+        template.match_indices(name).any(|(i, _)| {
+            let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+            !template[..i].ends_with(is_ident) && !template[i + name.len()..].starts_with(is_ident)
+        })
+    };
+    let mut missing = Vec::new();
+    if !mentions("slide_title") && !mentions("page_title") {
+        missing.push("`slide_title`");
+    }
+    if !["ingested_files", "slides", "slides_html"].iter().any(|v| mentions(v)) {
+        missing.push("the slides, through `ingested_files`, `slides` or `slides_html`");
+    }
+    missing
+}
+
+/// Leaves out slides that are marked as drafts, unless drafts are included
+fn filter_drafts(slides: Vec<SlideFile>, include_drafts: bool) -> Vec<SlideFile> {
+    if include_drafts {
+        return slides;
+    }
+    slides
+        .into_iter()
+        .filter(|s| {
+            if s.meta.draft {
+                debug!("Leaving out draft slide `{}`", s.path.display());
             }
+            !s.meta.draft
+        })
+        .collect()
+}
+
+/// Splits each slide file read with a `slide_separator` into one slide per part.
+/// See `SlideFile::split`
+fn split_slides(slides: Vec<SlideFile>) -> Vec<SlideFile> {
+    slides.into_iter().flat_map(SlideFile::split).collect()
+}
+
+/// Leaves out draft slides from each group, as `filter_drafts` does, splits the rest
+/// as `split_slides` does, and flattens the groups into a list of slides
+///
+/// # Returns
+/// The slides of every group in order, and the range of them that each stack spans.
+/// Stacks left without slides are dropped
+fn flatten_groups(groups: Vec<SlideGroup>, include_drafts: bool) -> (Vec<SlideFile>, Vec<Range<usize>>) {
+    let mut slides = Vec::new();
+    let mut stacks = Vec::new();
+    for group in groups {
+        let is_stack = matches!(group, SlideGroup::Stack(_));
+        let group_slides = match group {
+            SlideGroup::Single(slide) => vec![*slide],
+            SlideGroup::Stack(group_slides) => group_slides,
+        };
+        let start = slides.len();
+        slides.extend(split_slides(filter_drafts(group_slides, include_drafts)));
+        if is_stack && slides.len() > start {
+            stacks.push(start..slides.len());
+        }
+    }
+    (slides, stacks)
+}
+
+/// Progress of a build, as reported by `PresentationConfig::package_with_progress()`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BuildEvent {
+    /// A slide is being prepared for the template
+    ParsingSlide {
+        /// Absolute path to the slide
+        path: PathBuf,
+    },
+    /// The presentation has been rendered
+    RenderingDone {
+        /// The theme it was rendered with, if any
+        theme: Option<String>,
+    },
+    /// An image has been copied to the output
+    CopyingImage {
+        /// Absolute path to the image
+        src: PathBuf,
+        /// Where it was copied to, relative to the output directory
+        dst: PathBuf,
+    },
+    /// An image was in the output already, unchanged since it was copied, so it was not copied again
+    ImageUpToDate {
+        /// Absolute path to the image
+        src: PathBuf,
+        /// Where it is, relative to the output directory
+        dst: PathBuf,
+    },
+    /// The presentation has been packaged
+    Finished {
+        /// Number of images copied
+        images_copied: usize,
+        /// Number of bytes written, including images
+        bytes_written: u64,
+    },
+}
+
+/// What a build of a presentation did, as returned by `PresentationConfig::package()`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuildReport {
+    /// Where the presentation was written: its main output file, or the output directory
+    /// if it was packaged once per theme. `-` if the build went to stdout
+    pub output_path: PathBuf,
+    /// Number of slides built, without the title slide
+    pub slides: usize,
+    /// Number of images copied, without those that were up to date already
+    pub images_copied: usize,
+    /// Number of bytes written, including images
+    pub bytes_written: u64,
+    /// Number of bytes of rendered presentations, out of `bytes_written`.
+    /// On a dry run, the bytes that were rendered but not written
+    pub html_bytes: u64,
+    /// How long the build took
+    pub elapsed: Duration,
+    /// Whether this was a dry run, which did not write anything
+    pub dry_run: bool,
+}
+
+impl BuildReport {
+    /// Returns a one-line summary of the build, such as
+    /// `Built 42 slides, 18 images, 124.0 KB of HTML in 0.8s`
+    pub fn summary(&self) -> String {
+        if self.dry_run {
+            return format!(
+                "Dry run of {}, rendered {} without writing anything",
+                count_of(self.slides as u64, "slide"),
+                count_of(self.html_bytes, "byte")
+            );
+        }
+        format!(
+            "Built {}, {}, {} of HTML in {:.1}s",
+            count_of(self.slides as u64, "slide"),
+            count_of(self.images_copied as u64, "image"),
+            format_kb(self.html_bytes),
+            self.elapsed.as_secs_f64()
+        )
+    }
+
+    /// Prints the summary of the build, on stderr if the build went to stdout,
+    /// which is taken up by the archive
+    pub fn print_summary(&self, no_color: bool) {
+        if is_stdout(&self.output_path) && !self.dry_run {
+            eprintln!("{}", success(&self.summary(), color_enabled(no_color, &std::io::stderr())));
+        } else {
+            println!("{}", success(&self.summary(), color_enabled(no_color, &std::io::stdout())));
+        }
+    }
+}
+
+/// Counts of what was written while packaging
+#[derive(Debug, Default)]
+struct PackageStats {
+    images_copied: usize,
+    bytes_written: u64,
+    /// Bytes of rendered presentations written, out of `bytes_written`
+    html_bytes: u64,
+    /// Images that could not be copied, with `continue_on_copy_error`
+    copy_failures: usize,
+}
+
+impl PackageStats {
+    fn add(&mut self, other: PackageStats) {
+        self.images_copied += other.images_copied;
+        self.bytes_written += other.bytes_written;
+        self.html_bytes += other.html_bytes;
+        self.copy_failures += other.copy_failures;
+    }
+
+    /// Fails if any image could not be copied, once the rest of the presentation has been
+    fn check_copy_failures(&self) -> Result<(), anyhow::Error> {
+        if self.copy_failures > 0 {
+            bail!(
+                "{} images could not be copied, see the warnings above",
+                self.copy_failures
+            );
+        }
+        Ok(())
+    }
+}
+
+impl PresentationConfig {
+    /// Reads the config file at the given path, applying any overrides
+    /// given on the command line.
+    ///
+    /// # Arguments
+    /// * `config_path` - Path to the config file
+    /// * `args` - The command line arguments. Overrides specific to `from-config`
+    ///   are only applied if that is the command given
+    ///
+    /// # Errors
+    /// If the config file cannot be read, or the resulting configuration is invalid
+    pub fn from_config_path(config_path: &Path, args: &CliArgs) -> Result<Self, PresentationError> {
+        Self::from_config_path_cached(config_path, args, None)
+    }
+
+    /// Reads the config file at the given path like `from_config_path`,
+    /// taking the slides that have not changed from `slide_cache` if it is given
+    ///
+    /// # Errors
+    /// The same as `from_config_path`
+    pub fn from_config_path_cached(
+        config_path: &Path,
+        args: &CliArgs,
+        slide_cache: Option<Arc<SlideCache>>,
+    ) -> Result<Self, PresentationError> {
+        let mut config = PresentationConfigFile::read_config_file(config_path.to_path_buf())?;
+        config.slide_cache = slide_cache;
+        if let Some(Commands::FromConfig {
+            slide_dir: Some(slide_dir),
+            ..
+        }) = &args.command
+        {
+            // joining an absolute path replaces the config's working dir
+            config.slide_dir = fs::canonicalize(env::current_dir()?)?.join(slide_dir);
+        }
+        if args.max_image_bytes.is_some() {
+            config.max_image_bytes = args.max_image_bytes;
+        }
+        if args.locale.is_some() {
+            config.locale = args.locale.clone();
+        }
+        if args.image_root.is_some() {
+            config.image_root = args.image_root.clone();
+        }
+        if args.slide_numbers.is_some() {
+            config.slide_numbers = args.slide_numbers.clone();
+        }
+        config.strict |= args.strict;
+        config.include_drafts |= args.include_drafts;
+        config.no_remote_images |= args.no_remote_images;
+        config.no_overwrite |= args.no_overwrite;
+        let mut cfg = Self::try_from(config)?;
+        cfg.apply_cli_args(args)?;
+        Ok(cfg)
+    }
+
+    /// Applies the command line arguments that only affect how the presentation is built,
+    /// and checks the slides against `--require-unique-titles`.
+    /// Done once for each presentation, whether it is read from a config file or the command line
+    ///
+    /// # Errors
+    /// If `--require-unique-titles` is given and any slides have the same title
+    fn apply_cli_args(&mut self, args: &CliArgs) -> Result<(), PresentationError> {
+        self.quiet = args.quiet;
+        self.no_color = args.no_color;
+        self.source_comments = args.source_comments;
+        self.dry_run = args.dry_run;
+        if args.require_unique_titles {
+            check_unique_titles(&self.slides)?;
         }
         Ok(())
     }
@@ -134,83 +1867,295 @@ impl PresentationConfig {
 /// All paths will be converted to absolute paths with respect to the current working directory.
 /// (i.e. the directory the command was executed in)
 impl TryFrom<CliArgs> for PresentationConfig {
-    type Error = anyhow::Error;
+    type Error = PresentationError;
 
     fn try_from(args: CliArgs) -> Result<Self, Self::Error> {
-        match args.command {
-            Commands::FromConfig { config_path } => {
-                let config = PresentationConfigFile::read_config_file(config_path)?;
-                Ok(Self::try_from(config)?)
+        // presentations of config files take the command line arguments in `from_config_path`
+        let cfg = match &args.command {
+            None => {
+                let cwd = env::current_dir()?;
+                let Some(config_path) = find_default_config(&cwd) else {
+                    return Err(anyhow!(
+                        "No command given, and no `{}` in `{}`",
+                        DEFAULT_CONFIG_FILES.join("` or `"),
+                        cwd.display()
+                    )
+                    .into());
+                };
+                Self::from_config_path(&config_path, &args)?
             }
-            Commands::FromCli {
+            Some(
+                Commands::ExportMd { config_path, .. }
+                | Commands::Validate { config_path, .. }
+                | Commands::Watch { config_path }
+                | Commands::Serve { config_path, .. },
+            ) => Self::from_config_path(config_path, &args)?,
+            Some(Commands::FromConfig { config_paths, .. }) => {
+                if config_paths.len() != 1 {
+                    return Err(anyhow!(
+                        "Expected a single config file but got {}, pass several config files to `from-config` to build them all",
+                        config_paths.len()
+                    )
+                    .into());
+                }
+                Self::from_config_path(&config_paths[0], &args)?
+            }
+            Some(Commands::Init { .. }) => {
+                return Err(anyhow!("`init` creates a project instead of a presentation").into())
+            }
+            Some(Commands::FromCli {
                 title,
                 slide_dir,
                 template_file,
                 output_dir,
                 output_file,
-            } => {
+                themes,
+            }) => {
                 trace!("Converting CLI args to PresentationConfig");
-                let cwd = fs::canonicalize(env::current_dir()?)?;
-                let slide_title = if let Some(title) = title {
-                    title
-                } else {
-                    "Untitled Presentation".to_string()
-                };
-                let slides = find_slides(&cwd.join(slide_dir))?;
-                let cfg = PresentationConfig {
-                    title: slide_title,
-                    output_dir: cwd.join(output_dir),
-                    output_filename: output_file,
-                    template_file: cwd.join(template_file),
-                    slides,
-                };
-                cfg.validate()?;
-                Ok(cfg)
+                let mut builder = PresentationConfigBuilder::new(
+                    title.as_deref().unwrap_or("Untitled Presentation"),
+                )
+                .slides_from_dir(slide_dir)
+                .output_dir(output_dir)
+                .output_file(output_file)
+                .strict(args.strict)
+                .include_drafts(args.include_drafts)
+                .no_remote_images(args.no_remote_images)
+                .no_overwrite(args.no_overwrite);
+                if let Some(template_file) = template_file {
+                    builder = builder.template_file(template_file);
+                }
+                for theme in themes {
+                    builder = builder.theme(theme);
+                }
+                if let Some(max_image_bytes) = args.max_image_bytes {
+                    builder = builder.max_image_bytes(max_image_bytes);
+                }
+                if let Some(locale) = &args.locale {
+                    builder = builder.locale(locale);
+                }
+                if let Some(image_root) = &args.image_root {
+                    builder = builder.image_root(image_root);
+                }
+                if let Some(slide_numbers) = &args.slide_numbers {
+                    builder = builder.slide_numbers(slide_numbers.clone());
+                }
+                let mut cfg = builder.build()?;
+                cfg.apply_cli_args(&args)?;
+                cfg
             }
-        }
+        };
+        Ok(cfg)
     }
 }
 
 /// Attempts to convert a PresentationConfigFile to PresentationConfig
 /// Validates and converts relative paths to absolute paths in the process
 impl TryFrom<PresentationConfigFile> for PresentationConfig {
-    type Error = anyhow::Error;
+    type Error = PresentationError;
 
     fn try_from(config: PresentationConfigFile) -> Result<Self, Self::Error> {
         trace!("Attempting to convert PresentationConfigFile to PresentationConfig");
-        let include_files_abs_paths = config
-            .include_files
+        // glob patterns expand to several slides, which all take the options of their entry
+        let mut include_entries = Vec::new();
+        for include_file in &config.include_files {
+            let slide_dir = config.working_dir.join(&config.slide_dir);
+            for path in expand_include_file(&slide_dir, include_file.path())? {
+                include_entries.push((path, include_file));
+            }
+        }
+        let include_files_abs_paths = include_entries
             .iter()
-            .map(|relative_pth| {
-                config
-                    .working_dir
-                    .join(&config.slide_dir)
-                    .join(relative_pth)
-            })
+            .map(|(path, _)| path.clone())
             .collect::<Vec<PathBuf>>();
         trace!(
             "Converted {} include_file paths to abs paths",
             include_files_abs_paths.len()
         );
-        let slides = if include_files_abs_paths.is_empty() {
+        let slide_options = SlideOptions {
+            extensions: config.slide_extensions,
+            img_path_template: config.img_path_template,
+            embed_images: config.embed_images,
+            image_root: config
+                .image_root
+                .unwrap_or_else(|| PathBuf::from(DEFAULT_IMAGE_ROOT)),
+            image_layout: config.image_layout,
+            slide_separator: config.split_slides.then_some(config.slide_separator),
+            cache: config.slide_cache,
+        };
+        let (slides, stacks) = if include_files_abs_paths.is_empty() {
             // let's try to search for slides
-            find_slides(&config.working_dir.join(config.slide_dir))?
+            let slide_dir = config.working_dir.join(&config.slide_dir);
+            let exclude = output_exclusions(
+                &slide_dir,
+                &config.working_dir.join(&config.output_dir),
+                &config.output_file,
+            );
+            if config.image_slideshow {
+                let images = find_image_paths(&slide_dir, &exclude)?;
+                (SlideFile::from_image_paths(images, &slide_options)?, Vec::new())
+            } else if config.vertical_stacks {
+                flatten_groups(
+                    find_slide_groups(&slide_dir, &exclude, &slide_options)?,
+                    config.include_drafts,
+                )
+            } else {
+                let slides = find_slides_lenient(&slide_dir, &exclude, &slide_options)?;
+                (split_slides(filter_drafts(slides, config.include_drafts)), Vec::new())
+            }
         } else {
-            let sf = include_files_abs_paths
-                .iter()
-                .map(SlideFile::read_and_parse)
-                .collect::<Result<Vec<SlideFile>, anyhow::Error>>()?;
-            sf
+            if let Some(missing) = include_files_abs_paths.iter().find(|p| !p.exists()) {
+                return Err(missing_slide_error(missing).into());
+            }
+            let include_files_abs_paths = if config.discover_remaining {
+                let slide_dir = config.working_dir.join(&config.slide_dir);
+                let exclude = output_exclusions(
+                    &slide_dir,
+                    &config.working_dir.join(&config.output_dir),
+                    &config.output_file,
+                );
+                let discovered = if config.image_slideshow {
+                    find_image_paths(&slide_dir, &exclude)?
+                } else {
+                    find_slide_paths(&slide_dir, &exclude)?
+                };
+                append_discovered(include_files_abs_paths, discovered)
+            } else {
+                include_files_abs_paths
+            };
+            let mut slides = if config.image_slideshow {
+                SlideFile::from_image_paths(include_files_abs_paths, &slide_options)?
+            } else {
+                SlideFile::from_paths(include_files_abs_paths, &slide_options)?
+            };
+            // the listed slides come first, in the order they are listed
+            for ((_, include_file), slide) in include_entries.iter().zip(slides.iter_mut()) {
+                include_file.apply_to(&mut slide.meta);
+            }
+            (split_slides(filter_drafts(slides, config.include_drafts)), Vec::new())
         };
 
+        let output_dir = if is_stdout(&config.output_dir) {
+            config.output_dir
+        } else {
+            config.working_dir.join(config.output_dir)
+        };
+        let template_dir = config.template_dir.map(|d| config.working_dir.join(d));
+        // templates are relative to the template directory if there is one
+        let template_base = template_dir.as_ref().unwrap_or(&config.working_dir);
+        let outputs = config
+            .outputs
+            .into_iter()
+            .map(|target| OutputTarget {
+                template_file: target.template_file.map(|t| template_base.join(t)),
+                output_file: target.output_file,
+            })
+            .collect::<Vec<OutputTarget>>();
+        // the first output stands in for the presentation, such as when serving it
+        let (template_file, output_filename) = match outputs.first() {
+            Some(main) => (main.template_file.clone(), main.output_file.clone()),
+            None => (config.template_file.map(|t| template_base.join(t)), config.output_file),
+        };
         let cfg = PresentationConfig {
             title: config.title,
-            output_dir: config.working_dir.join(config.output_dir),
-            template_file: config.working_dir.join(config.template_file),
-            output_filename: config.output_file,
+            subtitle: config.subtitle,
+            title_slide: config.title_slide,
+            title_suffix: config.title_suffix,
+            title_separator: config.title_separator,
+            output_dir,
+            create_output_dir: config.create_output_dir,
+            no_overwrite: config.no_overwrite,
+            template_file,
+            template_dir,
+            output_filename,
+            outputs,
             slides,
+            stacks,
+            max_image_bytes: config.max_image_bytes,
+            static_assets: config
+                .static_assets
+                .iter()
+                .map(|asset| config.working_dir.join(asset))
+                .collect(),
+            themes: config.themes,
+            theme: config.theme,
+            quiet: false,
+            no_color: false,
+            source_comments: false,
+            dry_run: false,
+            slide_join: config.slide_join,
+            locale: config.locale,
+            extra_context: config.extra_context,
+            context_collision: config.context_collision,
+            config_path: Some(config.config_path),
+            strict: config.strict,
+            slide_options,
+            render_slides: config.render_slides,
+            no_remote_images: config.no_remote_images,
+            download_remote_images: config.download_remote_images,
+            minify: config.minify,
+            reveal: RevealOptions {
+                bundle: config.bundle_reveal,
+                version: config.reveal_version,
+                url: config.reveal_url,
+            },
+            reveal_config: RevealConfig {
+                width: config.width,
+                height: config.height,
+                margin: config.margin,
+                slide_number: config.slide_numbers,
+                view: config.view_mode,
+                ..RevealConfig::default()
+            },
+            zip_output: config.zip_output,
+            continue_on_copy_error: config.continue_on_copy_error,
+            thumbnails: config.thumbnails,
         };
         cfg.validate()?;
         Ok(cfg)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_missing_template_variables() {
+        let good = "<title>{{ slide_title }}</title>{% for fc in ingested_files %}{{ fc }}{% endfor %}";
+        assert!(missing_template_variables(good).is_empty());
+        assert!(missing_template_variables(DEFAULT_TEMPLATE).is_empty());
+        assert!(missing_template_variables("{{ slide_title }}{{ slides_html | safe }}").is_empty());
+
+        let missing_loop = "<title>{{ slide_title }}</title>{{ ingested_filesx }}{{ my_slides }}";
+        assert_eq!(
+            missing_template_variables(missing_loop),
+            vec!["the slides, through `ingested_files`, `slides` or `slides_html`"]
+        );
+        assert_eq!(missing_template_variables("{% for fc in ingested_files %}{% endfor %}"), vec!["`slide_title`"]);
+    }
+
+    #[test]
+    fn test_build_date() {
+        let now = Utc.with_ymd_and_hms(2022, 7, 31, 12, 0, 0).unwrap();
+        assert_eq!(build_date(now, None).unwrap(), "2022-07-31");
+        assert_eq!(build_date(now, Some("de_DE")).unwrap(), "31.07.2022");
+        assert_eq!(build_date(now, Some("en_US")).unwrap(), "07/31/2022");
+        assert!(build_date(now, Some("not_A_locale")).is_err());
+    }
+
+    #[test]
+    fn test_format_kb() {
+        assert_eq!(format_kb(0), "0.0 KB");
+        assert_eq!(format_kb(1536), "1.5 KB");
+        assert_eq!(format_kb(126_976), "124.0 KB");
+    }
+
+    #[test]
+    fn test_count_of() {
+        assert_eq!(count_of(0, "slide"), "0 slides");
+        assert_eq!(count_of(1, "slide"), "1 slide");
+        assert_eq!(count_of(2, "image"), "2 images");
+    }
+}
@@ -0,0 +1,143 @@
+use std::cmp::Ordering;
+use std::path::{Path, PathBuf};
+
+/// How slides should be ordered relative to each other
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OrderMode {
+    /// Natural order of the file names, so `2.md` comes before `10.md`
+    #[default]
+    Natural,
+    /// Plain lexical order of the file names, so `10.md` comes before `2.md`
+    Lexical,
+    /// Numeric order of the leading number in the file names.
+    /// Files without a leading number come after all numbered ones, in natural order
+    NumericPrefix,
+}
+
+/// Returns the file name of the path as a string, or an empty string if it has none
+fn file_name_of(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Parses the leading number of a file name, if any
+///
+/// # Examples
+/// `"10_intro.md"` and `"010-intro.md"` both give `Some(10)`, while `"intro.md"` gives `None`
+pub fn numeric_prefix(file_name: &str) -> Option<u64> {
+    let digits_end = file_name
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(file_name.len());
+    file_name[..digits_end].parse().ok()
+}
+
+/// Compares two file names according to the given mode
+pub fn compare_file_names(a: &str, b: &str, mode: OrderMode) -> Ordering {
+    match mode {
+        OrderMode::Natural => natord::compare(a, b),
+        OrderMode::Lexical => a.cmp(b),
+        OrderMode::NumericPrefix => match (numeric_prefix(a), numeric_prefix(b)) {
+            (Some(x), Some(y)) => x.cmp(&y).then_with(|| natord::compare(a, b)),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => natord::compare(a, b),
+        },
+    }
+}
+
+/// Orders slide paths by their file names
+///
+/// # Arguments
+/// * `paths` - Paths to slide files
+/// * `mode` - How the file names should be compared
+///
+/// # Returns
+/// The same paths, sorted
+pub fn order_slides(mut paths: Vec<PathBuf>, mode: OrderMode) -> Vec<PathBuf> {
+    paths.sort_by(|a, b| compare_file_names(&file_name_of(a), &file_name_of(b), mode));
+    paths
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_numeric_prefix() {
+        assert_eq!(numeric_prefix("10_intro.md"), Some(10));
+        assert_eq!(numeric_prefix("010-intro.md"), Some(10));
+        assert_eq!(numeric_prefix("3.md"), Some(3));
+        assert_eq!(numeric_prefix("intro.md"), None);
+        assert_eq!(numeric_prefix(""), None);
+    }
+
+    #[test]
+    fn test_order_slides() {
+        let cases: Vec<(&str, OrderMode, Vec<&str>, Vec<&str>)> = vec![
+            (
+                "numeric",
+                OrderMode::Natural,
+                vec!["10.md", "2.md", "1.md"],
+                vec!["1.md", "2.md", "10.md"],
+            ),
+            (
+                "numeric, lexical",
+                OrderMode::Lexical,
+                vec!["10.md", "2.md", "1.md"],
+                vec!["1.md", "10.md", "2.md"],
+            ),
+            (
+                "zero padded",
+                OrderMode::Natural,
+                vec!["010.md", "002.md", "001.md"],
+                vec!["001.md", "002.md", "010.md"],
+            ),
+            (
+                "zero padded mixed with unpadded",
+                OrderMode::NumericPrefix,
+                vec!["10_c.md", "02_b.md", "1_a.md"],
+                vec!["1_a.md", "02_b.md", "10_c.md"],
+            ),
+            (
+                "natural with sub-slides",
+                OrderMode::Natural,
+                vec!["1b.md", "10a.md", "1a.md", "2a.md"],
+                vec!["1a.md", "1b.md", "2a.md", "10a.md"],
+            ),
+            (
+                "underscore separator",
+                OrderMode::NumericPrefix,
+                vec!["10_outro.md", "2_body.md", "1_intro.md"],
+                vec!["1_intro.md", "2_body.md", "10_outro.md"],
+            ),
+            (
+                "dash separator",
+                OrderMode::NumericPrefix,
+                vec!["10-outro.md", "2-body.md", "1-intro.md"],
+                vec!["1-intro.md", "2-body.md", "10-outro.md"],
+            ),
+            (
+                "named after numbered",
+                OrderMode::NumericPrefix,
+                vec!["appendix.md", "2_body.md", "intro.md", "1_start.md"],
+                vec!["1_start.md", "2_body.md", "appendix.md", "intro.md"],
+            ),
+            (
+                "full paths compare by file name",
+                OrderMode::Natural,
+                vec!["/b/2.md", "/a/10.md", "/c/1.md"],
+                vec!["/c/1.md", "/b/2.md", "/a/10.md"],
+            ),
+        ];
+
+        for (name, mode, input, expected) in cases {
+            let input = input.into_iter().map(PathBuf::from).collect();
+            let expected = expected
+                .into_iter()
+                .map(PathBuf::from)
+                .collect::<Vec<PathBuf>>();
+            assert_eq!(order_slides(input, mode), expected, "case: {}", name);
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use tracing::debug;
+
+use crate::presentation::slide::hashed_name;
+
+/// A remote image that has been downloaded to be bundled with the presentation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DownloadedImage {
+    /// URL the image was downloaded from, as referenced by the slides
+    pub url: String,
+    /// Where the image is placed, relative to the output directory
+    pub dst_path: PathBuf,
+    /// Contents of the image
+    pub bytes: Vec<u8>,
+}
+
+/// Downloads the image at the given URL
+///
+/// # Arguments
+/// * `url` - URL of the image
+/// * `image_root` - Directory to place the image in, relative to the output directory
+///
+/// # Returns
+/// The image, to be placed at `./<image_root>/<hash of its contents>.<extension in the URL>`
+///
+/// # Errors
+/// If the image could not be downloaded, including if the server does not respond with a success
+pub fn download_image(url: &str, image_root: &Path) -> Result<DownloadedImage, anyhow::Error> {
+    debug!("Downloading `{}`", url);
+    let bytes = ureq::get(url)
+        .call()
+        .with_context(|| format!("Could not download `{}`", url))?
+        .body_mut()
+        .read_to_vec()
+        .with_context(|| format!("Could not read `{}`", url))?;
+    let dst_path = hashed_name(&bytes, url_extension(url), image_root);
+    Ok(DownloadedImage {
+        url: url.to_string(),
+        dst_path,
+        bytes,
+    })
+}
+
+/// Returns the extension of the last segment of the URL's path, if any
+fn url_extension(url: &str) -> Option<&OsStr> {
+    let path = url
+        .split(['?', '#'])
+        .next()
+        .unwrap_or_default()
+        .split_once("://")
+        .map(|(_, rest)| rest)?;
+    // the host has no extension to speak of
+    let (_, path) = path.split_once('/')?;
+    Path::new(path).extension()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_url_extension() {
+        let cases = [
+            ("https://example.com/pic.png", Some("png")),
+            ("https://example.com/a/pic.jpeg?size=large#top", Some("jpeg")),
+            ("https://example.com/pic", None),
+            ("https://example.com", None),
+            ("https://example.com/", None),
+        ];
+        for (url, expected) in cases {
+            assert_eq!(url_extension(url), expected.map(OsStr::new), "{}", url);
+        }
+    }
+}
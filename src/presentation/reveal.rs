@@ -0,0 +1,449 @@
+use std::fs;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{bail, Context};
+use flate2::read::GzDecoder;
+use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
+use std::fmt;
+use std::str::FromStr;
+use tracing::debug;
+
+use crate::presentation::slide::sha256_hex;
+
+/// Version of reveal.js used unless configured otherwise
+pub const DEFAULT_REVEAL_VERSION: &str = "4.6.0";
+/// Where reveal.js release tarballs are downloaded from unless configured otherwise.
+/// `{version}` is replaced by the version of reveal.js
+pub const DEFAULT_REVEAL_URL: &str =
+    "https://github.com/hakimel/reveal.js/archive/refs/tags/{version}.tar.gz";
+/// Directory that downloaded reveal.js releases are cached in
+pub const REVEAL_CACHE_DIR: &str = ".mkrevealslides-cache";
+/// Number of hex characters of the hash of the URL in the name of a cached release tarball
+const URL_HASH_LEN: usize = 12;
+/// Directories of a reveal.js release that are bundled with the presentation
+const BUNDLED_DIRS: [&str; 2] = ["dist", "plugin"];
+/// Theme of reveal.js used unless configured otherwise
+pub const DEFAULT_REVEAL_THEME: &str = "black";
+/// Themes that ship with reveal.js, each a stylesheet in `dist/theme/`
+pub const REVEAL_THEMES: [&str; 12] = [
+    "black",
+    "white",
+    "league",
+    "beige",
+    "sky",
+    "night",
+    "serif",
+    "simple",
+    "solarized",
+    "blood",
+    "moon",
+    "dracula",
+];
+/// Formats of the slide number that reveal.js understands
+pub const SLIDE_NUMBER_FORMATS: [&str; 4] = ["h.v", "h/v", "c", "c/t"];
+
+/// How reveal.js is made available to the presentation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RevealOptions {
+    /// Bundles reveal.js into the output directory instead of loading it from a CDN
+    pub bundle: bool,
+    /// Version of reveal.js to use
+    pub version: String,
+    /// Where the release tarball is downloaded from, with `{version}` replaced by `version`
+    pub url: String,
+}
+
+impl Default for RevealOptions {
+    fn default() -> Self {
+        RevealOptions {
+            bundle: false,
+            version: DEFAULT_REVEAL_VERSION.to_string(),
+            url: DEFAULT_REVEAL_URL.to_string(),
+        }
+    }
+}
+
+impl RevealOptions {
+    /// Returns where the template should load reveal.js from:
+    /// the output directory itself if it is bundled, or a CDN otherwise
+    pub fn base_path(&self) -> String {
+        if self.bundle {
+            ".".to_string()
+        } else {
+            format!("https://cdn.jsdelivr.net/npm/reveal.js@{}", self.version)
+        }
+    }
+
+    /// Checks if `version` looks like a version of reveal.js, such as `4.6.0` or `5.0.0-beta.1`.
+    /// It ends up in file names and URLs, so only letters, digits, `.` and `-` are allowed,
+    /// and it may not start with a `.`
+    pub fn is_valid_version(&self) -> bool {
+        !self.version.is_empty()
+            && !self.version.starts_with('.')
+            && self
+                .version
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-')
+    }
+
+    /// Returns the URL of the release tarball, with `{version}` replaced by `version`
+    fn tarball_url(&self) -> String {
+        self.url.replace("{version}", &self.version)
+    }
+}
+
+/// Options the template passes to `Reveal.initialize()`, through the `reveal_config` variable.
+/// Options that are not set are left out, so that reveal.js uses its defaults
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RevealConfig {
+    /// Reflects the current slide in the URL
+    pub hash: bool,
+    /// Width of the presentation in pixels, before it is scaled to fit the window
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<f64>,
+    /// Height of the presentation in pixels, before it is scaled to fit the window
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<f64>,
+    /// Space around the slides, as a fraction of their size
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub margin: Option<f64>,
+    /// Whether and how the number of the current slide is shown
+    #[serde(rename = "slideNumber", skip_serializing_if = "Option::is_none")]
+    pub slide_number: Option<SlideNumber>,
+    /// Whether the presentation is shown as slides or as a page to scroll through
+    #[serde(skip_serializing_if = "ViewMode::is_slides")]
+    pub view: ViewMode,
+}
+
+/// How reveal.js shows the presentation
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ViewMode {
+    /// One slide at a time, as a presentation
+    #[default]
+    Slides,
+    /// Every slide one after the other, to be read by scrolling. Needs reveal.js 5 or later
+    Scroll,
+}
+
+impl ViewMode {
+    /// Checks if this is the default view, which reveal.js does not need to be told about
+    pub fn is_slides(&self) -> bool {
+        *self == ViewMode::Slides
+    }
+}
+
+/// Whether and how reveal.js shows the number of the current slide
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum SlideNumber {
+    /// Shows the number in the default format if true, or hides it
+    Enabled(bool),
+    /// Shows the number in a format such as `c/t`, which should be one of `SLIDE_NUMBER_FORMATS`
+    Format(String),
+}
+
+impl SlideNumber {
+    /// Checks if reveal.js understands this setting
+    pub fn is_valid(&self) -> bool {
+        match self {
+            SlideNumber::Enabled(_) => true,
+            SlideNumber::Format(format) => SLIDE_NUMBER_FORMATS.contains(&format.as_str()),
+        }
+    }
+}
+
+/// Parses `true` and `false` as whether to show the number, and anything else as a format
+impl FromStr for SlideNumber {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "true" => SlideNumber::Enabled(true),
+            "false" => SlideNumber::Enabled(false),
+            format => SlideNumber::Format(format.to_string()),
+        })
+    }
+}
+
+impl fmt::Display for SlideNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SlideNumber::Enabled(enabled) => write!(f, "{}", enabled),
+            SlideNumber::Format(format) => write!(f, "{}", format),
+        }
+    }
+}
+
+impl Default for RevealConfig {
+    fn default() -> Self {
+        RevealConfig {
+            hash: true,
+            width: None,
+            height: None,
+            margin: None,
+            slide_number: None,
+            view: ViewMode::Slides,
+        }
+    }
+}
+
+impl RevealConfig {
+    /// Returns the options as a JSON object
+    pub fn to_json(&self) -> String {
+        tera::to_value(self)
+            .expect("options to be representable as JSON")
+            .to_string()
+    }
+}
+
+/// A file of reveal.js that is bundled with the presentation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RevealFile {
+    /// Where the file is placed, relative to the output directory
+    pub dst_path: PathBuf,
+    /// Contents of the file
+    pub bytes: Vec<u8>,
+}
+
+/// Returns the path of the release tarball of reveal.js, downloading it into
+/// `cache_dir` unless it is already there
+///
+/// # Arguments
+/// * `options` - The version of reveal.js and where to download it from
+/// * `cache_dir` - Directory to cache release tarballs in, created if it does not exist
+///
+/// # Errors
+/// - If the version is not valid, see `RevealOptions::is_valid_version`
+/// - If the tarball could not be downloaded or cached
+pub fn fetch_reveal(options: &RevealOptions, cache_dir: &Path) -> Result<PathBuf, anyhow::Error> {
+    let tarball = cache_dir.join(cached_tarball_name(options)?);
+    if tarball.is_file() {
+        debug!("Using cached reveal.js at `{}`", tarball.display());
+        return Ok(tarball);
+    }
+    let url = options.tarball_url();
+    debug!("Downloading reveal.js from `{}`", url);
+    let bytes = ureq::get(&url)
+        .call()
+        .with_context(|| format!("Could not download reveal.js from `{}`", url))?
+        .body_mut()
+        .with_config()
+        .limit(u64::MAX)
+        .read_to_vec()
+        .with_context(|| format!("Could not read reveal.js from `{}`", url))?;
+    fs::create_dir_all(cache_dir)
+        .with_context(|| format!("Could not create cache directory `{}`", cache_dir.display()))?;
+    fs::write(&tarball, bytes)
+        .with_context(|| format!("Could not cache reveal.js at `{}`", tarball.display()))?;
+    Ok(tarball)
+}
+
+/// Returns the name of the cached release tarball of reveal.js, which is keyed on both the
+/// version and the URL, so that a tarball from another URL is not mistaken for it.
+/// Such as `reveal.js-4.6.0-<first 12 hex chars of the SHA-256 of the URL>.tar.gz`
+///
+/// # Errors
+/// If the version is not valid, see `RevealOptions::is_valid_version`
+fn cached_tarball_name(options: &RevealOptions) -> Result<String, anyhow::Error> {
+    if !options.is_valid_version() {
+        bail!("`{}` is not a valid version of reveal.js", options.version);
+    }
+    let url_hash = sha256_hex(options.tarball_url().as_bytes());
+    Ok(format!("reveal.js-{}-{}.tar.gz", options.version, &url_hash[..URL_HASH_LEN]))
+}
+
+/// Reads the files of a reveal.js release tarball that are bundled with the presentation,
+/// which are those in its `dist/` and `plugin/` directories
+///
+/// # Arguments
+/// * `tarball` - Path to the gzipped release tarball, whose entries are all inside one top-level directory
+///
+/// # Errors
+/// - If the tarball could not be read
+/// - If an entry of the tarball has a path that could lead outside of the output directory,
+///   such as one with a `..` component
+pub fn read_reveal_files(tarball: &Path) -> Result<Vec<RevealFile>, anyhow::Error> {
+    let file = fs::File::open(tarball)
+        .with_context(|| format!("Could not open `{}`", tarball.display()))?;
+    let mut archive = tar::Archive::new(GzDecoder::new(file));
+    let mut files = Vec::new();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let path = entry.path()?.into_owned();
+        if !path.components().all(|c| matches!(c, Component::Normal(_))) {
+            bail!("`{}` in `{}` is not a plain relative path", path.display(), tarball.display());
+        }
+        // drop the top-level directory, such as `reveal.js-4.6.0/`
+        let dst_path = path.components().skip(1).collect::<PathBuf>();
+        let is_bundled = matches!(
+            dst_path.components().next(),
+            Some(Component::Normal(dir)) if BUNDLED_DIRS.iter().any(|d| dir == *d)
+        );
+        if !is_bundled {
+            continue;
+        }
+        let mut bytes = Vec::new();
+        entry.read_to_end(&mut bytes)?;
+        files.push(RevealFile { dst_path, bytes });
+    }
+    debug!("Read {} files of reveal.js from `{}`", files.len(), tarball.display());
+    Ok(files)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_read_reveal_files() {
+        let tmp_dir = tempdir().unwrap();
+        let tarball = tmp_dir.path().join("reveal.tar.gz");
+        let mut builder = tar::Builder::new(GzEncoder::new(
+            fs::File::create(&tarball).unwrap(),
+            Compression::default(),
+        ));
+        for (path, contents) in [
+            ("reveal.js-1.0.0/dist/reveal.js", "reveal"),
+            ("reveal.js-1.0.0/plugin/notes/notes.js", "notes"),
+            ("reveal.js-1.0.0/README.md", "readme"),
+            ("reveal.js-1.0.0/test/dist/other.js", "other"),
+        ] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, path, contents.as_bytes()).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+
+        let files = read_reveal_files(&tarball).unwrap();
+        assert_eq!(
+            files,
+            vec![
+                RevealFile {
+                    dst_path: PathBuf::from("dist/reveal.js"),
+                    bytes: b"reveal".to_vec(),
+                },
+                RevealFile {
+                    dst_path: PathBuf::from("plugin/notes/notes.js"),
+                    bytes: b"notes".to_vec(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_reveal_files_rejects_parent_dirs() {
+        let tmp_dir = tempdir().unwrap();
+        let tarball = tmp_dir.path().join("reveal.tar.gz");
+        let mut builder = tar::Builder::new(GzEncoder::new(
+            fs::File::create(&tarball).unwrap(),
+            Compression::default(),
+        ));
+        // `set_path` refuses `..`, so the name is written into the header directly
+        let path = b"reveal.js-1.0.0/dist/../../evil.js";
+        let mut header = tar::Header::new_gnu();
+        header.as_old_mut().name[..path.len()].copy_from_slice(path);
+        header.set_size(4);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append(&header, &b"evil"[..]).unwrap();
+        builder.into_inner().unwrap().finish().unwrap();
+
+        let err = read_reveal_files(&tarball).unwrap_err().to_string();
+        assert!(err.contains("evil.js"));
+        assert!(err.contains("is not a plain relative path"));
+    }
+
+    #[test]
+    fn test_cached_tarball_name() {
+        let options = RevealOptions::default();
+        let name = cached_tarball_name(&options).unwrap();
+        assert!(name.starts_with("reveal.js-4.6.0-"));
+        assert!(name.ends_with(".tar.gz"));
+        let mirror = RevealOptions {
+            url: "https://mirror.example.com/reveal.js-{version}.tar.gz".to_string(),
+            ..RevealOptions::default()
+        };
+        assert_ne!(cached_tarball_name(&mirror).unwrap(), name);
+
+        for version in ["../x", "..", "", "4.6.0/evil", ".hidden"] {
+            let options = RevealOptions {
+                version: version.to_string(),
+                ..RevealOptions::default()
+            };
+            assert!(!options.is_valid_version(), "{}", version);
+            assert!(cached_tarball_name(&options).is_err());
+        }
+        let beta = RevealOptions {
+            version: "5.0.0-beta.1".to_string(),
+            ..RevealOptions::default()
+        };
+        assert!(beta.is_valid_version());
+    }
+
+    #[test]
+    fn test_reveal_config_to_json() {
+        assert_eq!(RevealConfig::default().to_json(), r#"{"hash":true}"#);
+        let config = RevealConfig {
+            width: Some(1280.0),
+            margin: Some(0.1),
+            ..RevealConfig::default()
+        };
+        assert_eq!(config.to_json(), r#"{"hash":true,"margin":0.1,"width":1280.0}"#);
+        let config = RevealConfig {
+            slide_number: Some(SlideNumber::Format("c/t".to_string())),
+            ..RevealConfig::default()
+        };
+        assert_eq!(config.to_json(), r#"{"hash":true,"slideNumber":"c/t"}"#);
+        let config = RevealConfig {
+            slide_number: Some(SlideNumber::Enabled(true)),
+            ..RevealConfig::default()
+        };
+        assert_eq!(config.to_json(), r#"{"hash":true,"slideNumber":true}"#);
+        let config = RevealConfig {
+            view: ViewMode::Scroll,
+            ..RevealConfig::default()
+        };
+        assert_eq!(config.to_json(), r#"{"hash":true,"view":"scroll"}"#);
+        let config = RevealConfig {
+            view: ViewMode::Slides,
+            ..RevealConfig::default()
+        };
+        assert_eq!(config.to_json(), r#"{"hash":true}"#);
+    }
+
+    #[test]
+    fn test_slide_number() {
+        assert_eq!("true".parse::<SlideNumber>().unwrap(), SlideNumber::Enabled(true));
+        assert_eq!("h.v".parse::<SlideNumber>().unwrap(), SlideNumber::Format("h.v".to_string()));
+        assert!(SlideNumber::Enabled(false).is_valid());
+        assert!(SlideNumber::Format("c/t".to_string()).is_valid());
+        assert!(!SlideNumber::Format("t/c".to_string()).is_valid());
+        let numbers: Vec<SlideNumber> = serde_yaml::from_str("[true, \"c\"]").unwrap();
+        assert_eq!(numbers, vec![SlideNumber::Enabled(true), SlideNumber::Format("c".to_string())]);
+    }
+
+    #[test]
+    fn test_base_path() {
+        let options = RevealOptions {
+            version: "1.2.3".to_string(),
+            ..RevealOptions::default()
+        };
+        assert_eq!(options.base_path(), "https://cdn.jsdelivr.net/npm/reveal.js@1.2.3");
+        let options = RevealOptions {
+            bundle: true,
+            ..options
+        };
+        assert_eq!(options.base_path(), ".");
+    }
+}
@@ -0,0 +1,335 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+use serde_yaml::Mapping;
+use tracing::{debug, trace};
+
+use crate::presentation::slide::front_matter::SlideMeta;
+use crate::presentation::slide::{sha256_hex, SlideFile, SlideOptions};
+
+/// Directory, inside the cache directory next to the config file, that slides are cached in on disk
+pub const SLIDE_CACHE_DIR: &str = "slides";
+
+/// Modification time of a slide file and of each of its local images, copied or embedded, when it was read
+type ModifiedTimes = Vec<(PathBuf, Option<SystemTime>)>;
+
+/// A slide that has been read, with what it was read from
+#[derive(Debug)]
+struct CachedSlide {
+    /// Options the slide was read with, without the cache itself
+    options: SlideOptions,
+    modified: ModifiedTimes,
+    slide: SlideFile,
+}
+
+impl CachedSlide {
+    /// Checks if the slide would still be read the same way
+    fn is_fresh(&self, options: &SlideOptions) -> bool {
+        self.options == uncached(options)
+            && self.modified.iter().all(|(path, modified)| &modified_time(path) == modified)
+    }
+}
+
+/// A slide as it is cached on disk, with everything needed to recreate it without reading it
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredSlide {
+    modified: ModifiedTimes,
+    filename: String,
+    contents: String,
+    markdown: String,
+    local_images: Vec<(PathBuf, PathBuf)>,
+    embedded_images: Vec<PathBuf>,
+    remote_images: Vec<String>,
+    remote_links: Vec<String>,
+    meta: SlideMeta,
+    front_matter: Mapping,
+}
+
+/// Slides kept in memory between builds, so that a rebuild only reads the slides
+/// that have changed since, or whose local images have.
+/// Slides can also be kept on disk, so that they are reused by later runs too
+#[derive(Debug, Default)]
+pub struct SlideCache {
+    /// Slides by their path and their 1-based position in the presentation
+    slides: Mutex<HashMap<(PathBuf, usize), CachedSlide>>,
+    /// Directory that slides are also cached in, if any
+    dir: Option<PathBuf>,
+    /// Number of slides that were read from the disk
+    reads: AtomicUsize,
+}
+
+/// A cache is only equal to itself, so that options sharing a cache compare equal
+impl PartialEq for SlideCache {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl Eq for SlideCache {}
+
+impl SlideCache {
+    /// Creates a cache that also keeps slides in `dir`, which is created when the first slide is stored
+    pub fn on_disk(dir: PathBuf) -> Self {
+        SlideCache {
+            dir: Some(dir),
+            ..SlideCache::default()
+        }
+    }
+
+    /// Returns the slide at the given path and position, reading it only if it is not
+    /// in the cache, in memory or on disk, or has changed since it was read
+    ///
+    /// # Errors
+    /// The same as `SlideFile::read_and_parse_nth`. Slides that fail to read are not cached
+    pub fn get_or_read(
+        &self,
+        path: PathBuf,
+        slide_index: usize,
+        options: &SlideOptions,
+    ) -> Result<SlideFile, anyhow::Error> {
+        let key = (path, slide_index);
+        if let Some(cached) = self.slides.lock().expect("no reader to have panicked").get(&key) {
+            if cached.is_fresh(options) {
+                trace!("Using cached slide `{}`", key.0.display());
+                return Ok(cached.slide.clone());
+            }
+        }
+        let (slide, modified) = match self.load_cached(&key.0, slide_index, options) {
+            Some(loaded) => loaded,
+            None => {
+                self.reads.fetch_add(1, Ordering::Relaxed);
+                // the modification times are taken first, so that a change while reading is not missed
+                let slide_modified = modified_time(&key.0);
+                let slide = SlideFile::read_and_parse_nth(&key.0, slide_index, options)?;
+                let images = slide.local_images.iter().map(|(src, _)| src).chain(&slide.embedded_images);
+                let modified = std::iter::once((key.0.clone(), slide_modified))
+                    .chain(images.map(|src| (src.clone(), modified_time(src))))
+                    .collect::<ModifiedTimes>();
+                self.store_cached(&slide, slide_index, options, &modified);
+                (slide, modified)
+            }
+        };
+        let cached = CachedSlide {
+            options: uncached(options),
+            modified,
+            slide: slide.clone(),
+        };
+        self.slides.lock().expect("no reader to have panicked").insert(key, cached);
+        Ok(slide)
+    }
+
+    /// Returns how many slides have been read from the disk, rather than taken from the cache
+    pub fn reads(&self) -> usize {
+        self.reads.load(Ordering::Relaxed)
+    }
+
+    /// Returns where the slide at the given path and position, read with the given options,
+    /// is cached on disk, if slides are cached on disk
+    fn entry_path(&self, path: &Path, slide_index: usize, options: &SlideOptions) -> Option<PathBuf> {
+        let options = serde_yaml::to_string(options).expect("options to be representable as YAML");
+        let key = format!("{}\n{}\n{}", path.display(), slide_index, options);
+        Some(self.dir.as_ref()?.join(format!("{}.yml", sha256_hex(key.as_bytes()))))
+    }
+
+    /// Loads the slide at the given path and position from the disk cache, along with the
+    /// modification times it was read at. An entry is removed if the slide or any of its
+    /// local images have changed since, or if it cannot be read
+    fn load_cached(
+        &self,
+        path: &Path,
+        slide_index: usize,
+        options: &SlideOptions,
+    ) -> Option<(SlideFile, ModifiedTimes)> {
+        let entry_path = self.entry_path(path, slide_index, options)?;
+        let entry = fs::read_to_string(&entry_path).ok()?;
+        let stored = match serde_yaml::from_str::<StoredSlide>(&entry) {
+            Ok(stored) if stored.modified.iter().all(|(p, modified)| &modified_time(p) == modified) => stored,
+            _ => {
+                debug!("Removing stale cache entry for `{}`", path.display());
+                let _ = fs::remove_file(&entry_path);
+                return None;
+            }
+        };
+        trace!("Using slide `{}` cached on disk", path.display());
+        let slide = SlideFile {
+            filename: stored.filename,
+            path: path.to_path_buf(),
+            contents: stored.contents,
+            markdown: stored.markdown,
+            local_images: stored.local_images,
+            embedded_images: stored.embedded_images,
+            remote_images: stored.remote_images,
+            remote_links: stored.remote_links,
+            meta: stored.meta,
+            front_matter: stored.front_matter,
+        };
+        Some((slide, stored.modified))
+    }
+
+    /// Stores a slide that has just been read in the disk cache. A slide that cannot be
+    /// stored is only logged, since it is read again the next time instead
+    fn store_cached(
+        &self,
+        slide: &SlideFile,
+        slide_index: usize,
+        options: &SlideOptions,
+        modified: &[(PathBuf, Option<SystemTime>)],
+    ) {
+        let Some(entry_path) = self.entry_path(&slide.path, slide_index, options) else {
+            return;
+        };
+        let stored = StoredSlide {
+            modified: modified.to_vec(),
+            filename: slide.filename.clone(),
+            contents: slide.contents.clone(),
+            markdown: slide.markdown.clone(),
+            local_images: slide.local_images.clone(),
+            embedded_images: slide.embedded_images.clone(),
+            remote_images: slide.remote_images.clone(),
+            remote_links: slide.remote_links.clone(),
+            meta: slide.meta.clone(),
+            front_matter: slide.front_matter.clone(),
+        };
+        let stored = serde_yaml::to_string(&stored)
+            .map_err(std::io::Error::other)
+            .and_then(|yaml| {
+                fs::create_dir_all(entry_path.parent().expect("entry to be in the cache directory"))?;
+                fs::write(&entry_path, yaml)
+            });
+        if let Err(e) = stored {
+            debug!("Could not cache `{}` on disk: {}", slide.path.display(), e);
+        }
+    }
+}
+
+/// Returns a copy of the options without a cache, so that cached slides do not keep their cache alive
+fn uncached(options: &SlideOptions) -> SlideOptions {
+    SlideOptions {
+        cache: None,
+        ..options.clone()
+    }
+}
+
+/// Returns the modification time of a file, if it can be read
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_get_or_read() {
+        let tmp_dir = tempdir().unwrap();
+        let slide = fs::canonicalize(tmp_dir.path()).unwrap().join("1_slide.md");
+        fs::write(&slide, "Slide").unwrap();
+        let cache = Arc::new(SlideCache::default());
+        let options = SlideOptions {
+            cache: Some(cache.clone()),
+            ..SlideOptions::default()
+        };
+
+        assert_eq!(cache.get_or_read(slide.clone(), 1, &options).unwrap().contents, "<p>Slide</p>\n");
+        cache.get_or_read(slide.clone(), 1, &options).unwrap();
+        assert_eq!(cache.reads(), 1);
+        // a different position or different options read the slide again
+        cache.get_or_read(slide.clone(), 2, &options).unwrap();
+        let other_options = SlideOptions {
+            image_root: PathBuf::from("images"),
+            ..options.clone()
+        };
+        cache.get_or_read(slide.clone(), 1, &other_options).unwrap();
+        assert_eq!(cache.reads(), 3);
+
+        fs::write(&slide, "Changed").unwrap();
+        let file = fs::File::options().write(true).open(&slide).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(10)).unwrap();
+        assert_eq!(cache.get_or_read(slide.clone(), 1, &options).unwrap().contents, "<p>Changed</p>\n");
+        assert_eq!(cache.reads(), 4);
+        tmp_dir.close().unwrap();
+    }
+
+    #[test]
+    fn test_disk_cache() {
+        let tmp_dir = tempdir().unwrap();
+        let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).unwrap();
+        let slide = tmp_dir_pth.join("1_slide.md");
+        fs::write(&slide, "---\ntransition: zoom\n---\n# Slide").unwrap();
+        let cache_dir = tmp_dir_pth.join(".mkrevealslides-cache").join(SLIDE_CACHE_DIR);
+        let options = SlideOptions::default();
+
+        let cache = SlideCache::on_disk(cache_dir.clone());
+        let read = cache.get_or_read(slide.clone(), 1, &options).unwrap();
+        assert_eq!(fs::read_dir(&cache_dir).unwrap().count(), 1);
+
+        // a new cache, as in a later run, loads the slide from the disk instead of reading it
+        let cache = SlideCache::on_disk(cache_dir.clone());
+        let (loaded, _) = cache.load_cached(&slide, 1, &options).unwrap();
+        assert_eq!(loaded, read);
+        assert_eq!(loaded.meta.transition.as_deref(), Some("zoom"));
+        cache.get_or_read(slide.clone(), 1, &options).unwrap();
+        assert_eq!(cache.reads(), 0);
+
+        fs::write(&slide, "# Changed").unwrap();
+        let file = fs::File::options().write(true).open(&slide).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(10)).unwrap();
+        let cache = SlideCache::on_disk(cache_dir.clone());
+        assert!(cache.load_cached(&slide, 1, &options).is_none());
+        assert_eq!(fs::read_dir(&cache_dir).unwrap().count(), 0);
+        let refreshed = cache.get_or_read(slide.clone(), 1, &options).unwrap();
+        assert_eq!(refreshed.contents, "<h1>Changed</h1>\n");
+        assert_eq!(cache.reads(), 1);
+        let (loaded, _) = cache.load_cached(&slide, 1, &options).unwrap();
+        assert_eq!(loaded, refreshed);
+        tmp_dir.close().unwrap();
+    }
+
+    #[test]
+    fn test_embedded_image_change_reads_slide_again() {
+        let tmp_dir = tempdir().unwrap();
+        let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).unwrap();
+        let slide = tmp_dir_pth.join("1_slide.md");
+        let img = tmp_dir_pth.join("img.png");
+        fs::write(&slide, "![](img.png)").unwrap();
+        fs::write(&img, "old").unwrap();
+        let cache_dir = tmp_dir_pth.join(".mkrevealslides-cache").join(SLIDE_CACHE_DIR);
+        let cache = Arc::new(SlideCache::on_disk(cache_dir.clone()));
+        let options = SlideOptions {
+            embed_images: true,
+            cache: Some(cache.clone()),
+            ..SlideOptions::default()
+        };
+
+        let read = cache.get_or_read(slide.clone(), 1, &options).unwrap();
+        assert_eq!(read.embedded_images, vec![img.clone()]);
+        assert!(read.local_images.is_empty());
+        cache.get_or_read(slide.clone(), 1, &options).unwrap();
+        assert_eq!(cache.reads(), 1);
+
+        fs::write(&img, "new").unwrap();
+        let file = fs::File::options().write(true).open(&img).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(10)).unwrap();
+        // a new cache, as in a later run, does not load the stale slide from the disk
+        let later_run = SlideCache::on_disk(cache_dir.clone());
+        assert!(later_run.load_cached(&slide, 1, &options).is_none());
+        // and the running cache reads the slide again too
+        let refreshed = cache.get_or_read(slide.clone(), 1, &options).unwrap();
+        assert_eq!(cache.reads(), 2);
+        assert_ne!(refreshed.markdown, read.markdown);
+        assert!(refreshed.markdown.contains(&STANDARD.encode("new")));
+        let (loaded, _) = later_run.load_cached(&slide, 1, &options).unwrap();
+        assert_eq!(loaded, refreshed);
+        tmp_dir.close().unwrap();
+    }
+}
@@ -0,0 +1,199 @@
+use serde::{Deserialize, Serialize};
+use serde_yaml::{Mapping, Value};
+
+/// Metadata that can be set for a single slide in a YAML front matter block
+/// at the very top of the slide file, delimited by `---` lines.
+///
+/// ```markdown
+/// ---
+/// auto_slide_ms: 5000
+/// ---
+/// # My slide
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SlideMeta {
+    /// Advance to the next slide automatically after this many milliseconds
+    pub auto_slide_ms: Option<u64>,
+    /// Leave this slide out of the presentation, unless drafts are included
+    pub draft: bool,
+    /// Background color of the slide, as a hex color or a CSS color name.
+    /// May also be given as `background`
+    #[serde(alias = "background")]
+    pub background_color: Option<String>,
+    /// Transition of the slide, such as `fade` or `zoom`
+    pub transition: Option<String>,
+    /// Background image of the slide, as linked from the presentation.
+    /// Only set for the slides of an image slideshow, not by front matter
+    #[serde(skip_deserializing)]
+    pub background_image: Option<String>,
+}
+
+/// Named colors that CSS understands, besides hex colors
+const CSS_COLOR_NAMES: [&str; 150] = [
+    "aliceblue", "antiquewhite", "aqua", "aquamarine", "azure", "beige", "bisque", "black",
+    "blanchedalmond", "blue", "blueviolet", "brown", "burlywood", "cadetblue", "chartreuse",
+    "chocolate", "coral", "cornflowerblue", "cornsilk", "crimson", "cyan", "darkblue", "darkcyan",
+    "darkgoldenrod", "darkgray", "darkgreen", "darkgrey", "darkkhaki", "darkmagenta",
+    "darkolivegreen", "darkorange", "darkorchid", "darkred", "darksalmon", "darkseagreen",
+    "darkslateblue", "darkslategray", "darkslategrey", "darkturquoise", "darkviolet", "deeppink",
+    "deepskyblue", "dimgray", "dimgrey", "dodgerblue", "firebrick", "floralwhite", "forestgreen",
+    "fuchsia", "gainsboro", "ghostwhite", "gold", "goldenrod", "gray", "green", "greenyellow",
+    "grey", "honeydew", "hotpink", "indianred", "indigo", "ivory", "khaki", "lavender",
+    "lavenderblush", "lawngreen", "lemonchiffon", "lightblue", "lightcoral", "lightcyan",
+    "lightgoldenrodyellow", "lightgray", "lightgreen", "lightgrey", "lightpink", "lightsalmon",
+    "lightseagreen", "lightskyblue", "lightslategray", "lightslategrey", "lightsteelblue",
+    "lightyellow", "lime", "limegreen", "linen", "magenta", "maroon", "mediumaquamarine",
+    "mediumblue", "mediumorchid", "mediumpurple", "mediumseagreen", "mediumslateblue",
+    "mediumspringgreen", "mediumturquoise", "mediumvioletred", "midnightblue", "mintcream",
+    "mistyrose", "moccasin", "navajowhite", "navy", "oldlace", "olive", "olivedrab", "orange",
+    "orangered", "orchid", "palegoldenrod", "palegreen", "paleturquoise", "palevioletred",
+    "papayawhip", "peachpuff", "peru", "pink", "plum", "powderblue", "purple", "rebeccapurple",
+    "red", "rosybrown", "royalblue", "saddlebrown", "salmon", "sandybrown", "seagreen",
+    "seashell", "sienna", "silver", "skyblue", "slateblue", "slategray", "slategrey", "snow",
+    "springgreen", "steelblue", "tan", "teal", "thistle", "tomato", "turquoise", "violet",
+    "wheat", "white", "whitesmoke", "yellow", "yellowgreen", "transparent", "currentcolor",
+];
+
+/// Checks if the value looks like a CSS color, that is,
+/// a hex color such as `#1a1a1a` or a CSS color name such as `navy`
+pub fn looks_like_color(value: &str) -> bool {
+    match value.strip_prefix('#') {
+        Some(hex) => {
+            [3, 4, 6, 8].contains(&hex.len()) && hex.chars().all(|c| c.is_ascii_hexdigit())
+        }
+        None => CSS_COLOR_NAMES.contains(&value.to_ascii_lowercase().as_str()),
+    }
+}
+
+impl SlideMeta {
+    /// Returns the attributes that should be placed on this slide's `<section>`
+    ///
+    /// # Returns
+    /// A string of space-prefixed HTML attributes, empty if there are none
+    pub fn section_attributes(&self) -> String {
+        let mut attributes = String::new();
+        if let Some(auto_slide_ms) = self.auto_slide_ms {
+            attributes.push_str(&format!(" data-autoslide=\"{}\"", auto_slide_ms));
+        }
+        if let Some(background_color) = &self.background_color {
+            attributes.push_str(&format!(
+                " data-background-color=\"{}\"",
+                background_color.replace('"', "&quot;")
+            ));
+        }
+        if let Some(transition) = &self.transition {
+            attributes.push_str(&format!(
+                " data-transition=\"{}\"",
+                transition.replace('"', "&quot;")
+            ));
+        }
+        if let Some(background_image) = &self.background_image {
+            attributes.push_str(&format!(
+                " data-background-image=\"{}\"",
+                background_image.replace('"', "&quot;")
+            ));
+        }
+        attributes
+    }
+}
+
+/// Splits a leading front matter block from the rest of a slide.
+/// A block that is not a YAML mapping is not front matter, so that a slide may start with
+/// a `---` thematic break and have another one later on
+///
+/// # Arguments
+/// * `contents` - Full contents of the slide file
+///
+/// # Returns
+/// The mapping inside the front matter block, if there is one, and the remaining markdown
+pub fn split_front_matter(contents: &str) -> (Option<Mapping>, &str) {
+    let rest = match contents
+        .strip_prefix("---\n")
+        .or_else(|| contents.strip_prefix("---\r\n"))
+    {
+        Some(rest) => rest,
+        None => return (None, contents),
+    };
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            return match serde_yaml::from_str(&rest[..offset]) {
+                Ok(Value::Mapping(mapping)) => (Some(mapping), &rest[offset + line.len()..]),
+                _ => (None, contents),
+            };
+        }
+        offset += line.len();
+    }
+    // no closing delimiter, so this is not front matter
+    (None, contents)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_split_front_matter() {
+        let (fm, body) = split_front_matter("---\nauto_slide_ms: 5000\n---\n# Title\n");
+        let fm = fm.unwrap();
+        assert_eq!(fm.len(), 1);
+        assert_eq!(fm.get(&Value::from("auto_slide_ms")), Some(&Value::from(5000)));
+        assert_eq!(body, "# Title\n");
+
+        let (fm, body) = split_front_matter("# Title\n---\nmore\n");
+        assert_eq!(fm, None);
+        assert_eq!(body, "# Title\n---\nmore\n");
+
+        let (fm, body) = split_front_matter("---\nnever closed\n");
+        assert_eq!(fm, None);
+        assert_eq!(body, "---\nnever closed\n");
+    }
+
+    #[test]
+    fn test_split_front_matter_with_horizontal_rules() {
+        let contents = "---\n# Title\n\nSome text.\n---\nMore text.\n";
+        let (fm, body) = split_front_matter(contents);
+        assert_eq!(fm, None);
+        assert_eq!(body, contents);
+
+        let contents = "---\nJust a sentence.\n---\n";
+        let (fm, body) = split_front_matter(contents);
+        assert_eq!(fm, None);
+        assert_eq!(body, contents);
+
+        let contents = "---\n- a list\n---\n";
+        assert_eq!(split_front_matter(contents), (None, contents));
+    }
+
+    #[test]
+    fn test_section_attributes() {
+        assert_eq!(SlideMeta::default().section_attributes(), "");
+        let meta = SlideMeta {
+            auto_slide_ms: Some(5000),
+            ..Default::default()
+        };
+        assert_eq!(meta.section_attributes(), " data-autoslide=\"5000\"");
+        let meta = SlideMeta {
+            background_color: Some("#1a1a1a".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(meta.section_attributes(), " data-background-color=\"#1a1a1a\"");
+        let meta = SlideMeta {
+            transition: Some("zoom".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(meta.section_attributes(), " data-transition=\"zoom\"");
+    }
+
+    #[test]
+    fn test_looks_like_color() {
+        for color in ["#1a1a1a", "#FFF", "#ff000080", "navy", "RebeccaPurple"] {
+            assert!(looks_like_color(color), "{}", color);
+        }
+        for not_color in ["#12345", "#ggg", "1a1a1a", "blurple", ""] {
+            assert!(!looks_like_color(not_color), "{}", not_color);
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use std::ops::Range;
+
+use pulldown_cmark::escape::escape_html;
+use pulldown_cmark::{Event, Options, Parser, Tag};
+
+/// Marks the start of a placeholder for math. From the private use area of Unicode,
+/// so that it means nothing to markdown and is not in any slide
+const PLACEHOLDER_START: char = '\u{E000}';
+/// Marks the end of a placeholder for math
+const PLACEHOLDER_END: char = '\u{E001}';
+
+/// Math taken out of a slide's markdown, so that markdown does not escape or format it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Math {
+    /// The math as written, with its `$` or `$$` delimiters
+    source: String,
+    /// Whether it is `$$` display math rather than `$` inline math
+    display: bool,
+}
+
+impl Math {
+    /// Returns the placeholder that stands in for the `n`th math of a slide
+    fn placeholder(n: usize) -> String {
+        format!("{}{}{}", PLACEHOLDER_START, n, PLACEHOLDER_END)
+    }
+
+    /// Returns the math as HTML, with its delimiters kept for the math plugin of reveal.js to find
+    ///
+    /// # Arguments
+    /// * `block` - Places display math in a `<div>` of its own rather than a `<span>`
+    fn to_html(&self, block: bool) -> String {
+        let mut escaped = String::new();
+        escape_html(&mut escaped, &self.source).expect("writing to a string to succeed");
+        match (self.display, block) {
+            (true, true) => format!("<div class=\"math display\">{}</div>", escaped),
+            (true, false) => format!("<span class=\"math display\">{}</span>", escaped),
+            (false, _) => format!("<span class=\"math inline\">{}</span>", escaped),
+        }
+    }
+}
+
+/// Replaces the `$...$` and `$$...$$` math of markdown with placeholders.
+/// Math in code, and `$` that are escaped or do not delimit math, such as in `$5`, are left as is.
+/// Like in pandoc, inline math must not start or end with whitespace,
+/// and must not be followed by a digit
+///
+/// # Returns
+/// The markdown with placeholders, and the math they stand in for, in order
+pub(crate) fn extract_math(markdown: &str) -> (String, Vec<Math>) {
+    let code = code_ranges(markdown);
+    let in_code = |i: usize| code.iter().any(|range| range.contains(&i));
+    let mut extracted = String::with_capacity(markdown.len());
+    let mut math = Vec::new();
+    let mut last = 0;
+    let mut i = 0;
+    while let Some(offset) = markdown[i..].find('$') {
+        let start = i + offset;
+        i = start + 1;
+        if in_code(start) || markdown[..start].ends_with('\\') {
+            continue;
+        }
+        let rest = &markdown[start..];
+        let end = if let Some(body) = rest.strip_prefix("$$") {
+            body.find("$$").map(|close| (start + 2 + close + 2, true))
+        } else {
+            inline_math_len(rest).map(|len| (start + len, false))
+        };
+        let Some((end, display)) = end else {
+            continue;
+        };
+        if in_code(end - 1) {
+            continue;
+        }
+        extracted.push_str(&markdown[last..start]);
+        extracted.push_str(&Math::placeholder(math.len()));
+        math.push(Math {
+            source: markdown[start..end].to_string(),
+            display,
+        });
+        last = end;
+        i = end;
+    }
+    extracted.push_str(&markdown[last..]);
+    (extracted, math)
+}
+
+/// Returns the length of the inline math at the start of `text`, delimiters included, if it is math
+fn inline_math_len(text: &str) -> Option<usize> {
+    let body = &text[1..];
+    if body.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let close = body.find('$')?;
+    let inner = &body[..close];
+    let after = &body[close + 1..];
+    if inner.is_empty()
+        || inner.contains('\n')
+        || inner.ends_with(char::is_whitespace)
+        || after.starts_with(|c: char| c.is_ascii_digit())
+    {
+        return None;
+    }
+    Some(1 + close + 1)
+}
+
+/// Returns the byte ranges of the code spans and code blocks of markdown
+pub(super) fn code_ranges(markdown: &str) -> Vec<Range<usize>> {
+    Parser::new_ext(markdown, Options::all())
+        .into_offset_iter()
+        .filter(|(event, _)| matches!(event, Event::Code(_) | Event::Start(Tag::CodeBlock(_))))
+        .map(|(_, range)| range)
+        .collect()
+}
+
+/// Puts the math back into the HTML rendered from markdown with placeholders.
+/// Display math that makes up a paragraph on its own replaces the paragraph
+pub(crate) fn restore_math_html(html: &str, math: &[Math]) -> String {
+    let mut restored = html.to_string();
+    for (n, m) in math.iter().enumerate() {
+        let placeholder = Math::placeholder(n);
+        let paragraph = format!("<p>{}</p>", placeholder);
+        restored = if m.display && restored.contains(&paragraph) {
+            restored.replacen(&paragraph, &m.to_html(true), 1)
+        } else {
+            restored.replacen(&placeholder, &m.to_html(false), 1)
+        };
+    }
+    restored
+}
+
+/// Puts the math back into markdown with placeholders, as it was written
+pub(crate) fn restore_math_markdown(markdown: &str, math: &[Math]) -> String {
+    let mut restored = markdown.to_string();
+    for (n, m) in math.iter().enumerate() {
+        restored = restored.replacen(&Math::placeholder(n), &m.source, 1);
+    }
+    restored
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_extract_math() {
+        let (extracted, math) = extract_math("Energy is $E=mc^2$, not $5 or $ 6$.");
+        assert_eq!(extracted, format!("Energy is {}, not $5 or $ 6$.", Math::placeholder(0)));
+        assert_eq!(
+            math,
+            vec![Math {
+                source: "$E=mc^2$".to_string(),
+                display: false
+            }]
+        );
+        assert_eq!(restore_math_markdown(&extracted, &math), "Energy is $E=mc^2$, not $5 or $ 6$.");
+
+        let (_, math) = extract_math("From $5 to $10, `$x$` and \\$y$ stay\n\n```\n$$a$$\n```\n");
+        assert!(math.is_empty());
+
+        let (extracted, math) = extract_math("$$\n\\frac{a_1}{b_2}\n$$\n");
+        assert_eq!(extracted, format!("{}\n", Math::placeholder(0)));
+        assert!(math[0].display);
+    }
+}
@@ -1,33 +1,536 @@
-use crate::errors::ValidationError;
+use crate::errors::{PresentationError, ValidationError};
 use anyhow::Context;
 use std::cmp::Ordering;
+use std::ops::Range;
+use std::ffi::OsStr;
 use std::fs;
-use std::path::{Path, PathBuf};
-use pulldown_cmark::{Event, html, Options, Parser, Tag};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use pulldown_cmark::escape::escape_html;
+use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, html, Options, Parser, Tag};
+use rayon::prelude::*;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use serde_yaml::{Mapping, Value};
+use tracing::warn;
 
-use crate::presentation::io::is_markdown_file;
+use crate::presentation::io::{canonicalize_retry, has_extension};
+use crate::presentation::ordering::{compare_file_names, OrderMode};
+use cache::SlideCache;
+use front_matter::{split_front_matter, SlideMeta};
+use math::{code_ranges, extract_math, restore_math_html, restore_math_markdown};
+
+/// Per-slide metadata from YAML front matter
+pub mod front_matter;
+/// Slides kept in memory between rebuilds
+pub mod cache;
+/// Math that is passed through to reveal.js untouched
+mod math;
+
+/// Directory, relative to the output directory, that images are placed in unless configured otherwise
+pub const DEFAULT_IMAGE_ROOT: &str = "img";
+/// Stands between the parts of a slide file that is split into several slides.
+/// From the private use area of Unicode, like the placeholders for math
+const PART_BREAK: &str = "\u{E002}";
+
+/// Options that control how SlideFiles are read
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SlideOptions {
+    /// File extensions, without the leading `.`, of files that are slides.
+    /// Slides are always read as markdown, whatever their extension
+    pub extensions: Vec<String>,
+    /// Where local images are placed, relative to the output directory.
+    /// See `image_dst_path` for the placeholders that may be used.
+    /// Images are placed according to `hashed_dst_path` if this is not set
+    pub img_path_template: Option<String>,
+    /// Inlines local images into the slides as `data:` URIs instead of
+    /// placing them in the output directory
+    pub embed_images: bool,
+    /// Directory, relative to the output directory, that images are placed in
+    /// according to `hashed_dst_path_in` when there is no `img_path_template`
+    pub image_root: PathBuf,
+    /// How local images are laid out when there is no `img_path_template`
+    pub image_layout: ImageLayout,
+    /// Splits each slide file into several slides at every line containing only this,
+    /// such as `---`, outside of code. See `SlideFile::split`. Each file is one slide if not set
+    pub slide_separator: Option<String>,
+    /// Slides read before, which are reused instead of being read again if they have not changed
+    #[serde(skip)]
+    pub cache: Option<Arc<SlideCache>>,
+}
+
+impl Default for SlideOptions {
+    fn default() -> Self {
+        SlideOptions {
+            extensions: vec!["md".to_string()],
+            img_path_template: None,
+            embed_images: false,
+            image_root: PathBuf::from(DEFAULT_IMAGE_ROOT),
+            image_layout: ImageLayout::default(),
+            slide_separator: None,
+            cache: None,
+        }
+    }
+}
+
+/// How local images are laid out in the output directory when there is no `img_path_template`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageLayout {
+    /// In `image_root`, named after a hash of their contents. See `hashed_dst_path_in`
+    #[default]
+    Hashed,
+    /// At the path their slide links to them by. See `mirrored_dst_path`
+    Mirror,
+}
+
+/// Returns the MIME type of an image from its extension,
+/// or `application/octet-stream` if the extension is not known
+fn image_mime_type(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .and_then(OsStr::to_str)
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "webp" => "image/webp",
+        "avif" => "image/avif",
+        "bmp" => "image/bmp",
+        "ico" => "image/x-icon",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Checks if the file at the given path is an image, by its extension
+pub fn is_image(path: &Path) -> bool {
+    image_mime_type(path) != "application/octet-stream"
+}
+
+/// Returns the image at the given path as a `data:` URI
+///
+/// # Errors
+/// If the image could not be read
+fn image_data_uri(path: &Path) -> Result<String, std::io::Error> {
+    Ok(format!(
+        "data:{};base64,{}",
+        image_mime_type(path),
+        STANDARD.encode(fs::read(path)?)
+    ))
+}
+
+/// Number of hex characters of the SHA-256 of an image used in its destination path
+const IMAGE_HASH_LEN: usize = 12;
+
+/// Returns the destination of an image keyed on a hash of its contents, so that
+/// different images with the same filename do not collide, and identical images
+/// referenced from several places are only copied once
+///
+/// # Arguments
+/// * `src` - Path to the image
+///
+/// # Returns
+/// `./img/<first 12 hex chars of the SHA-256 of the image>.<extension of the image>`
+///
+/// # Errors
+/// If the image could not be read
+pub fn hashed_dst_path(src: &Path) -> Result<PathBuf, std::io::Error> {
+    hashed_dst_path_in(src, Path::new(DEFAULT_IMAGE_ROOT))
+}
+
+/// Returns the destination of an image like `hashed_dst_path`, in another directory
+///
+/// # Arguments
+/// * `src` - Path to the image
+/// * `image_root` - Directory to place the image in, relative to the output directory
+///
+/// # Returns
+/// `./<image_root>/<first 12 hex chars of the SHA-256 of the image>.<extension of the image>`
+///
+/// # Errors
+/// If the image could not be read
+pub fn hashed_dst_path_in(src: &Path, image_root: &Path) -> Result<PathBuf, std::io::Error> {
+    Ok(hashed_name(&fs::read(src)?, src.extension(), image_root))
+}
+
+/// Returns `./<image_root>/<first 12 hex chars of the SHA-256 of the bytes>.<extension>`
+pub(crate) fn hashed_name(bytes: &[u8], extension: Option<&OsStr>, image_root: &Path) -> PathBuf {
+    let hash = sha256_hex(bytes);
+    let mut dst_path = Path::new(".").join(image_root).join(&hash[..IMAGE_HASH_LEN]);
+    if let Some(extension) = extension {
+        dst_path.set_extension(extension);
+    }
+    dst_path
+}
+
+/// Returns the destination of an image that mirrors the link to it from its slide,
+/// so that the output directory has the same layout as the images next to the slides
+///
+/// # Arguments
+/// * `link` - The link to the image, relative to its slide
+///
+/// # Returns
+/// `./<link without any . or .. components>`, such as `./img/diagrams/arch.png`
+/// for `../img/diagrams/arch.png`, or `None` if the link is absolute
+pub fn mirrored_dst_path(link: &Path) -> Option<PathBuf> {
+    if link.has_root() {
+        return None;
+    }
+    let relative = link
+        .components()
+        .filter(|c| matches!(c, Component::Normal(_)))
+        .collect::<PathBuf>();
+    Some(Path::new(".").join(relative))
+}
+
+/// Number of hex characters of the SHA-256 of an image's directory used to tell apart
+/// images that would otherwise be placed at the same path
+const DIR_HASH_LEN: usize = 8;
+
+/// Returns the SHA-256 of the bytes as lowercase hex
+pub(crate) fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Appends a short hash of the image's directory to the filename of its destination,
+/// for when a different image of the same slide would be placed at the same path
+///
+/// # Arguments
+/// * `dst_path` - The destination path that collides
+/// * `src` - Absolute path to the image
+///
+/// # Returns
+/// `dst_path` with `-<first 8 hex chars of the SHA-256 of the image's directory>`
+/// added to its file stem
+fn disambiguated_dst_path(dst_path: &Path, src: &Path) -> PathBuf {
+    let src_dir = src.parent().unwrap_or(src);
+    let hash = sha256_hex(src_dir.to_string_lossy().as_bytes());
+    let mut file_name = dst_path.file_stem().unwrap_or_default().to_os_string();
+    file_name.push("-");
+    file_name.push(&hash[..DIR_HASH_LEN]);
+    if let Some(extension) = dst_path.extension() {
+        file_name.push(".");
+        file_name.push(extension);
+    }
+    dst_path.with_file_name(file_name)
+}
+
+/// Fills in the placeholders of an `img_path_template`
+///
+/// # Arguments
+/// * `template` - The template, which may contain these placeholders
+///   - `{slide_stem}` - The slide's filename without its extension
+///   - `{slide_index}` - The 1-based position of the slide, padded to two digits
+///   - `{file_name}` - The image's filename
+/// * `slide_stem` - The slide's filename without its extension
+/// * `slide_index` - The 1-based position of the slide
+/// * `file_name` - The image's filename
+///
+/// # Returns
+/// The destination path of the image, relative to the output directory
+pub fn image_dst_path(template: &str, slide_stem: &str, slide_index: usize, file_name: &str) -> PathBuf {
+    PathBuf::from(
+        template
+            .replace("{slide_stem}", slide_stem)
+            .replace("{slide_index}", &format!("{:02}", slide_index))
+            .replace("{file_name}", file_name),
+    )
+}
+
+/// Checks whether a slide failed to be read because it could not be read at all,
+/// rather than because what was read is not a valid slide
+fn is_unreadable(path: &Path, e: &anyhow::Error) -> bool {
+    let outermost: &(dyn std::error::Error + Send + Sync + 'static) = e.as_ref();
+    path.file_name().and_then(OsStr::to_str).is_none() || outermost.is::<std::io::Error>()
+}
+
+/// The local images of a slide, as they are found while reading it
+#[derive(Default)]
+struct SlideImages {
+    /// Images that are copied into the output directory, with where they are placed
+    copied: Vec<(PathBuf, PathBuf)>,
+    /// Images that are embedded into the slide as `data:` URIs
+    embedded: Vec<PathBuf>,
+}
+
+/// Returns the link that a local image of a slide is rewritten to: a `data:` URI
+/// if `options.embed_images` is set, or else where the image is placed in the output
+/// directory. The image is added to `images` either way
+///
+/// # Arguments
+/// * `url` - The link to the image in the slide, relative to `slide_dir` unless it is absolute
+/// * `slide_path` - Path to the slide, for errors
+/// * `slide_dir` - Directory of the slide
+/// * `slide_stem` - File name of the slide without its extension, for `{slide_stem}` in image paths
+/// * `slide_index` - The 1-based position of the slide, for `{slide_index}` in image paths
+/// * `options` - Options that control where the image is placed
+/// * `images` - The local images of the slide so far
+///
+/// # Errors
+/// If the image does not exist or cannot be read, or its path is not UTF-8
+fn local_image_link(
+    url: &str,
+    slide_path: &Path,
+    slide_dir: &Path,
+    slide_stem: &str,
+    slide_index: usize,
+    options: &SlideOptions,
+    images: &mut SlideImages,
+) -> Result<String, anyhow::Error> {
+    // drop any `./` so the path joins cleanly onto the slide's directory
+    let img_path = Path::new(url)
+        .components()
+        .filter(|c| c != &Component::CurDir)
+        .collect::<PathBuf>();
+    // an absolute path takes the place of the slide's directory, and is checked the same way
+    let img_path = slide_dir.join(img_path);
+    let img_abs_path = canonicalize_retry(&img_path)
+        .ok()
+        .filter(|p| p.is_file())
+        .ok_or_else(|| PresentationError::ImageNotFound {
+            slide: slide_path.to_path_buf(),
+            image: img_path.clone(),
+        })?;
+    if options.embed_images {
+        let link = image_data_uri(&img_abs_path)
+            .with_context(|| format!("Could not read image `{}`", img_abs_path.display()))?;
+        if !images.embedded.contains(&img_abs_path) {
+            images.embedded.push(img_abs_path);
+        }
+        return Ok(link);
+    }
+    let img_filename = img_abs_path
+        .file_name()
+        .with_context(|| format!("`{}` does not contain a valid filename", img_abs_path.display()))?
+        .to_str()
+        .with_context(|| format!("Filename at `{}` is not UTF-8!", img_abs_path.display()))?;
+    let dst_path = match &options.img_path_template {
+        Some(template) => {
+            let dst_path = image_dst_path(template, slide_stem, slide_index, img_filename);
+            // a different image of this slide already goes there
+            if images.copied.iter().any(|(src, dst)| dst == &dst_path && src != &img_abs_path) {
+                disambiguated_dst_path(&dst_path, &img_abs_path)
+            } else {
+                dst_path
+            }
+        }
+        None => match mirrored_dst_path(Path::new(url)).filter(|_| options.image_layout == ImageLayout::Mirror) {
+            Some(dst_path) => dst_path,
+            None => hashed_dst_path_in(&img_abs_path, &options.image_root)
+                .with_context(|| format!("Could not read image `{}`", img_abs_path.display()))?,
+        },
+    };
+    let link = dst_path
+        .to_str()
+        .with_context(|| format!("Image destination `{}` is not UTF-8", dst_path.display()))?
+        .to_string();
+    // an image referenced more than once is only copied once
+    let image = (img_abs_path, dst_path);
+    if !images.copied.contains(&image) {
+        images.copied.push(image);
+    }
+    Ok(link)
+}
+
+/// Splits the markdown of a slide file into the parts that are each a slide of their own,
+/// at every line containing only `separator` outside of code.
+/// The markdown is a single part if there is no separator
+fn split_parts<'a>(markdown: &'a str, separator: Option<&str>) -> Vec<&'a str> {
+    let Some(separator) = separator.map(str::trim) else {
+        return vec![markdown];
+    };
+    let code = code_ranges(markdown);
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut offset = 0;
+    for line in markdown.split_inclusive('\n') {
+        if line.trim() == separator && !code.iter().any(|range| range.contains(&offset)) {
+            parts.push(&markdown[start..offset]);
+            start = offset + line.len();
+        }
+        offset += line.len();
+    }
+    parts.push(&markdown[start..]);
+    parts
+}
+
+/// Splits the speaker notes of a slide, which follow the first line containing only `Note:`,
+/// from the rest of its markdown
+///
+/// # Returns
+/// The markdown before the marker and, if there is a marker,
+/// the markdown from the marker onwards along with the notes after it
+fn split_notes(markdown: &str) -> (&str, Option<(&str, &str)>) {
+    let mut offset = 0;
+    for line in markdown.split_inclusive('\n') {
+        if line.trim() == "Note:" {
+            let marker_and_notes = &markdown[offset..];
+            return (
+                &markdown[..offset],
+                Some((marker_and_notes, &marker_and_notes[line.len()..])),
+            );
+        }
+        offset += line.len();
+    }
+    (markdown, None)
+}
+
+/// Marker at the end of a list item that makes it a reveal.js fragment
+const FRAGMENT_MARKER: &str = "{.fragment}";
+
+/// Turns each list item whose own text ends with `{.fragment}` into `<li class="fragment">`,
+/// removing the marker from its text. Text of nested lists does not count towards an item's own
+///
+/// # Arguments
+/// * `events` - The events of a slide, as parsed
+fn mark_fragments(mut events: Vec<Event>) -> Vec<Event> {
+    for i in 0..events.len() {
+        if events[i] != Event::Start(Tag::Item) {
+            continue;
+        }
+        // the item's own text ends with its first paragraph, a nested list, or the item itself
+        let last_text = events[i + 1..]
+            .iter()
+            .take_while(|e| {
+                !matches!(
+                    e,
+                    Event::End(Tag::Item) | Event::End(Tag::Paragraph) | Event::Start(Tag::List(_))
+                )
+            })
+            .enumerate()
+            .filter(|(_, e)| matches!(e, Event::Text(_)))
+            .last()
+            .map(|(j, _)| i + 1 + j);
+        let Some(last_text) = last_text else {
+            continue;
+        };
+        let Event::Text(text) = &events[last_text] else {
+            unreachable!("position to be of a text event");
+        };
+        if let Some(text) = text.trim_end().strip_suffix(FRAGMENT_MARKER) {
+            events[last_text] = Event::Text(text.trim_end().to_string().into());
+            events[i] = Event::Html("<li class=\"fragment\">".into());
+        }
+    }
+    events
+}
+
+/// Returns the start of a fenced code block, with the class that reveal.js's highlight plugin
+/// expects for its language, such as `<pre><code class="language-rust">`.
+/// Only the first word of the info string is the language, so `rust,ignore` and
+/// `rust {1,3}` are both highlighted as `rust`. Blocks without a language have no class
+///
+/// # Arguments
+/// * `info` - The info string after the opening fence
+fn code_block_start(info: &str) -> Event<'static> {
+    let language = info
+        .split(|c: char| c.is_whitespace() || c == ',' || c == '{')
+        .next()
+        .unwrap_or_default();
+    if language.is_empty() {
+        return Event::Html("<pre><code>".into());
+    }
+    let mut html = String::from("<pre><code class=\"language-");
+    escape_html(&mut html, language).expect("writing to a string to succeed");
+    html.push_str("\">");
+    Event::Html(html.into())
+}
+
+/// Replaces the link of each image in the markdown
+///
+/// # Arguments
+/// * `markdown` - The markdown to rewrite
+/// * `links` - The byte range of each image in `markdown` in ascending order,
+///   along with its old and new link
+///
+/// # Returns
+/// The markdown with the links replaced. Links that are not found within their image,
+/// such as those of reference-style images, are left as is
+fn rewrite_links(markdown: &str, links: &[(Range<usize>, String, String)]) -> String {
+    let mut rewritten = String::with_capacity(markdown.len());
+    let mut last = 0;
+    for (range, old, new) in links {
+        let image = &markdown[range.clone()];
+        // the link comes right after the alt text, and before any title
+        let link_start = image.find("](").map(|i| i + 2);
+        if let Some(i) = link_start.and_then(|s| image[s..].find(old.as_str()).map(|i| s + i)) {
+            rewritten.push_str(&markdown[last..range.start + i]);
+            rewritten.push_str(new);
+            last = range.start + i + old.len();
+        }
+    }
+    rewritten.push_str(&markdown[last..]);
+    rewritten
+}
 
 /// A SlideFile is a slide that exists as a file on the disk somewhere
-#[derive(PartialEq, Debug, Clone)]
+#[derive(PartialEq, Debug, Clone, Serialize)]
 pub struct SlideFile {
+    #[serde(skip)]
     filename: String,
     /// Absolute path to where this slideFile is located on the disk
     pub path: PathBuf,
     /// Full contents of the SlideFile
+    #[serde(skip)]
     pub contents: String,
+    /// Markdown of the SlideFile without its front matter,
+    /// with local image links rewritten the same way as in `contents`
+    #[serde(skip)]
+    pub markdown: String,
 
     pub local_images: Vec<(PathBuf, PathBuf)>,
+    /// Absolute paths of the local images that are embedded as `data:` URIs with `embed_images`,
+    /// rather than placed in the output directory
+    pub embedded_images: Vec<PathBuf>,
+    /// URLs of images that are not bundled, such as `https://` ones
+    pub remote_images: Vec<String>,
+    /// URLs of `http://` and `https://` links
+    pub remote_links: Vec<String>,
+    /// Metadata from the front matter block of the SlideFile, if any
+    pub meta: SlideMeta,
+    /// Every key of the front matter block of the SlideFile, as parsed
+    #[serde(skip)]
+    front_matter: Mapping,
+}
+
+/// A top-level slide of a presentation, which may be a vertical stack of slides
+#[derive(PartialEq, Debug, Clone)]
+pub enum SlideGroup {
+    /// A single horizontal slide
+    Single(Box<SlideFile>),
+    /// Slides stacked vertically under one horizontal slide, in order
+    Stack(Vec<SlideFile>),
+}
+
+impl SlideGroup {
+    /// Returns the slides of this group, in order
+    pub fn slides(&self) -> &[SlideFile] {
+        match self {
+            SlideGroup::Single(slide) => std::slice::from_ref(slide.as_ref()),
+            SlideGroup::Stack(slides) => slides,
+        }
+    }
 }
 
 impl PartialOrd for SlideFile {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(natord::compare(&self.filename, &other.filename))
+        Some(self.cmp(other))
     }
 }
 
 impl Ord for SlideFile {
+    /// Slides whose file names start with a number come first, in numeric order of that number,
+    /// followed by the rest. Ties are broken by natural order of the file names
     fn cmp(&self, other: &Self) -> Ordering {
-        natord::compare(&self.filename, &other.filename)
+        compare_file_names(&self.filename, &other.filename, OrderMode::NumericPrefix)
     }
 }
 
@@ -35,6 +538,8 @@ impl Eq for SlideFile {}
 
 impl SlideFile {
     /// Reads a SlideFile from the disk.
+    /// A leading YAML front matter block is stripped and parsed into `meta`.
+    /// Anything after a line containing only `Note:` is placed in an `<aside class="notes">` block.
     /// This will also transform any local links to be relative to <OUTPUT_DIR>/img/
     ///
     /// For example, say you have a slide with the following content
@@ -43,8 +548,9 @@ impl SlideFile {
     /// ```
     /// When read in, the `content` of this SlideFile will contain instead
     /// ```markdown
-    /// ![](./img/whatever/image.png)
+    /// ![](./img/<hash of image.png>.png)
     /// ```
+    /// The destination of images can be changed with `SlideOptions::img_path_template`
     ///
     ///
     /// # Arguments
@@ -53,12 +559,46 @@ impl SlideFile {
     /// # Errors
     /// * `ValidationError` - If the SlideFile is not a valid SlideFile
     /// * `std::io::Error` - If there was an error reading the SlideFile
+    /// * `PresentationError::InvalidSlide` - If the front matter does not have the types `SlideMeta` expects
+    /// * `PresentationError::ImageNotFound` - If a local image does not exist
     ///
     /// # Notes
     /// This is a blocking operation since it will read the file from the disk
     /// and attempt to parse it.
     pub fn read_and_parse<P: AsRef<Path>>(path: P) -> Result<Self, anyhow::Error> {
+        Self::read_and_parse_with(path, &SlideOptions::default())
+    }
 
+    /// Reads a SlideFile from the disk, like `read_and_parse`, with the given options
+    ///
+    /// # Arguments
+    /// * `path` - Absolute path to the SlideFile on the disk
+    /// * `options` - Options that control how the SlideFile is read
+    ///
+    /// # Errors
+    /// The same as `read_and_parse`
+    pub fn read_and_parse_with<P: AsRef<Path>>(
+        path: P,
+        options: &SlideOptions,
+    ) -> Result<Self, anyhow::Error> {
+        Self::read_and_parse_nth(path, 1, options)
+    }
+
+    /// Reads a SlideFile from the disk, like `read_and_parse_with`,
+    /// for the slide at the given position in the presentation
+    ///
+    /// # Arguments
+    /// * `path` - Absolute path to the SlideFile on the disk
+    /// * `slide_index` - The 1-based position of the slide, for `{slide_index}` in image paths
+    /// * `options` - Options that control how the SlideFile is read
+    ///
+    /// # Errors
+    /// The same as `read_and_parse`
+    pub fn read_and_parse_nth<P: AsRef<Path>>(
+        path: P,
+        slide_index: usize,
+        options: &SlideOptions,
+    ) -> Result<Self, anyhow::Error> {
         let path = path.as_ref().to_path_buf();
         let filename = path
             .file_name()
@@ -71,59 +611,147 @@ impl SlideFile {
             .to_str()
             .with_context(|| format!("Filename at `{}` is not UTF-8!", path.display()))?
             .to_string();
-        Self::validate_path(&path)?;
+        Self::validate_path(&path, options)?;
+        let slide_stem = path
+            .file_stem()
+            .with_context(|| format!("`{}` does not contain a valid filename", path.display()))?
+            .to_str()
+            .with_context(|| format!("Filename at `{}` is not UTF-8!", path.display()))?
+            .to_string();
         let contents = fs::read_to_string(&path)?;
-        let mut local_images = Vec::new();
+        let (front_matter, markdown) = split_front_matter(&contents);
+        let front_matter = front_matter.unwrap_or_default();
+        let meta: SlideMeta = serde_yaml::from_value(Value::Mapping(front_matter.clone())).map_err(|e| {
+            PresentationError::InvalidSlide {
+                path: path.clone(),
+                reason: format!("Invalid front matter: {}", e),
+            }
+        })?;
+        // math is taken out first, so that markdown does not escape or format it
+        let (markdown, math) = extract_math(markdown);
+        let slide_dir = path
+            .parent()
+            .with_context(|| format!("`{}` is not in a directory", path.display()))?;
+        let mut images = SlideImages::default();
+        let mut remote_images = Vec::new();
+        let mut remote_links = Vec::new();
+        let mut image_error = None;
+        // each part is parsed on its own, so that its notes stay with it, and joined by `PART_BREAK`
+        let mut part_contents = Vec::new();
+        let mut part_markdown = Vec::new();
+        for part in split_parts(&markdown, options.slide_separator.as_deref()) {
+            let (part, notes) = split_notes(part);
+            // byte range of each rewritten image in the markdown, with its old and new link
+            let mut rewritten_links = Vec::new();
 
-        let parser = Parser::new_ext(&contents, Options::all());
-        let parser = parser.map(|event| match event {
-            Event::Start(Tag::Image(link_type, url, title)) => {
-                // check if the image is local
-                if !url.contains("://") {
-                    let img_path = PathBuf::from(url.as_ref());
-                    let img_abs_path = if !img_path.is_absolute() {
-                        let img_abs_path = fs::canonicalize(path.parent()
-                            .expect("slide file to have parent")
-                            .join(img_path))
-                            .expect("img path to exist");
-                        img_abs_path
+            let parser = Parser::new_ext(part, Options::all()).into_offset_iter();
+            let parser = parser.map(|(event, range)| match event {
+                Event::Start(Tag::Image(link_type, url, title)) => {
+                    // check if the image is local
+                    if !url.contains("://") {
+                        match local_image_link(&url, &path, slide_dir, &slide_stem, slide_index, options, &mut images) {
+                            Ok(link) => {
+                                rewritten_links.push((range, url.to_string(), link.clone()));
+                                Event::Start(Tag::Image(link_type, link.into(), title))
+                            }
+                            Err(e) => {
+                                image_error.get_or_insert(e);
+                                Event::Start(Tag::Image(link_type, url, title))
+                            }
+                        }
                     } else {
-                        img_path
-                    };
-                    // this is a local image, let's grab the full path to it
-                    let img_filename = img_abs_path.file_name()
-                        .expect("image to have a valid file name");
-                    // todo: this will BREAK if there are other images with the same name, best to use a hash
-                    // the destination path is ./img/<slide filename>/<img filename>
-                    let dst_path = PathBuf::from("./img")
-                        .join(&filename)
-                        .join(img_filename)
-                        .to_str().expect("can convert to string").to_string();
-                    local_images.push((img_abs_path, PathBuf::from(&dst_path)));
-                    Event::Start(Tag::Image(link_type, dst_path.into(), title))
-                } else {
-                    // don't rewrite the link
-                    Event::Start(Tag::Image(link_type, url, title))
+                        // don't rewrite the link
+                        remote_images.push(url.to_string());
+                        Event::Start(Tag::Image(link_type, url, title))
+                    }
+                },
+                Event::Start(Tag::Link(link_type, url, title)) => {
+                    if url.starts_with("http://") || url.starts_with("https://") {
+                        remote_links.push(url.to_string());
+                    }
+                    Event::Start(Tag::Link(link_type, url, title))
                 }
-            },
-            _ => event
-        });
+                Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info))) => code_block_start(&info),
+                _ => event
+            });
 
-        let mut contents = String::new();
-        html::push_html(&mut contents, parser);
+            let mut contents = String::new();
+            html::push_html(&mut contents, mark_fragments(parser.collect()).into_iter());
+            let mut markdown = rewrite_links(part, &rewritten_links);
+            if let Some((marker_and_notes, notes)) = notes {
+                contents.push_str("<aside class=\"notes\">\n");
+                html::push_html(&mut contents, Parser::new_ext(notes, Options::all()));
+                contents.push_str("</aside>\n");
+                markdown.push_str(marker_and_notes);
+            }
+            part_contents.push(contents);
+            part_markdown.push(markdown);
+        }
+        if let Some(e) = image_error {
+            return Err(e.context(format!("Failed to parse slide `{}`", path.display())));
+        }
+        let contents = part_contents.join(PART_BREAK);
+        let markdown = part_markdown.join(PART_BREAK);
+        let contents = restore_math_html(&contents, &math);
+        let markdown = restore_math_markdown(&markdown, &math);
 
         let sf = Self {
             filename,
             path,
             contents,
-            local_images,
+            markdown,
+            local_images: images.copied,
+            embedded_images: images.embedded,
+            remote_images,
+            remote_links,
+            meta,
+            front_matter,
         };
         Ok(sf)
     }
 
-    /// Creates a list of SlideFiles from paths
+    /// Splits a SlideFile read with a `slide_separator` into one slide per part, in order.
+    /// Each part has the front matter of the whole file, and the images and links it references.
+    /// A SlideFile without separators is returned as it is
+    pub fn split(self) -> Vec<SlideFile> {
+        if !self.contents.contains(PART_BREAK) {
+            return vec![self];
+        }
+        self.contents
+            .split(PART_BREAK)
+            .zip(self.markdown.split(PART_BREAK))
+            .map(|(contents, markdown)| SlideFile {
+                filename: self.filename.clone(),
+                path: self.path.clone(),
+                contents: contents.to_string(),
+                markdown: markdown.to_string(),
+                local_images: self
+                    .local_images
+                    .iter()
+                    .filter(|(_, dst)| dst.to_str().is_some_and(|dst| markdown.contains(dst)))
+                    .cloned()
+                    .collect(),
+                // a `data:` URI does not tell which image it is, so every part keeps them all
+                embedded_images: self.embedded_images.clone(),
+                remote_images: self.remote_images.iter().filter(|url| markdown.contains(url.as_str())).cloned().collect(),
+                remote_links: self.remote_links.iter().filter(|url| markdown.contains(url.as_str())).cloned().collect(),
+                meta: self.meta.clone(),
+                front_matter: self.front_matter.clone(),
+            })
+            .collect()
+    }
+
+    /// Returns every key of the front matter block of the SlideFile, as parsed.
+    /// This includes keys that mkrevealslides itself does not use.
+    /// Empty if the SlideFile has no front matter
+    pub fn front_matter(&self) -> &Mapping {
+        &self.front_matter
+    }
+
+    /// Creates a list of SlideFiles from paths, reading them in parallel
     /// # Arguments
-    /// * `paths` - A list of paths to slide files.
+    /// * `paths` - A list of paths to slide files, in the order they appear in the presentation.
+    /// * `options` - Options that control how the SlideFiles are read
     ///
     /// # Returns
     /// A list of SlideFiles.
@@ -131,18 +759,182 @@ impl SlideFile {
     /// # Errors
     /// - If a slide file has an invalid file name
     /// - If a slide file has a filename that is not UTF-8 compatible
-    pub fn from_paths(paths: Vec<PathBuf>) -> Result<Vec<Self>, anyhow::Error> {
+    pub fn from_paths(paths: Vec<PathBuf>, options: &SlideOptions) -> Result<Vec<Self>, anyhow::Error> {
+        Self::read_all(paths.into_iter().map(|p| (p, false)).collect(), options)
+    }
+
+    /// Creates a list of SlideFiles from paths, like `from_paths`, but skips the slides that
+    /// cannot be read, because their filename or contents are not UTF-8 or they cannot be opened,
+    /// with a warning
+    ///
+    /// # Errors
+    /// If a slide that could be read is not valid, such as one with invalid front matter
+    pub fn from_paths_lenient(paths: Vec<PathBuf>, options: &SlideOptions) -> Result<Vec<Self>, anyhow::Error> {
+        Self::read_all(paths.into_iter().map(|p| (p, true)).collect(), options)
+    }
+
+    /// Reads SlideFiles in parallel, skipping the unreadable slides of the paths marked as lenient.
+    /// Skipped slides still count towards the position of the slides after them
+    pub(crate) fn read_all(paths: Vec<(PathBuf, bool)>, options: &SlideOptions) -> Result<Vec<Self>, anyhow::Error> {
+        let reads = paths
+            .into_par_iter()
+            .enumerate()
+            .map(|(i, (p, lenient))| {
+                let read = if p.file_name().and_then(OsStr::to_str).is_none() {
+                    Err(anyhow::anyhow!("Filename at `{}` is not UTF-8!", p.display()))
+                } else {
+                    match &options.cache {
+                        Some(cache) => cache.get_or_read(p.clone(), i + 1, options),
+                        None => SlideFile::read_and_parse_nth(&p, i + 1, options),
+                    }
+                };
+                (p, lenient, read)
+            })
+            .collect::<Vec<(PathBuf, bool, Result<SlideFile, anyhow::Error>)>>();
+        // warnings are logged here, on the calling thread, rather than on the thread pool
+        let mut slides = Vec::with_capacity(reads.len());
+        for (path, lenient, read) in reads {
+            match read {
+                Ok(slide) => slides.push(slide),
+                Err(e) if lenient && is_unreadable(&path, &e) => {
+                    warn!(file = %path.display(), "Skipping `{}`: {}", path.display(), e);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(slides)
+    }
+
+    /// Creates a slide of an image slideshow, which shows the image as its background.
+    /// The image is placed in the output directory like the images of other slides
+    ///
+    /// # Arguments
+    /// * `path` - Absolute path to the image
+    /// * `slide_index` - The 1-based position of the slide, for `{slide_index}` in image paths
+    /// * `options` - Options that control where the image is placed
+    ///
+    /// # Errors
+    /// If the image could not be read, or its filename is not UTF-8
+    pub fn from_image<P: AsRef<Path>>(
+        path: P,
+        slide_index: usize,
+        options: &SlideOptions,
+    ) -> Result<Self, anyhow::Error> {
+        let path = canonicalize_retry(path.as_ref()).map_err(|_| PresentationError::ImageNotFound {
+            slide: path.as_ref().to_path_buf(),
+            image: path.as_ref().to_path_buf(),
+        })?;
+        let filename = path
+            .file_name()
+            .and_then(OsStr::to_str)
+            .with_context(|| format!("Filename at `{}` is not UTF-8!", path.display()))?
+            .to_string();
+        let mut local_images = Vec::new();
+        let mut embedded_images = Vec::new();
+        let link = if options.embed_images {
+            embedded_images.push(path.clone());
+            image_data_uri(&path).with_context(|| format!("Could not read image `{}`", path.display()))?
+        } else {
+            let dst_path = match &options.img_path_template {
+                Some(template) => {
+                    let slide_stem = path.file_stem().and_then(OsStr::to_str).unwrap_or_default();
+                    image_dst_path(template, slide_stem, slide_index, &filename)
+                }
+                None => hashed_dst_path_in(&path, &options.image_root)
+                    .with_context(|| format!("Could not read image `{}`", path.display()))?,
+            };
+            local_images.push((path.clone(), dst_path.clone()));
+            dst_path
+                .to_str()
+                .with_context(|| format!("Image destination `{}` is not UTF-8", dst_path.display()))?
+                .to_string()
+        };
+        let meta = SlideMeta {
+            background_image: Some(link.clone()),
+            ..SlideMeta::default()
+        };
+        Ok(Self {
+            filename,
+            markdown: format!("![]({})\n", link),
+            path,
+            contents: String::new(),
+            local_images,
+            embedded_images,
+            remote_images: Vec::new(),
+            remote_links: Vec::new(),
+            meta,
+            front_matter: Mapping::new(),
+        })
+    }
+
+    /// Creates the slides of an image slideshow, one per image, reading them in parallel
+    ///
+    /// # Arguments
+    /// * `paths` - Paths to the images, in the order they appear in the presentation
+    /// * `options` - Options that control where the images are placed
+    ///
+    /// # Errors
+    /// The same as `from_image`
+    pub fn from_image_paths(paths: Vec<PathBuf>, options: &SlideOptions) -> Result<Vec<Self>, anyhow::Error> {
         paths
-            .into_iter()
-            .map(SlideFile::read_and_parse)
-            .collect::<Result<Vec<SlideFile>, anyhow::Error>>()
+            .into_par_iter()
+            .enumerate()
+            .map(|(i, p)| SlideFile::from_image(p, i + 1, options))
+            .collect()
+    }
+
+    /// Returns the text of the first level 1 heading of the slide, if it has one
+    pub fn first_heading(&self) -> Option<String> {
+        let mut events = Parser::new_ext(&self.markdown, Options::all())
+            .skip_while(|event| !matches!(event, Event::Start(Tag::Heading(HeadingLevel::H1, ..))));
+        events.next()?;
+        let mut heading = String::new();
+        for event in events {
+            match event {
+                Event::End(Tag::Heading(..)) => break,
+                Event::Text(text) | Event::Code(text) => heading.push_str(&text),
+                _ => {}
+            }
+        }
+        Some(heading.trim().to_string())
+    }
+
+    /// Finds the local images referenced by a slide that do not exist,
+    /// without reading or canonicalizing the images themselves
+    ///
+    /// # Arguments
+    /// * `path` - Absolute path to the slide file on the disk
+    ///
+    /// # Returns
+    /// The paths of the missing images, relative to the slide's directory if they were given so
+    ///
+    /// # Errors
+    /// If the slide file could not be read
+    pub fn missing_local_images<P: AsRef<Path>>(path: P) -> Result<Vec<PathBuf>, anyhow::Error> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Could not read slide `{}`", path.display()))?;
+        let (_, markdown) = split_front_matter(&contents);
+        let slide_dir = path
+            .parent()
+            .with_context(|| format!("`{}` is not in a directory", path.display()))?;
+        let missing = Parser::new_ext(markdown, Options::all())
+            .filter_map(|event| match event {
+                Event::Start(Tag::Image(_, url, _)) if !url.contains("://") => {
+                    Some(slide_dir.join(url.as_ref()))
+                }
+                _ => None,
+            })
+            .filter(|img_path| !img_path.exists())
+            .collect();
+        Ok(missing)
     }
 
     /// Attempts to validate the path to a SlideFile
     /// This checks if the file
     /// - actually exists
     /// - can be read
-    /// - is a .md file
+    /// - has one of the configured slide extensions
     ///
     /// # Returns
     /// None
@@ -150,8 +942,11 @@ impl SlideFile {
     /// # Errors
     /// - If the slide file does not exist
     /// - If the slide file is not a file
-    /// - If the slide file is not a markdown file
-    fn validate_path<P: AsRef<Path>>(slide_file_path: P) -> Result<(), ValidationError> {
+    /// - If the slide file does not have a slide extension
+    pub(crate) fn validate_path<P: AsRef<Path>>(
+        slide_file_path: P,
+        options: &SlideOptions,
+    ) -> Result<(), ValidationError> {
         if !slide_file_path.as_ref().is_absolute() {
             return Err(ValidationError::new(
                 &slide_file_path.as_ref().display().to_string(),
@@ -170,10 +965,13 @@ impl SlideFile {
                 "Path is not a file".to_string(),
             ));
         }
-        if !is_markdown_file(slide_file_path.as_ref()) {
+        if !has_extension(slide_file_path.as_ref(), &options.extensions) {
             return Err(ValidationError::new(
                 &slide_file_path.as_ref().display().to_string(),
-                "File is not a markdown file".to_string(),
+                format!(
+                    "File is not a slide file, expected one of the extensions: {}",
+                    options.extensions.join(", ")
+                ),
             ));
         }
         Ok(())
@@ -201,11 +999,402 @@ mod test {
         let _h_local_img = File::create(&local_img).unwrap();
 
         let slide_file = SlideFile::read_and_parse(slide_file).unwrap();
-        assert_eq!(slide_file.contents, "<p><img src=\"./img/slide.md/image.png\" alt=\"oh no an image\" /></p>\n");
+        // the image is empty, so this is the hash of no bytes
+        assert_eq!(slide_file.contents, "<p><img src=\"./img/e3b0c44298fc.png\" alt=\"oh no an image\" /></p>\n");
+        assert_eq!(slide_file.markdown, "![oh no an image](./img/e3b0c44298fc.png)");
         assert_eq!(slide_file.local_images.len(), 1);
         assert_eq!(
             slide_file.local_images[0],
-            (local_img, PathBuf::from("./img/slide.md/image.png"))
+            (local_img, PathBuf::from("./img/e3b0c44298fc.png"))
+        );
+    }
+
+    #[test]
+    fn test_parse_slide_with_image_layouts() {
+        let tmp_dir = tempdir().unwrap();
+        let abs_path_to_tmp_dir = fs::canonicalize(tmp_dir.path()).unwrap();
+        let slide_file = abs_path_to_tmp_dir.join("slides/1_arch.md");
+        let img = abs_path_to_tmp_dir.join("img/diagrams/arch.png");
+        fs::create_dir_all(slide_file.parent().unwrap()).unwrap();
+        fs::create_dir_all(img.parent().unwrap()).unwrap();
+        fs::write(&slide_file, "![arch](../img/diagrams/arch.png)").unwrap();
+        fs::write(&img, "").unwrap();
+
+        let slide = SlideFile::read_and_parse(&slide_file).unwrap();
+        assert_eq!(slide.local_images, vec![(img.clone(), PathBuf::from("./img/e3b0c44298fc.png"))]);
+
+        let options = SlideOptions {
+            image_layout: ImageLayout::Mirror,
+            ..SlideOptions::default()
+        };
+        let slide = SlideFile::read_and_parse_with(&slide_file, &options).unwrap();
+        assert_eq!(slide.local_images, vec![(img, PathBuf::from("./img/diagrams/arch.png"))]);
+        assert_eq!(slide.markdown, "![arch](./img/diagrams/arch.png)");
+        tmp_dir.close().unwrap();
+    }
+
+    #[test]
+    fn test_mirrored_dst_path() {
+        assert_eq!(
+            mirrored_dst_path(Path::new("../img/diagrams/arch.png")),
+            Some(PathBuf::from("./img/diagrams/arch.png"))
+        );
+        assert_eq!(mirrored_dst_path(Path::new("./photo.jpg")), Some(PathBuf::from("./photo.jpg")));
+        assert_eq!(mirrored_dst_path(Path::new("/abs/photo.jpg")), None);
+    }
+
+    #[test]
+    fn test_parse_slide_with_missing_image() {
+        let tmp_dir = tempdir().unwrap();
+        let abs_path_to_tmp_dir = fs::canonicalize(tmp_dir.path()).unwrap();
+        let slide_file = abs_path_to_tmp_dir.join("slide.md");
+        let missing_img = abs_path_to_tmp_dir.join("missing.png");
+        let options = SlideOptions {
+            img_path_template: Some("img/{file_name}".to_string()),
+            ..SlideOptions::default()
+        };
+        for link in ["./missing.png".to_string(), missing_img.display().to_string()] {
+            fs::write(&slide_file, format!("![gone]({})", link)).unwrap();
+            for options in [&SlideOptions::default(), &options] {
+                let err = SlideFile::read_and_parse_with(&slide_file, options).unwrap_err();
+                let message = format!("{:#}", err);
+                assert!(message.contains(&format!("Failed to parse slide `{}`", slide_file.display())));
+                assert!(message.contains(&format!("Could not find image `{}`", missing_img.display())));
+            }
+        }
+        tmp_dir.close().unwrap();
+    }
+
+    #[test]
+    fn test_slide_file_ordering() {
+        let cases: Vec<(&str, Vec<&str>, Vec<&str>)> = vec![
+            (
+                "numbered",
+                vec!["10_x.md", "2_x.md", "1_x.md"],
+                vec!["1_x.md", "2_x.md", "10_x.md"],
+            ),
+            (
+                "numbered and named",
+                vec!["outro.md", "10_x.md", "appendix2.md", "2_x.md", "appendix10.md"],
+                vec!["2_x.md", "10_x.md", "appendix2.md", "appendix10.md", "outro.md"],
+            ),
+            (
+                "named",
+                vec!["intro.md", "part10.md", "part2.md"],
+                vec!["intro.md", "part2.md", "part10.md"],
+            ),
+        ];
+        for (name, file_names, expected) in cases {
+            let tmp_dir = tempdir().unwrap();
+            let mut slides = file_names
+                .iter()
+                .map(|f| {
+                    let path = tmp_dir.path().join(f);
+                    fs::write(&path, "").unwrap();
+                    SlideFile::read_and_parse(path).unwrap()
+                })
+                .collect::<Vec<SlideFile>>();
+            slides.sort();
+            let sorted = slides.iter().map(|s| s.filename.as_str()).collect::<Vec<&str>>();
+            assert_eq!(sorted, expected, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_parse_slide_with_notes() {
+        let tmp_dir = tempdir().unwrap();
+        let slide_file = tmp_dir.path().join("slide.md");
+        fs::write(&slide_file, "# Title\n\nShown\n\nNote:\nRemember to *smile*\n").unwrap();
+
+        let slide_file = SlideFile::read_and_parse(slide_file).unwrap();
+        assert_eq!(
+            slide_file.contents,
+            "<h1>Title</h1>\n<p>Shown</p>\n<aside class=\"notes\">\n<p>Remember to <em>smile</em></p>\n</aside>\n"
+        );
+        assert_eq!(slide_file.markdown, "# Title\n\nShown\n\nNote:\nRemember to *smile*\n");
+
+        assert_eq!(split_notes("No notes\n"), ("No notes\n", None));
+        assert_eq!(
+            split_notes("Text\nNote: not a marker\nNote:\nA\nNote:\nB"),
+            ("Text\nNote: not a marker\n", Some(("Note:\nA\nNote:\nB", "A\nNote:\nB")))
+        );
+    }
+
+    #[test]
+    fn test_parse_slide_with_fragments() {
+        let tmp_dir = tempdir().unwrap();
+        let slide_file = tmp_dir.path().join("slide.md");
+        fs::write(
+            &slide_file,
+            "- Always shown\n- Shown *later* {.fragment}\n- Nested\n  - Inner {.fragment}\n- Code `{.fragment}` inside\n",
+        )
+        .unwrap();
+
+        let slide_file = SlideFile::read_and_parse(slide_file).unwrap();
+        assert_eq!(
+            slide_file.contents,
+            "<ul>\n\
+             <li>Always shown</li>\n\
+             <li class=\"fragment\">Shown <em>later</em></li>\n\
+             <li>Nested\n<ul>\n<li class=\"fragment\">Inner</li>\n</ul>\n</li>\n\
+             <li>Code <code>{.fragment}</code> inside</li>\n\
+             </ul>\n"
+        );
+    }
+
+    #[test]
+    fn test_hashed_dst_path() {
+        let tmp_dir = tempdir().unwrap();
+        let abs_path_to_tmp_dir = fs::canonicalize(tmp_dir.path()).unwrap();
+        let img_a = abs_path_to_tmp_dir.join("a/image.png");
+        let img_b = abs_path_to_tmp_dir.join("b/image.png");
+        let img_c = abs_path_to_tmp_dir.join("c/copy.png");
+        for (img, contents) in [(&img_a, "first"), (&img_b, "second"), (&img_c, "first")] {
+            fs::create_dir_all(img.parent().unwrap()).unwrap();
+            fs::write(img, contents).unwrap();
+        }
+
+        let dst_a = hashed_dst_path(&img_a).unwrap();
+        let dst_b = hashed_dst_path(&img_b).unwrap();
+        assert_ne!(dst_a, dst_b);
+        assert_eq!(dst_a, hashed_dst_path(&img_c).unwrap());
+        assert_eq!(dst_a.parent().unwrap(), Path::new("./img"));
+        assert_eq!(dst_a.extension().unwrap(), "png");
+        assert_eq!(dst_a.file_stem().unwrap().len(), IMAGE_HASH_LEN);
+        assert!(hashed_dst_path(&abs_path_to_tmp_dir.join("missing.png")).is_err());
+    }
+
+    #[test]
+    fn test_rewrite_links() {
+        let new = "./img/a.png".to_string();
+        let cases = [
+            ("![a](a.png)", "![a](./img/a.png)"),
+            ("Hi ![a.png](a.png \"a.png\") there", "Hi ![a.png](./img/a.png \"a.png\") there"),
+            ("![a][ref]", "![a][ref]"),
+        ];
+        for (markdown, expected) in cases {
+            let start = markdown.find('!').unwrap();
+            let end = markdown.rfind([')', ']']).unwrap() + 1;
+            let links = [(start..end, "a.png".to_string(), new.clone())];
+            assert_eq!(rewrite_links(markdown, &links), expected);
+        }
+    }
+
+    #[test]
+    fn test_disambiguated_dst_path() {
+        let dst_path = Path::new("./img/slide/diagram.png");
+        let a = disambiguated_dst_path(dst_path, Path::new("/a/diagram.png"));
+        let b = disambiguated_dst_path(dst_path, Path::new("/b/diagram.png"));
+        assert_ne!(a, b);
+        assert_eq!(a.parent().unwrap(), Path::new("./img/slide"));
+        assert_eq!(a.extension().unwrap(), "png");
+        let stem = a.file_stem().unwrap().to_str().unwrap();
+        assert!(stem.starts_with("diagram-"));
+        assert_eq!(stem.len(), "diagram-".len() + DIR_HASH_LEN);
+    }
+
+    #[test]
+    fn test_image_mime_type() {
+        let cases = [
+            ("a.png", "image/png"),
+            ("a.JPG", "image/jpeg"),
+            ("a.svg", "image/svg+xml"),
+            ("a.xyz", "application/octet-stream"),
+            ("a", "application/octet-stream"),
+        ];
+        for (path, expected) in cases {
+            assert_eq!(image_mime_type(Path::new(path)), expected);
+        }
+    }
+
+    #[test]
+    fn test_image_dst_path() {
+        let cases = [
+            ("./img/{slide_stem}/{file_name}", "./img/slide/image.png"),
+            ("./img/{slide_index}/{file_name}", "./img/03/image.png"),
+            ("./assets/{slide_stem}-{file_name}", "./assets/slide-image.png"),
+        ];
+        for (template, expected) in cases {
+            assert_eq!(image_dst_path(template, "slide", 3, "image.png"), PathBuf::from(expected));
+        }
+    }
+
+    #[test]
+    fn test_parse_slide_with_math() {
+        let slide_contents = "Inline $a_1 < b_2 * c_3$ costs $5, not $10\n\n$$\n\\frac{a}{b}\n$$\n\nSee `$x$`\n";
+        let tmp_dir = tempdir().unwrap();
+        let abs_path_to_tmp_dir = fs::canonicalize(tmp_dir.path()).unwrap();
+        let slide_file = abs_path_to_tmp_dir.join("slide.md");
+        fs::write(&slide_file, slide_contents).unwrap();
+
+        let slide_file = SlideFile::read_and_parse(slide_file).unwrap();
+        assert_eq!(
+            slide_file.contents,
+            "<p>Inline <span class=\"math inline\">$a_1 &lt; b_2 * c_3$</span> costs $5, not $10</p>\n\
+             <div class=\"math display\">$$\n\\frac{a}{b}\n$$</div>\n\
+             <p>See <code>$x$</code></p>\n"
         );
+        assert_eq!(slide_file.markdown, slide_contents);
+    }
+
+    #[test]
+    fn test_parse_slide_with_code_blocks() {
+        let slide_contents = "```rust\nfn main() {}\n```\n\n```rust,ignore\nlet x = 1 < 2;\n```\n\n```\nplain\n```\n\n    indented\n";
+        let tmp_dir = tempdir().unwrap();
+        let abs_path_to_tmp_dir = fs::canonicalize(tmp_dir.path()).unwrap();
+        let slide_file = abs_path_to_tmp_dir.join("slide.md");
+        fs::write(&slide_file, slide_contents).unwrap();
+
+        let slide_file = SlideFile::read_and_parse(slide_file).unwrap();
+        assert_eq!(
+            slide_file.contents,
+            "<pre><code class=\"language-rust\">fn main() {}\n</code></pre>\n\
+             <pre><code class=\"language-rust\">let x = 1 &lt; 2;\n</code></pre>\n\
+             <pre><code>plain\n</code></pre>\n\
+             <pre><code>indented\n</code></pre>\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_slide_with_absolute_image_path() {
+        let tmp_dir = tempdir().unwrap();
+        let abs_path_to_tmp_dir = fs::canonicalize(tmp_dir.path()).unwrap();
+        let slide_dir = abs_path_to_tmp_dir.join("slides");
+        fs::create_dir(&slide_dir).unwrap();
+        let slide_file = slide_dir.join("slide.md");
+        // outside of the slide's directory, through a `..` that is resolved
+        let img = abs_path_to_tmp_dir.join("shared/logo.png");
+        fs::create_dir(img.parent().unwrap()).unwrap();
+        fs::write(&img, "logo").unwrap();
+
+        let linked = slide_dir.join("../shared/logo.png");
+        fs::write(&slide_file, format!("![]({})", linked.display())).unwrap();
+        let slide = SlideFile::read_and_parse(&slide_file).unwrap();
+        let dst = hashed_dst_path(&img).unwrap();
+        assert_eq!(slide.local_images, vec![(img, dst.clone())]);
+        assert_eq!(slide.contents, format!("<p><img src=\"{}\" alt=\"\" /></p>\n", dst.display()));
+
+        for missing in [abs_path_to_tmp_dir.join("shared/missing.png"), abs_path_to_tmp_dir.join("shared")] {
+            fs::write(&slide_file, format!("![]({})", missing.display())).unwrap();
+            let err = SlideFile::read_and_parse(&slide_file).unwrap_err();
+            match err.downcast_ref::<PresentationError>() {
+                Some(PresentationError::ImageNotFound { slide, image }) => {
+                    assert_eq!(slide, &slide_file);
+                    assert_eq!(image, &missing);
+                }
+                _ => panic!("expected the image not to be found, got {:#}", err),
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_slide_with_repeated_image() {
+        let slide_contents = "![](./image.png)\n\n![again](image.png)\n";
+        let tmp_dir = tempdir().unwrap();
+        let abs_path_to_tmp_dir = fs::canonicalize(tmp_dir.path()).unwrap();
+        let slide_file = abs_path_to_tmp_dir.join("slide.md");
+        fs::write(&slide_file, slide_contents).unwrap();
+        let img = abs_path_to_tmp_dir.join("image.png");
+        fs::write(&img, "image").unwrap();
+
+        let slide_file = SlideFile::read_and_parse(slide_file).unwrap();
+        let dst = hashed_dst_path(&img).unwrap();
+        assert_eq!(slide_file.local_images, vec![(img, dst.clone())]);
+        assert_eq!(
+            slide_file.contents,
+            format!(
+                "<p><img src=\"{dst}\" alt=\"\" /></p>\n<p><img src=\"{dst}\" alt=\"again\" /></p>\n",
+                dst = dst.display()
+            )
+        );
+    }
+
+    #[test]
+    fn test_split_slide_at_separator() {
+        let slide_contents = "---\ntransition: zoom\n---\n# One\n\n![](img.png)\n\n<!-- slide -->\n\
+                              # Two\n\n```\n<!-- slide -->\n```\n\nNote:\nSpeak\n<!-- slide -->\n# Three\n";
+        let tmp_dir = tempdir().unwrap();
+        let abs_path_to_tmp_dir = fs::canonicalize(tmp_dir.path()).unwrap();
+        let slide_file = abs_path_to_tmp_dir.join("slide.md");
+        fs::write(&slide_file, slide_contents).unwrap();
+        let img = abs_path_to_tmp_dir.join("img.png");
+        fs::write(&img, "image").unwrap();
+
+        // without a separator, the file is a single slide
+        let slide = SlideFile::read_and_parse(&slide_file).unwrap();
+        assert_eq!(slide.clone().split(), vec![slide]);
+
+        let options = SlideOptions {
+            slide_separator: Some("<!-- slide -->".to_string()),
+            ..SlideOptions::default()
+        };
+        let parts = SlideFile::read_and_parse_with(&slide_file, &options).unwrap().split();
+        assert_eq!(parts.len(), 3);
+        let dst = hashed_dst_path(&img).unwrap();
+        assert_eq!(parts[0].contents, format!("<h1>One</h1>\n<p><img src=\"{}\" alt=\"\" /></p>\n", dst.display()));
+        assert_eq!(parts[0].local_images, vec![(img, dst)]);
+        // the separator in the code block is kept, and the notes stay with their slide
+        assert_eq!(
+            parts[1].contents,
+            "<h1>Two</h1>\n<pre><code>&lt;!-- slide --&gt;\n</code></pre>\n\
+             <aside class=\"notes\">\n<p>Speak</p>\n</aside>\n"
+        );
+        assert!(parts[1].local_images.is_empty());
+        assert_eq!(parts[2].contents, "<h1>Three</h1>\n");
+        assert_eq!(parts[2].markdown, "# Three\n");
+        assert!(parts.iter().all(|p| p.meta.transition.as_deref() == Some("zoom")));
+    }
+
+    #[test]
+    fn test_parse_slide_with_front_matter() {
+        let slide_contents = "---\nauto_slide_ms: 5000\n---\n# Kiosk\n";
+        let tmp_dir = tempdir().unwrap();
+        let abs_path_to_tmp_dir = fs::canonicalize(tmp_dir.path()).unwrap();
+        let slide_file = abs_path_to_tmp_dir.join("slide.md");
+        fs::write(&slide_file, slide_contents).unwrap();
+
+        let slide_file = SlideFile::read_and_parse(slide_file).unwrap();
+        assert_eq!(slide_file.contents, "<h1>Kiosk</h1>\n");
+        assert_eq!(slide_file.meta.auto_slide_ms, Some(5000));
+        assert_eq!(slide_file.front_matter().len(), 1);
+        assert_eq!(
+            slide_file.meta.section_attributes(),
+            " data-autoslide=\"5000\""
+        );
+    }
+
+    #[test]
+    fn test_front_matter_is_accessible() {
+        let slide_contents = "---\nauto_slide_ms: 100\nauthor: Jane\ntags: [intro, demo]\n---\nHello\n";
+        let tmp_dir = tempdir().unwrap();
+        let abs_path_to_tmp_dir = fs::canonicalize(tmp_dir.path()).unwrap();
+        let slide_file = abs_path_to_tmp_dir.join("slide.md");
+        fs::write(&slide_file, slide_contents).unwrap();
+
+        let slide_file = SlideFile::read_and_parse(slide_file).unwrap();
+        let front_matter = slide_file.front_matter();
+        assert_eq!(front_matter.len(), 3);
+        assert_eq!(front_matter.get(&Value::from("author")), Some(&Value::from("Jane")));
+        assert_eq!(front_matter.get(&Value::from("auto_slide_ms")), Some(&Value::from(100)));
+        assert_eq!(
+            front_matter.get(&Value::from("tags")),
+            Some(&Value::Sequence(vec![Value::from("intro"), Value::from("demo")]))
+        );
+        assert_eq!(slide_file.meta.auto_slide_ms, Some(100));
+    }
+
+    #[test]
+    fn test_front_matter_must_be_a_mapping() {
+        let tmp_dir = tempdir().unwrap();
+        let abs_path_to_tmp_dir = fs::canonicalize(tmp_dir.path()).unwrap();
+        let slide_file = abs_path_to_tmp_dir.join("slide.md");
+        // a list between two horizontal rules is markdown
+        fs::write(&slide_file, "---\n- a list\n---\nHello\n").unwrap();
+        let slide = SlideFile::read_and_parse(&slide_file).unwrap();
+        assert!(slide.front_matter().is_empty());
+        assert_eq!(slide.contents, "<hr />\n<ul>\n<li>a list</li>\n</ul>\n<hr />\n<p>Hello</p>\n");
+
+        fs::write(&slide_file, "---\nauto_slide_ms: soon\n---\nHello\n").unwrap();
+        assert!(SlideFile::read_and_parse(slide_file).is_err());
     }
 }
@@ -0,0 +1,208 @@
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{bail, Context};
+use pulldown_cmark::escape::{escape_href, escape_html};
+use tracing::debug;
+
+/// Environment variable that names the browser used to render thumbnails
+pub const BROWSER_ENV_VAR: &str = "MKREVEALSLIDES_BROWSER";
+/// Environment variable that, if set to anything but an empty string, runs the browser without
+/// its sandbox. Chromium refuses to run as root with the sandbox, such as in many containers
+pub const BROWSER_NO_SANDBOX_ENV_VAR: &str = "MKREVEALSLIDES_BROWSER_NO_SANDBOX";
+/// Browsers that are looked for on the `PATH` if `MKREVEALSLIDES_BROWSER` is not set
+const BROWSER_NAMES: [&str; 4] = ["chromium", "chromium-browser", "google-chrome", "google-chrome-stable"];
+/// Directory inside the output directory that thumbnails are written to
+pub const THUMBNAIL_DIR: &str = "thumbnails";
+/// Name of the overview page that shows every thumbnail
+pub const OVERVIEW_FILE: &str = "overview.html";
+/// Name of the image of the overview page, showing every thumbnail at once
+pub const CONTACT_SHEET_FILE: &str = "contact_sheet.png";
+/// Width and height in pixels that each slide is rendered at
+const THUMBNAIL_SIZE: (u32, u32) = (480, 270);
+/// Number of thumbnails in each row of the contact sheet
+const CONTACT_SHEET_COLUMNS: u32 = 4;
+/// Space in pixels around the page and between the thumbnails of the overview page
+const OVERVIEW_GAP: u32 = 8;
+
+/// Finds a headless-capable browser to render thumbnails with: the one named by
+/// `MKREVEALSLIDES_BROWSER`, or else the first Chromium or Chrome on the `PATH`
+pub fn find_browser() -> Option<PathBuf> {
+    if let Some(browser) = env::var_os(BROWSER_ENV_VAR).filter(|b| !b.is_empty()) {
+        return Some(PathBuf::from(browser));
+    }
+    let path = env::var_os("PATH")?;
+    env::split_paths(&path)
+        .flat_map(|dir| BROWSER_NAMES.iter().map(move |name| dir.join(name)))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Returns the `file://` URL of the given slide of a presentation
+///
+/// # Arguments
+/// * `presentation` - Absolute path to the presentation file
+/// * `position` - Horizontal and vertical index of the slide, as reveal.js counts them
+pub fn slide_url(presentation: &Path, position: (usize, usize)) -> String {
+    let mut url = String::from("file://");
+    escape_href(&mut url, &presentation.to_string_lossy()).expect("writing to a String to succeed");
+    format!("{}#/{}/{}", url, position.0, position.1)
+}
+
+/// Renders a slide to a PNG screenshot with a headless browser
+///
+/// # Arguments
+/// * `browser` - Path to a Chromium-based browser
+/// * `url` - URL of the slide to render
+/// * `dst` - Where the screenshot is written
+///
+/// # Errors
+/// If the browser could not be run, or did not write the screenshot
+pub fn render_thumbnail(browser: &Path, url: &str, dst: &Path) -> Result<(), anyhow::Error> {
+    render_page(browser, url, dst, THUMBNAIL_SIZE)
+}
+
+/// Renders the overview page to a PNG contact sheet with a headless browser,
+/// with the window sized to fit every thumbnail of the page
+///
+/// # Arguments
+/// * `browser` - Path to a Chromium-based browser
+/// * `overview` - Absolute path to the overview page
+/// * `thumbnails` - Number of thumbnails on the overview page
+/// * `dst` - Where the contact sheet is written
+///
+/// # Errors
+/// If the browser could not be run, or did not write the contact sheet
+pub fn render_contact_sheet(
+    browser: &Path,
+    overview: &Path,
+    thumbnails: usize,
+    dst: &Path,
+) -> Result<(), anyhow::Error> {
+    let mut url = String::from("file://");
+    escape_href(&mut url, &overview.to_string_lossy()).expect("writing to a String to succeed");
+    render_page(browser, &url, dst, contact_sheet_size(thumbnails))
+}
+
+/// Returns the width and height in pixels of a contact sheet of the given number of thumbnails,
+/// laid out in rows of `CONTACT_SHEET_COLUMNS` as on the overview page
+fn contact_sheet_size(thumbnails: usize) -> (u32, u32) {
+    let (width, height) = overview_thumbnail_size();
+    let columns = CONTACT_SHEET_COLUMNS.min(thumbnails.max(1) as u32);
+    let rows = (thumbnails.max(1) as u32).div_ceil(CONTACT_SHEET_COLUMNS);
+    (
+        columns * (width + OVERVIEW_GAP) + OVERVIEW_GAP,
+        rows * (height + OVERVIEW_GAP) + OVERVIEW_GAP,
+    )
+}
+
+/// Returns the width and height in pixels that each thumbnail takes up on the overview page,
+/// including its border
+fn overview_thumbnail_size() -> (u32, u32) {
+    (THUMBNAIL_SIZE.0 / 2 + 2, THUMBNAIL_SIZE.1 / 2 + 2)
+}
+
+/// Renders a page to a PNG screenshot with a headless browser.
+/// The browser runs without its sandbox if `MKREVEALSLIDES_BROWSER_NO_SANDBOX` is set
+///
+/// # Arguments
+/// * `browser` - Path to a Chromium-based browser
+/// * `url` - URL of the page to render
+/// * `dst` - Where the screenshot is written
+/// * `size` - Width and height of the browser window in pixels
+///
+/// # Errors
+/// If the browser could not be run, or did not write the screenshot
+fn render_page(browser: &Path, url: &str, dst: &Path, size: (u32, u32)) -> Result<(), anyhow::Error> {
+    debug!("Rendering `{}` to `{}`", url, dst.display());
+    let mut command = Command::new(browser);
+    command.args(["--headless", "--disable-gpu"]);
+    if env::var_os(BROWSER_NO_SANDBOX_ENV_VAR).is_some_and(|v| !v.is_empty()) {
+        command.arg("--no-sandbox");
+    }
+    let output = command
+        .args(["--hide-scrollbars", "--virtual-time-budget=5000"])
+        .arg(format!("--window-size={},{}", size.0, size.1))
+        .arg(format!("--screenshot={}", dst.display()))
+        .arg(url)
+        .output()
+        .with_context(|| format!("Could not run browser `{}`", browser.display()))?;
+    if !output.status.success() || !dst.is_file() {
+        bail!(
+            "Browser `{}` could not render `{}`: {}",
+            browser.display(),
+            url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+/// Returns an HTML page that shows the given thumbnails as a grid, each linking to its slide
+///
+/// # Arguments
+/// * `title` - Title of the presentation
+/// * `thumbnails` - Path of each thumbnail relative to the page, with the link to its slide
+pub fn overview_html(title: &str, thumbnails: &[(PathBuf, String)]) -> String {
+    let mut escaped_title = String::new();
+    escape_html(&mut escaped_title, title).expect("writing to a String to succeed");
+    let mut html = format!(
+        "<!doctype html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{0}</title>\n\
+         <style>\nbody {{ display: flex; flex-wrap: wrap; gap: {3}px; margin: {3}px; }}\n\
+         a {{ display: block; line-height: 0; }}\n\
+         img {{ width: {1}px; height: {2}px; border: 1px solid #ccc; }}\n</style>\n\
+         </head>\n<body>\n",
+        escaped_title,
+        THUMBNAIL_SIZE.0 / 2,
+        THUMBNAIL_SIZE.1 / 2,
+        OVERVIEW_GAP
+    );
+    for (i, (thumbnail, link)) in thumbnails.iter().enumerate() {
+        let mut src = String::new();
+        escape_href(&mut src, &thumbnail.to_string_lossy()).expect("writing to a String to succeed");
+        let mut href = String::new();
+        escape_href(&mut href, link).expect("writing to a String to succeed");
+        html.push_str(&format!(
+            "<a href=\"{}\"><img src=\"{}\" alt=\"Slide {}\"></a>\n",
+            href,
+            src,
+            i + 1
+        ));
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_slide_url() {
+        assert_eq!(
+            slide_url(Path::new("/tmp/my slides/index.html"), (2, 1)),
+            "file:///tmp/my%20slides/index.html#/2/1"
+        );
+    }
+
+    #[test]
+    fn test_contact_sheet_size() {
+        assert_eq!(contact_sheet_size(1), (258, 153));
+        assert_eq!(contact_sheet_size(4), (1008, 153));
+        assert_eq!(contact_sheet_size(5), (1008, 298));
+    }
+
+    #[test]
+    fn test_overview_html() {
+        let html = overview_html(
+            "A & B",
+            &[
+                (PathBuf::from("thumbnails/1.png"), "index.html#/0/0".to_string()),
+                (PathBuf::from("thumbnails/2.png"), "index.html#/1/0".to_string()),
+            ],
+        );
+        assert!(html.contains("<title>A &amp; B</title>"));
+        assert!(html.contains("<a href=\"index.html#/0/0\"><img src=\"thumbnails/1.png\" alt=\"Slide 1\"></a>"));
+        assert!(html.contains("<a href=\"index.html#/1/0\"><img src=\"thumbnails/2.png\" alt=\"Slide 2\"></a>"));
+    }
+}
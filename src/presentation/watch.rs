@@ -0,0 +1,337 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::io::stderr;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Context;
+use chrono::Local;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use tracing::{debug, trace};
+
+use crate::presentation::reveal::REVEAL_CACHE_DIR;
+use crate::presentation::slide::cache::{SlideCache, SLIDE_CACHE_DIR};
+use crate::presentation::PresentationConfig;
+use crate::ui::cli::CliArgs;
+use crate::ui::color::{color_enabled, error, success};
+
+/// How long to wait after a change for more changes before rebuilding,
+/// so that saving a file, which may touch it several times, builds once
+pub const DEBOUNCE_PERIOD: Duration = Duration::from_millis(200);
+
+/// The files and directories that a presentation is built from
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct WatchedPaths {
+    /// Directories that contain slides, and the template directory with everything below it.
+    /// Any change to their entries is relevant
+    dirs: BTreeSet<PathBuf>,
+    /// Individual files, such as the template and the config file
+    files: BTreeSet<PathBuf>,
+    /// Directory the presentation is written to, whose changes are ignored
+    output_dir: PathBuf,
+}
+
+impl WatchedPaths {
+    fn new(cfg: &PresentationConfig, config_path: &Path) -> Self {
+        let dirs = cfg
+            .slides
+            .iter()
+            .filter_map(|slide| slide.path.parent())
+            .map(Path::to_path_buf)
+            .chain(cfg.template_dir.iter().flat_map(|d| with_sub_dirs(d, &cfg.output_dir)))
+            .collect();
+        let files = std::iter::once(config_path.to_path_buf())
+            .chain(cfg.output_targets().iter().filter_map(|t| t.template_file.clone()))
+            .collect();
+        WatchedPaths {
+            dirs,
+            files,
+            output_dir: cfg.output_dir.clone(),
+        }
+    }
+
+    /// Returns the directories to register with the watcher. Files are watched through
+    /// their directory, as editors often save by replacing the file
+    fn watch_dirs(&self) -> BTreeSet<&Path> {
+        self.dirs
+            .iter()
+            .map(PathBuf::as_path)
+            .chain(self.files.iter().filter_map(|f| f.parent()))
+            .collect()
+    }
+
+    /// Checks if a change to the given path should trigger a rebuild
+    fn is_relevant(&self, path: &Path) -> bool {
+        if path.starts_with(&self.output_dir) {
+            return false;
+        }
+        self.files.contains(path) || path.parent().is_some_and(|p| self.dirs.contains(p))
+    }
+}
+
+/// Returns `dir` and every directory below it, except `output_dir`,
+/// as a template may include others from anywhere in the template directory
+fn with_sub_dirs(dir: &Path, output_dir: &Path) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        if let Ok(entries) = fs::read_dir(&dir) {
+            pending.extend(
+                entries
+                    .filter_map(Result::ok)
+                    .map(|entry| entry.path())
+                    .filter(|p| p.is_dir() && !p.starts_with(output_dir)),
+            );
+        }
+        found.push(dir);
+    }
+    found
+}
+
+/// Waits until no item has arrived on `rx` for `period`, collecting every item
+/// that arrived in the meantime after `first`
+fn debounce<T>(rx: &Receiver<T>, first: T, period: Duration) -> Vec<T> {
+    let mut items = vec![first];
+    loop {
+        match rx.recv_timeout(period) {
+            Ok(item) => items.push(item),
+            Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => return items,
+        }
+    }
+}
+
+/// Builds the presentation of the config file, then rebuilds it whenever a slide,
+/// the template, a file in the template directory or the config file changes. Runs until the process is interrupted
+///
+/// A build that fails is reported and watching carries on, so that the mistake can be fixed
+///
+/// # Arguments
+/// * `config_path` - Path to the config file
+/// * `args` - The command line arguments
+/// * `on_build` - Called after the first build and every successful rebuild
+///
+/// # Errors
+/// If the config file cannot be read at first, the files cannot be watched,
+/// or `on_build` fails
+pub fn watch(
+    config_path: &Path,
+    args: &CliArgs,
+    mut on_build: impl FnMut(&PresentationConfig) -> Result<(), anyhow::Error>,
+) -> Result<(), anyhow::Error> {
+    let config_path = config_path
+        .canonicalize()
+        .with_context(|| format!("Could not find `{}`", config_path.display()))?;
+    let cache_dir = config_path
+        .parent()
+        .expect("config file to be in a directory")
+        .join(REVEAL_CACHE_DIR)
+        .join(SLIDE_CACHE_DIR);
+    let cache = (!args.no_cache).then(|| Arc::new(SlideCache::on_disk(cache_dir)));
+    let cfg = rebuild(&config_path, args, cache.clone())?;
+    on_build(&cfg)?;
+    let mut watched = WatchedPaths::new(&cfg, &config_path);
+    let (tx, rx) = channel();
+    let mut watcher: RecommendedWatcher = notify::recommended_watcher(tx)?;
+    register(&mut watcher, &watched, &BTreeSet::new())?;
+    if !args.quiet {
+        eprintln!("Watching for changes, press Ctrl-C to stop");
+    }
+
+    while let Ok(first) = rx.recv() {
+        let events = debounce(&rx, first, DEBOUNCE_PERIOD);
+        let changed = events
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter(|event: &Event| !matches!(event.kind, EventKind::Access(_)))
+            .flat_map(|event| event.paths)
+            .filter(|path| watched.is_relevant(path))
+            .collect::<BTreeSet<PathBuf>>();
+        if changed.is_empty() {
+            continue;
+        }
+        for path in &changed {
+            debug!("`{}` changed", path.display());
+        }
+        let time = Local::now().format("%H:%M:%S");
+        match rebuild(&config_path, args, cache.clone()) {
+            Ok(cfg) => {
+                // slides or the template may have moved
+                let previous = std::mem::replace(&mut watched, WatchedPaths::new(&cfg, &config_path));
+                register(&mut watcher, &watched, &previous.watch_dirs())?;
+                if !args.quiet {
+                    let message = format!("[{}] Rebuilt `{}`", time, cfg.title);
+                    eprintln!("{}", success(&message, color_enabled(args.no_color, &stderr())));
+                }
+                on_build(&cfg)?;
+            }
+            Err(e) => {
+                let message = format!("[{}] Failed to rebuild: {:#}", time, e);
+                eprintln!("{}", error(&message, color_enabled(args.no_color, &stderr())));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Reads the config file and packages its presentation, taking the slides
+/// that have not changed since the last build from `cache` if it is given
+fn rebuild(
+    config_path: &Path,
+    args: &CliArgs,
+    cache: Option<Arc<SlideCache>>,
+) -> Result<PresentationConfig, anyhow::Error> {
+    let cfg = PresentationConfig::from_config_path_cached(config_path, args, cache)?;
+    let report = cfg.package()?;
+    if !cfg.quiet {
+        report.print_summary(cfg.no_color);
+    }
+    Ok(cfg)
+}
+
+/// Watches the directories of `watched` that are not in `already_watched`,
+/// and stops watching those of `already_watched` that are no longer needed
+fn register(
+    watcher: &mut RecommendedWatcher,
+    watched: &WatchedPaths,
+    already_watched: &BTreeSet<&Path>,
+) -> Result<(), anyhow::Error> {
+    let dirs = watched.watch_dirs();
+    for dir in already_watched.difference(&dirs) {
+        trace!("No longer watching `{}`", dir.display());
+        // the directory may have been removed, which already ends the watch
+        let _ = watcher.unwatch(dir);
+    }
+    for dir in dirs.difference(already_watched) {
+        trace!("Watching `{}`", dir.display());
+        watcher
+            .watch(dir, RecursiveMode::NonRecursive)
+            .with_context(|| format!("Could not watch `{}`", dir.display()))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use clap::Parser;
+    use std::thread;
+    use std::time::SystemTime;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_is_relevant() {
+        let watched = WatchedPaths {
+            dirs: BTreeSet::from([PathBuf::from("/p/slides")]),
+            files: BTreeSet::from([PathBuf::from("/p/config.yaml"), PathBuf::from("/p/template.html")]),
+            output_dir: PathBuf::from("/p/slides/output"),
+        };
+        assert!(watched.is_relevant(Path::new("/p/slides/1_intro.md")));
+        assert!(watched.is_relevant(Path::new("/p/slides/new_stack")));
+        assert!(watched.is_relevant(Path::new("/p/config.yaml")));
+        assert!(watched.is_relevant(Path::new("/p/template.html")));
+        assert!(!watched.is_relevant(Path::new("/p/notes.txt")));
+        assert!(!watched.is_relevant(Path::new("/p/slides/output")));
+        assert!(!watched.is_relevant(Path::new("/p/slides/output/index.html")));
+        assert_eq!(
+            watched.watch_dirs(),
+            BTreeSet::from([Path::new("/p"), Path::new("/p/slides")])
+        );
+    }
+
+    #[test]
+    fn test_rebuild_reuses_unchanged_slides() {
+        let tmp_dir = tempdir().unwrap();
+        let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).unwrap();
+        let slide_dir = tmp_dir_pth.join("slides");
+        fs::create_dir(&slide_dir).unwrap();
+        fs::write(slide_dir.join("1_slide1.md"), "Slide 1").unwrap();
+        fs::write(slide_dir.join("2_slide2.md"), "Slide 2").unwrap();
+        let template_file = tmp_dir_pth.join("template.html");
+        fs::write(&template_file, "{{ slide_title }}{{ slides_html }}").unwrap();
+        let cfg_file = tmp_dir_pth.join("config.yaml");
+        fs::write(
+            &cfg_file,
+            "title: Test\nslide_dir: slides\noutput_dir: output\noutput_file: index.html\ntemplate_file: template.html\n",
+        )
+        .unwrap();
+        let args = CliArgs::parse_from([
+            std::ffi::OsStr::new("mkrevealslides"),
+            std::ffi::OsStr::new("--quiet"),
+            std::ffi::OsStr::new("watch"),
+            cfg_file.as_os_str(),
+        ]);
+        let cache = Arc::new(SlideCache::default());
+
+        rebuild(&cfg_file, &args, Some(cache.clone())).unwrap();
+        assert_eq!(cache.reads(), 2);
+
+        fs::write(&template_file, "<h1>{{ slide_title }}</h1>{{ slides_html }}").unwrap();
+        rebuild(&cfg_file, &args, Some(cache.clone())).unwrap();
+        assert_eq!(cache.reads(), 2);
+        let output = fs::read_to_string(tmp_dir_pth.join("output/index.html")).unwrap();
+        assert!(output.starts_with("<h1>Test</h1>"));
+
+        let changed = slide_dir.join("2_slide2.md");
+        fs::write(&changed, "Slide 2, changed").unwrap();
+        let file = fs::File::options().write(true).open(&changed).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(10)).unwrap();
+        rebuild(&cfg_file, &args, Some(cache.clone())).unwrap();
+        assert_eq!(cache.reads(), 3);
+        let output = fs::read_to_string(tmp_dir_pth.join("output/index.html")).unwrap();
+        assert!(output.contains("Slide 2, changed"));
+        tmp_dir.close().unwrap();
+    }
+
+    #[test]
+    fn test_watches_template_dir() {
+        let tmp_dir = tempdir().unwrap();
+        let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).unwrap();
+        let slide_dir = tmp_dir_pth.join("slides");
+        fs::create_dir(&slide_dir).unwrap();
+        fs::write(slide_dir.join("1_slide1.md"), "Slide 1").unwrap();
+        let template_dir = tmp_dir_pth.join("templates");
+        fs::create_dir_all(template_dir.join("partials")).unwrap();
+        fs::write(template_dir.join("index.html"), "{% include \"partials/nav.html\" %}{{ slides_html }}").unwrap();
+        fs::write(template_dir.join("partials/nav.html"), "<nav></nav>").unwrap();
+        let cfg_file = tmp_dir_pth.join("config.yaml");
+        fs::write(
+            &cfg_file,
+            "title: Test\nslide_dir: slides\noutput_dir: output\noutput_file: index.html\n\
+             template_dir: templates\ntemplate_file: index.html\n",
+        )
+        .unwrap();
+        let args = CliArgs::parse_from([
+            std::ffi::OsStr::new("mkrevealslides"),
+            std::ffi::OsStr::new("--quiet"),
+            std::ffi::OsStr::new("watch"),
+            cfg_file.as_os_str(),
+        ]);
+
+        let cfg = rebuild(&cfg_file, &args, None).unwrap();
+        let watched = WatchedPaths::new(&cfg, &cfg_file);
+        assert!(watched.is_relevant(&template_dir.join("partials/nav.html")));
+        assert!(watched.is_relevant(&template_dir.join("footer.html")));
+        assert!(watched.watch_dirs().contains(template_dir.join("partials").as_path()));
+        tmp_dir.close().unwrap();
+    }
+
+    #[test]
+    fn test_debounce() {
+        let (tx, rx) = channel();
+        let sender = thread::spawn(move || {
+            for i in 1..4 {
+                tx.send(i).unwrap();
+                thread::sleep(Duration::from_millis(10));
+            }
+            thread::sleep(Duration::from_millis(300));
+            tx.send(4).unwrap();
+        });
+        let first = rx.recv().unwrap();
+        assert_eq!(debounce(&rx, first, Duration::from_millis(100)), vec![1, 2, 3]);
+        let first = rx.recv().unwrap();
+        assert_eq!(debounce(&rx, first, Duration::from_millis(100)), vec![4]);
+        sender.join().unwrap();
+    }
+}
@@ -0,0 +1,301 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+use std::fs;
+
+use anyhow::{bail, Context};
+use tracing::{debug, trace, warn};
+
+use crate::presentation::io::is_stdout;
+use crate::presentation::watch::watch;
+use crate::ui::cli::CliArgs;
+
+/// Path of the server-sent events endpoint that tells pages to reload
+pub const LIVE_RELOAD_PATH: &str = "/__livereload";
+/// Script injected into every served HTML page, reloading it once the presentation is rebuilt
+const LIVE_RELOAD_SCRIPT: &str =
+    "<script>new EventSource(\"/__livereload\").onmessage = () => location.reload();</script>";
+/// How often an idle live-reload connection is checked for having been closed
+const KEEPALIVE_PERIOD: Duration = Duration::from_secs(15);
+
+/// Counts the builds of the presentation, so that live-reload connections
+/// can wait for the next one
+#[derive(Debug, Default)]
+struct Builds {
+    count: Mutex<u64>,
+    built: Condvar,
+}
+
+impl Builds {
+    /// Records that the presentation has been rebuilt, waking every live-reload connection
+    fn finished(&self) {
+        *self.count.lock().expect("no connection to have panicked") += 1;
+        self.built.notify_all();
+    }
+}
+
+/// Builds the presentation of the config file and serves its output directory over HTTP,
+/// rebuilding it whenever its files change and reloading the pages that are open
+///
+/// # Arguments
+/// * `config_path` - Path to the config file
+/// * `args` - The command line arguments
+/// * `addr` - Address to listen on
+/// * `open` - Opens the presentation in the default browser once it is served
+///
+/// # Errors
+/// If the presentation cannot be built at first, is written to stdout, or `addr` cannot be bound
+pub fn serve(config_path: &Path, args: &CliArgs, addr: SocketAddr, open: bool) -> Result<(), anyhow::Error> {
+    let builds = Arc::new(Builds::default());
+    let mut serving = false;
+    watch(config_path, args, |cfg| {
+        if serving {
+            builds.finished();
+            return Ok(());
+        }
+        if is_stdout(&cfg.output_dir) {
+            bail!("Cannot serve a presentation that is written to stdout");
+        }
+        let listener = TcpListener::bind(addr).with_context(|| format!("Could not listen on `{}`", addr))?;
+        // the presentation is the first file it is rendered into, of the first theme if there are several
+        let index = cfg.output_targets()[0].output_file.clone();
+        let page = match cfg.themes.first() {
+            Some(theme) => Path::new(theme).join(&index),
+            None => index.clone(),
+        };
+        let url = format!("http://{}/{}", listener.local_addr()?, page.display());
+        let root = cfg.output_dir.clone();
+        let builds = builds.clone();
+        thread::spawn(move || accept(listener, root, index, builds));
+        serving = true;
+        if !args.quiet {
+            eprintln!("Serving `{}` at {}", cfg.output_dir.display(), url);
+        }
+        if open {
+            open_browser(&url);
+        }
+        Ok(())
+    })
+}
+
+/// Handles each connection to `listener` on its own thread
+fn accept(listener: TcpListener, root: PathBuf, index: PathBuf, builds: Arc<Builds>) {
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                warn!("Could not accept connection: {}", e);
+                continue;
+            }
+        };
+        let root = root.clone();
+        let index = index.clone();
+        let builds = builds.clone();
+        thread::spawn(move || {
+            if let Err(e) = handle(stream, &root, &index, &builds) {
+                debug!("Connection failed: {}", e);
+            }
+        });
+    }
+}
+
+/// Answers a single request: the live-reload endpoint, or a file of the output directory
+fn handle(mut stream: TcpStream, root: &Path, index: &Path, builds: &Builds) -> Result<(), std::io::Error> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 2 {
+        line.clear();
+    }
+    trace!("Request: {}", request_line.trim_end());
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return respond(&mut stream, "400 Bad Request", "text/plain", b"Bad request");
+    };
+    if method != "GET" {
+        return respond(&mut stream, "405 Method Not Allowed", "text/plain", b"Method not allowed");
+    }
+    let path = target.split(['?', '#']).next().unwrap_or_default();
+    if path == LIVE_RELOAD_PATH {
+        return live_reload(stream, builds);
+    }
+    let Some(file) = resolve(root, index, &percent_decode(path)) else {
+        return respond(&mut stream, "404 Not Found", "text/plain", b"Not found");
+    };
+    match fs::read(&file) {
+        Ok(mut body) => {
+            let content_type = content_type(&file);
+            if content_type.starts_with("text/html") {
+                body = inject_live_reload(&body);
+            }
+            respond(&mut stream, "200 OK", content_type, &body)
+        }
+        Err(_) => respond(&mut stream, "404 Not Found", "text/plain", b"Not found"),
+    }
+}
+
+/// Writes a complete response and closes the connection
+fn respond(stream: &mut TcpStream, status: &str, content_type: &str, body: &[u8]) -> Result<(), std::io::Error> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    )?;
+    stream.write_all(body)?;
+    stream.flush()
+}
+
+/// Keeps a server-sent events stream open, sending an event each time the presentation is rebuilt.
+/// Returns once the page has gone away
+fn live_reload(mut stream: TcpStream, builds: &Builds) -> Result<(), std::io::Error> {
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\n\r\n"
+    )?;
+    stream.flush()?;
+    let mut count = builds.count.lock().expect("no connection to have panicked");
+    let mut seen = *count;
+    loop {
+        let (next, timeout) = builds
+            .built
+            .wait_timeout(count, KEEPALIVE_PERIOD)
+            .expect("no connection to have panicked");
+        count = next;
+        // fails once the page has been closed
+        if timeout.timed_out() {
+            stream.write_all(b": keepalive\n\n")?;
+        } else if *count != seen {
+            seen = *count;
+            stream.write_all(b"data: reload\n\n")?;
+        }
+        stream.flush()?;
+    }
+}
+
+/// Returns the file that a request path refers to, if it is inside `root`.
+/// Directories are served by their `index` file
+fn resolve(root: &Path, index: &Path, path: &str) -> Option<PathBuf> {
+    let relative = Path::new(path.trim_start_matches('/'));
+    if relative.components().any(|c| !matches!(c, Component::Normal(_))) {
+        return None;
+    }
+    let file = root.join(relative);
+    if file.is_dir() {
+        Some(file.join(index))
+    } else {
+        Some(file)
+    }
+}
+
+/// Decodes `%XX` escapes in a request path. Invalid escapes are kept as they are
+fn percent_decode(path: &str) -> String {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes
+            .get(i + 1..i + 3)
+            .filter(|_| bytes[i] == b'%')
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Returns the content type to serve a file with, by its extension
+fn content_type(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    match extension.as_str() {
+        "html" | "htm" => "text/html; charset=utf-8",
+        "css" => "text/css; charset=utf-8",
+        "js" | "mjs" => "text/javascript; charset=utf-8",
+        "json" => "application/json",
+        "md" | "txt" => "text/plain; charset=utf-8",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "webp" => "image/webp",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "ttf" => "font/ttf",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Adds the live-reload script to a page, before `</body>` if it has one or at the end otherwise
+fn inject_live_reload(html: &[u8]) -> Vec<u8> {
+    let html = String::from_utf8_lossy(html);
+    match html.rfind("</body>") {
+        Some(i) => format!("{}{}\n{}", &html[..i], LIVE_RELOAD_SCRIPT, &html[i..]).into_bytes(),
+        None => format!("{}{}\n", html, LIVE_RELOAD_SCRIPT).into_bytes(),
+    }
+}
+
+/// Opens the URL in the default browser, warning if that is not possible
+fn open_browser(url: &str) {
+    let mut command = if cfg!(target_os = "windows") {
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else {
+        Command::new("xdg-open")
+    };
+    if let Err(e) = command.arg(url).spawn() {
+        warn!("Could not open `{}` in a browser: {}", url, e);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        let root = Path::new("/out");
+        let index = Path::new("index.html");
+        assert_eq!(resolve(root, index, "/img/a.png"), Some(PathBuf::from("/out/img/a.png")));
+        assert_eq!(resolve(root, index, "/../secret"), None);
+        assert_eq!(resolve(root, index, "/img/../../secret"), None);
+    }
+
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!(percent_decode("/my%20slides/a%2Fb.png"), "/my slides/a/b.png");
+        assert_eq!(percent_decode("/100%"), "/100%");
+        assert_eq!(percent_decode("/%zz"), "/%zz");
+    }
+
+    #[test]
+    fn test_inject_live_reload() {
+        let html = inject_live_reload(b"<html><body><p>Hi</p></body></html>");
+        assert_eq!(
+            String::from_utf8(html).unwrap(),
+            format!("<html><body><p>Hi</p>{}\n</body></html>", LIVE_RELOAD_SCRIPT)
+        );
+        let html = inject_live_reload(b"Hi");
+        assert_eq!(String::from_utf8(html).unwrap(), format!("Hi{}\n", LIVE_RELOAD_SCRIPT));
+    }
+}
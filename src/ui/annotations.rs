@@ -0,0 +1,146 @@
+use clap::ArgEnum;
+use std::fmt;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields};
+use tracing_subscriber::registry::LookupSpan;
+
+/// How log output should be formatted
+#[derive(ArgEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AnnotationFormat {
+    /// Plain, human readable log lines
+    #[default]
+    Plain,
+    /// GitHub Actions workflow commands, e.g. `::warning file=a.md::message`
+    Github,
+}
+
+/// Formats log events as GitHub Actions workflow commands, so that
+/// warnings and errors show up as annotations on the workflow run.
+///
+/// The `file` field of an event, if present, is used as the location of the annotation.
+pub struct GithubAnnotations;
+
+/// Collects the fields of an event that make up an annotation
+#[derive(Default)]
+struct AnnotationFields {
+    message: String,
+    file: Option<String>,
+}
+
+impl Visit for AnnotationFields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "message" => self.message = value.to_string(),
+            "file" => self.file = Some(value.to_string()),
+            _ => {}
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        match field.name() {
+            "message" => self.message = format!("{:?}", value),
+            "file" => self.file = Some(format!("{:?}", value)),
+            _ => {}
+        }
+    }
+}
+
+/// Escapes the message of a workflow command
+fn escape_data(s: &str) -> String {
+    s.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escapes a property value of a workflow command
+fn escape_property(s: &str) -> String {
+    escape_data(s).replace(':', "%3A").replace(',', "%2C")
+}
+
+impl<S, N> FormatEvent<S, N> for GithubAnnotations
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        _ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let command = match *event.metadata().level() {
+            Level::ERROR => "error",
+            Level::WARN => "warning",
+            Level::INFO => "notice",
+            _ => "debug",
+        };
+        let mut fields = AnnotationFields::default();
+        event.record(&mut fields);
+
+        write!(writer, "::{}", command)?;
+        if let Some(file) = &fields.file {
+            write!(writer, " file={}", escape_property(file))?;
+        }
+        writeln!(writer, "::{}", escape_data(&fields.message))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::presentation::io::output_exclusions;
+    use std::io;
+    use std::path::Path;
+    use std::sync::{Arc, Mutex};
+
+    /// Writer that appends everything to a shared buffer
+    #[derive(Clone, Default)]
+    struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Runs `f` with GitHub annotations enabled, returning everything that was logged
+    fn capture_annotations(f: impl FnOnce()) -> String {
+        let buf = SharedBuf::default();
+        let writer = buf.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(Level::WARN)
+            .event_format(GithubAnnotations)
+            .with_writer(move || writer.clone())
+            .finish();
+        tracing::subscriber::with_default(subscriber, f);
+        let output = buf.0.lock().unwrap().clone();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_annotation_format() {
+        let output = capture_annotations(|| {
+            tracing::warn!(file = "slides/1,2.md", "Something, is: off\nreally");
+            tracing::info!("not logged at this level");
+        });
+        assert_eq!(
+            output,
+            "::warning file=slides/1%2C2.md::Something, is: off%0Areally\n"
+        );
+    }
+
+    #[test]
+    fn test_known_warning_as_annotation() {
+        let slide_dir = Path::new("/nonexistent/slides");
+        let output = capture_annotations(|| {
+            output_exclusions(slide_dir, &slide_dir.join("out"), Path::new("index.html"));
+        });
+        assert!(output.starts_with("::warning file=/nonexistent/slides/out::Output directory"));
+    }
+}
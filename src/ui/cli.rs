@@ -1,7 +1,10 @@
+use crate::presentation::reveal::SlideNumber;
+use crate::ui::annotations::AnnotationFormat;
 use clap::{Parser, Subcommand};
 use std::ffi::OsStr;
 use std::fs;
 use std::fs::File;
+use std::net::IpAddr;
 use std::path::PathBuf;
 use tracing::Level;
 
@@ -12,18 +15,187 @@ pub struct CliArgs {
     #[clap(short, long, parse(from_occurrences))]
     pub verbose: usize,
 
+    /// How log output should be formatted. `github` emits warnings
+    /// as GitHub Actions annotations
+    #[clap(long, arg_enum, default_value = "plain")]
+    pub annotations: AnnotationFormat,
+
+    /// Turn likely misconfigurations, such as an empty template, into errors
+    #[clap(long)]
+    pub strict: bool,
+
+    /// Fail instead of overwriting the output file if it exists already
+    #[clap(long)]
+    pub no_overwrite: bool,
+
+    /// Include slides marked as `draft: true` in their front matter
+    #[clap(long)]
+    pub include_drafts: bool,
+
+    /// Fail if any slide references an image that is not bundled, such as an `https://` one
+    #[clap(long)]
+    pub no_remote_images: bool,
+
+    /// Suppress informational output, such as the build summary
+    #[clap(short, long)]
+    pub quiet: bool,
+
+    /// Disable colored output. Output is only colored when it goes to a terminal,
+    /// and never if the `NO_COLOR` environment variable is set
+    #[clap(long)]
+    pub no_color: bool,
+
+    /// Abort if any referenced image is larger than this many bytes.
+    /// Overrides `max_image_bytes` in the config file.
+    #[clap(long)]
+    pub max_image_bytes: Option<u64>,
+
+    /// Directory, relative to the output directory, that images are placed in.
+    /// Overrides `image_root` in the config file.
+    #[clap(long, parse(from_os_str))]
+    pub image_root: Option<PathBuf>,
+
+    /// Shows the number of the current slide: `true`, `false`, or one of the formats
+    /// `h.v`, `h/v`, `c` and `c/t`. Overrides `slide_numbers` in the config file.
+    #[clap(long)]
+    pub slide_numbers: Option<SlideNumber>,
+
+    /// Locale used to format the `build_date` template variable, such as `de_DE`.
+    /// Overrides `locale` in the config file.
+    #[clap(long)]
+    pub locale: Option<String>,
+
+    /// Maximum number of threads used to read slides and copy images.
+    /// Defaults to the number of CPUs
+    #[clap(long)]
+    pub threads: Option<usize>,
+
+    /// Skip the build if nothing has changed since the time recorded in this file,
+    /// which is updated after every successful build
+    #[clap(long, parse(from_os_str))]
+    pub since_build: Option<PathBuf>,
+
+    /// Write a Makefile-style dependency file to this path after a successful build,
+    /// listing the presentation files and every file they are built from
+    #[clap(long, parse(from_os_str))]
+    pub emit_deps: Option<PathBuf>,
+
+    /// Check that every remote image and link of the slides can be reached, without building.
+    /// Fails if any cannot
+    #[clap(long)]
+    pub check_links: bool,
+
+    /// Start each slide with an HTML comment naming the file it was made from,
+    /// such as `<!-- slide: slides/1_intro.md -->`
+    #[clap(long)]
+    pub source_comments: bool,
+
+    /// Fail if any two slides have the same first level 1 heading
+    #[clap(long)]
+    pub require_unique_titles: bool,
+
+    /// Read every slide again on each rebuild of `watch` and `serve`,
+    /// instead of only the slides that have changed
+    #[clap(long)]
+    pub no_cache: bool,
+
+    /// Render the presentation, but print the files that would be written and the images
+    /// that would be copied instead of writing anything
+    #[clap(long)]
+    pub dry_run: bool,
+
+    /// Print the fully resolved configuration and exit without building
+    #[clap(long)]
+    pub print_config: bool,
+
+    /// What to do. Builds `slides.yml`, or else `slides.yaml`, in the current directory
+    /// like `from-config` if not given
     #[clap(subcommand)]
-    pub command: Commands,
+    pub command: Option<Commands>,
 }
 
 /// Subcommands available to the CLI interface
 #[derive(Subcommand, Debug)]
 pub enum Commands {
-    /// Creates your presentation from a config file
+    /// Creates your presentation from a config file.
+    /// If several config files are given, they are built concurrently
     FromConfig {
+        /// Path to your config file(s)
+        #[clap(parse(from_os_str), required = true)]
+        config_paths: Vec<PathBuf>,
+
+        /// Maximum number of presentations to build at once.
+        /// Defaults to, and is capped at, the number of threads set by `--threads`
+        #[clap(short, long)]
+        jobs: Option<usize>,
+
+        /// Directory to search for slides in, instead of the config's `slide_dir`.
+        /// Relative to the current directory
+        #[clap(long, parse(try_from_os_str=validate_slide_dir))]
+        slide_dir: Option<PathBuf>,
+    },
+    /// Exports the slides of a config file as a single markdown file,
+    /// placed in the config's output directory along with any local images
+    ExportMd {
         /// Path to your config file
         #[clap(parse(from_os_str))]
         config_path: PathBuf,
+
+        /// Filename of the markdown file, relative to the output directory
+        #[clap(short, long, parse(from_os_str), default_value = "slides.md")]
+        output_file: PathBuf,
+
+        /// Export a handout instead, with each slide numbered and separated by `---`
+        #[clap(long)]
+        handout: bool,
+    },
+    /// Checks a config file and its slides for problems without building the presentation
+    Validate {
+        /// Path to your config file
+        #[clap(parse(from_os_str))]
+        config_path: PathBuf,
+
+        /// Only check that the local images referenced by the slides exist,
+        /// without reading them or rendering anything
+        #[clap(long)]
+        fast: bool,
+    },
+    /// Builds your presentation from a config file, then rebuilds it whenever a slide,
+    /// the template or the config file changes, until interrupted with Ctrl-C
+    Watch {
+        /// Path to your config file
+        #[clap(parse(from_os_str))]
+        config_path: PathBuf,
+    },
+    /// Builds your presentation from a config file and serves it over HTTP,
+    /// rebuilding it and reloading the browser whenever its files change
+    Serve {
+        /// Path to your config file
+        #[clap(parse(from_os_str))]
+        config_path: PathBuf,
+
+        /// Port to listen on
+        #[clap(short, long, default_value_t = 8000)]
+        port: u16,
+
+        /// Address to listen on. Use `0.0.0.0` to make the presentation
+        /// reachable from other devices on the network
+        #[clap(long, default_value = "127.0.0.1")]
+        bind: IpAddr,
+
+        /// Do not open the presentation in a browser
+        #[clap(long)]
+        no_open: bool,
+    },
+    /// Creates a new project with a config file, a first slide and a template
+    Init {
+        /// Directory to create the project in
+        #[clap(parse(from_os_str), default_value = ".")]
+        dir: PathBuf,
+
+        /// Create the project even if the directory is not empty, overwriting its files
+        #[clap(long)]
+        force: bool,
     },
     /// Creates your presentation from CLI arguments
     FromCli {
@@ -35,17 +207,24 @@ pub enum Commands {
         #[clap(parse(try_from_os_str=validate_slide_dir))]
         slide_dir: PathBuf,
 
-        /// Path to the template file to use
-        #[clap(parse(try_from_os_str=file_exists))]
-        template_file: PathBuf,
+        /// Path to the template file to use.
+        /// The built-in template is used if this is not given
+        #[clap(long, parse(try_from_os_str=file_exists))]
+        template_file: Option<PathBuf>,
 
-        /// Output directory to place generated slides in
-        #[clap(parse(from_os_str), default_value = "output")]
+        /// Output directory to place generated slides in.
+        /// Use `-` to write a tar archive of the build to stdout instead
+        #[clap(parse(try_from_os_str=not_a_file), default_value = "output")]
         output_dir: PathBuf,
 
         /// Output filename to use
         #[clap(parse(from_os_str), default_value = "index.html")]
         output_file: PathBuf,
+
+        /// Theme to package the presentation with. May be given multiple times
+        /// to package one copy per theme into `<OUTPUT_DIR>/<THEME>/`
+        #[clap(long = "theme")]
+        themes: Vec<String>,
     },
 }
 
@@ -91,13 +270,11 @@ fn validate_slide_dir(slide_dir: &OsStr) -> Result<PathBuf, String> {
             }
             Ok(path_to_dir)
         }
-        Err(e) => {
-            return Err(format!(
-                "Could not read directory `{}`: {}",
-                path_to_dir.display(),
-                e
-            ))
-        }
+        Err(e) => Err(format!(
+            "Could not read directory `{}`: {}",
+            path_to_dir.display(),
+            e
+        )),
     }
 }
 
@@ -119,15 +296,34 @@ fn file_exists(s: &OsStr) -> Result<PathBuf, String> {
     }
 }
 
+/// Checks that the given output directory is not an existing file, which is most likely
+/// a template file given where it used to be, before it became `--template-file`
+fn not_a_file(s: &OsStr) -> Result<PathBuf, String> {
+    let path = PathBuf::from(s);
+    if path.is_file() {
+        Err(format!(
+            "`{}` is a file, not an output directory. Pass a template file with `--template-file`",
+            path.display()
+        ))
+    } else {
+        Ok(path)
+    }
+}
+
 impl CliArgs {
-    /// Returns an appropriate log level based on the verbosity level configured
+    /// Returns an appropriate log level based on the verbosity level configured.
+    /// Warnings are always logged with `--annotations github`, since that is what annotations are for
     pub fn get_log_level(&self) -> Level {
-        match self.verbose {
+        let level = match self.verbose {
             0 => Level::ERROR,
             1 => Level::WARN,
             2 => Level::INFO,
             3 => Level::DEBUG,
             _ => Level::TRACE,
+        };
+        match self.annotations {
+            AnnotationFormat::Github => level.max(Level::WARN),
+            AnnotationFormat::Plain => level,
         }
     }
 }
@@ -0,0 +1,61 @@
+use std::env;
+use std::io::IsTerminal;
+
+/// ANSI escape that resets the color of the text after it
+const RESET: &str = "\x1b[0m";
+/// ANSI escape for green text
+const GREEN: &str = "\x1b[32m";
+/// ANSI escape for yellow text
+const YELLOW: &str = "\x1b[33m";
+/// ANSI escape for red text
+const RED: &str = "\x1b[31m";
+
+/// Checks if output to the given stream should be colored.
+/// That is the case if it is a terminal, unless `--no-color` is given
+/// or the `NO_COLOR` environment variable is set to anything but an empty string
+///
+/// # Arguments
+/// * `no_color` - Whether `--no-color` was given
+/// * `stream` - The stream that the output goes to, such as `std::io::stdout()`
+pub fn color_enabled(no_color: bool, stream: &impl IsTerminal) -> bool {
+    let no_color_env = env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    !no_color && !no_color_env && stream.is_terminal()
+}
+
+/// Wraps the text in the given ANSI color if `color` is set
+fn paint(text: &str, ansi: &str, color: bool) -> String {
+    if color {
+        format!("{}{}{}", ansi, text, RESET)
+    } else {
+        text.to_string()
+    }
+}
+
+/// Formats a message about something that succeeded, in green if `color` is set
+pub fn success(text: &str, color: bool) -> String {
+    paint(text, GREEN, color)
+}
+
+/// Formats a message about something that was skipped, in yellow if `color` is set
+pub fn warning(text: &str, color: bool) -> String {
+    paint(text, YELLOW, color)
+}
+
+/// Formats a message about something that failed, in red if `color` is set
+pub fn error(text: &str, color: bool) -> String {
+    paint(text, RED, color)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_paint() {
+        assert_eq!(success("done", true), "\x1b[32mdone\x1b[0m");
+        assert_eq!(warning("skipped", true), "\x1b[33mskipped\x1b[0m");
+        assert_eq!(error("failed", true), "\x1b[31mfailed\x1b[0m");
+        assert_eq!(success("done", false), "done");
+        assert_eq!(error("failed", false), "failed");
+    }
+}
@@ -1,33 +1,343 @@
+use crate::errors::ValidationError;
+use crate::presentation::io::canonicalize_retry;
+use crate::presentation::reveal::{
+    SlideNumber, ViewMode, DEFAULT_REVEAL_THEME, DEFAULT_REVEAL_URL, DEFAULT_REVEAL_VERSION,
+};
+use crate::presentation::slide::cache::SlideCache;
+use crate::presentation::slide::front_matter::SlideMeta;
+use crate::presentation::slide::{ImageLayout, SlideOptions};
 use anyhow::Context;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use tracing::trace;
 
 // todo: support defaults for slide_dir, output_directory and output_file
 /// A PresentationConfigFile which has been deserialized
-#[derive(Debug, Deserialize)]
+#[derive(Debug, PartialEq, Deserialize)]
 pub struct PresentationConfigFile {
     pub title: String,
+    /// Subtitle of the presentation, shown on the title slide
+    #[serde(default)]
+    pub subtitle: Option<String>,
+    /// Adds a slide with the title and subtitle before all others
+    #[serde(default)]
+    pub title_slide: bool,
+    /// Appended to the title in the `page_title` template variable, such as the name of a course
+    #[serde(default)]
+    pub title_suffix: Option<String>,
+    /// Placed between the title and `title_suffix`. ` — ` if not set
+    #[serde(default)]
+    pub title_separator: Option<String>,
     /// Slide directory relative to the directory of the config file
     pub slide_dir: PathBuf,
     /// Output directory relative to the directory of the config file
     /// Does not need to exist
     pub output_dir: PathBuf,
-    /// Output filename with extension
+    /// Creates the output directory if it does not exist. If false, it must exist already
+    #[serde(default = "default_create_output_dir")]
+    pub create_output_dir: bool,
+    /// Fails validation if the output file exists already, instead of overwriting it
+    #[serde(default)]
+    pub no_overwrite: bool,
+    /// Output filename with extension. Only needed if `outputs` is not set
+    #[serde(default)]
     pub output_file: PathBuf,
-    /// Template file relative to the directory of the config file
-    pub template_file: PathBuf,
-    /// Include files relative to the directory of the config file
+    /// Template file relative to the directory of the config file, or to `template_dir` if it is set.
+    /// The built-in template is used if this is not set
+    #[serde(default)]
+    pub template_file: Option<PathBuf>,
+    /// Directory of templates relative to the directory of the config file, loaded together
+    /// so that `template_file` and the templates of `outputs` can include and extend them
+    #[serde(default)]
+    pub template_dir: Option<PathBuf>,
+    /// Files to render the slides into, each with its own template.
+    /// Replaces `template_file` and `output_file` if set
+    #[serde(default)]
+    pub outputs: Vec<OutputTarget>,
+    /// Include files relative to the directory of the config file,
+    /// each optionally with options for its slide
+    #[serde(default)]
+    pub include_files: Vec<IncludeFile>,
+    /// Appends the slides in `slide_dir` that are not in `include_files` after them
+    #[serde(default)]
+    pub discover_remaining: bool,
+    /// Largest size in bytes that any single referenced image may have
+    #[serde(default)]
+    pub max_image_bytes: Option<u64>,
+    /// Files and directories relative to the directory of the config file,
+    /// copied as they are into the output directory
+    #[serde(default)]
+    pub static_assets: Vec<PathBuf>,
+    /// Themes to package the presentation with, one output directory each
+    #[serde(default)]
+    pub themes: Vec<String>,
+    /// Theme of reveal.js in the `theme` template variable, unless `themes` is set
+    #[serde(default = "default_theme")]
+    pub theme: String,
+    /// String placed between slides in the `slides_html` template variable
+    #[serde(default = "default_slide_join")]
+    pub slide_join: String,
+    /// Locale used to format the `build_date` template variable, such as `de_DE`
+    #[serde(default)]
+    pub locale: Option<String>,
+    /// Additional variables to make available to the template
+    #[serde(default, alias = "extra")]
+    pub extra_context: BTreeMap<String, serde_yaml::Value>,
+    /// What to do when a key in `extra_context` has the same name as a built-in variable
+    #[serde(default)]
+    pub context_collision: ContextCollision,
+    /// Turns likely misconfigurations, such as an empty template, into errors instead of warnings
+    #[serde(default)]
+    pub strict: bool,
+    /// Include slides marked as `draft: true` in their front matter
+    #[serde(default)]
+    pub include_drafts: bool,
+    /// File extensions, without the leading `.`, of files that are slides
+    #[serde(default = "default_slide_extensions")]
+    pub slide_extensions: Vec<String>,
+    /// Makes each subdirectory of `slide_dir` a vertical stack of the slides inside it
+    #[serde(default)]
+    pub vertical_stacks: bool,
+    /// Splits each slide file into several slides at every line containing only `slide_separator`
+    #[serde(default)]
+    pub split_slides: bool,
+    /// Line that slide files are split at when `split_slides` is set
+    #[serde(default = "default_slide_separator")]
+    pub slide_separator: String,
+    /// Where local images are placed, relative to the output directory.
+    /// Images are placed according to a hash of their contents if this is not set
+    #[serde(default)]
+    pub img_path_template: Option<String>,
+    /// Directory, relative to the output directory, that images are placed in. `img` if not set.
+    /// May also be given as `image_dir`
+    #[serde(default, alias = "image_dir")]
+    pub image_root: Option<PathBuf>,
+    /// How local images are laid out in the output directory when there is no `img_path_template`
+    #[serde(default)]
+    pub image_layout: ImageLayout,
+    /// Inlines local images into the slides as `data:` URIs instead of copying them
+    #[serde(default)]
+    pub embed_images: bool,
+    /// Renders each slide as a template, with the same variables as the presentation template
+    #[serde(default)]
+    pub render_slides: bool,
+    /// Fails the build if any slide references an image that is not bundled, such as an `https://` one
+    #[serde(default)]
+    pub no_remote_images: bool,
+    /// Downloads remote images when packaging and bundles them like local ones
+    #[serde(default)]
+    pub download_remote_images: bool,
+    /// Minifies the rendered presentation before it is written
+    #[serde(default)]
+    pub minify: bool,
+    /// Bundles reveal.js into the output directory instead of loading it from a CDN
+    #[serde(default)]
+    pub bundle_reveal: bool,
+    /// Version of reveal.js to use
+    #[serde(default = "default_reveal_version")]
+    pub reveal_version: String,
+    /// Where the reveal.js release tarball is downloaded from when bundling it,
+    /// with `{version}` replaced by `reveal_version`
+    #[serde(default = "default_reveal_url")]
+    pub reveal_url: String,
+    /// Width of the presentation in pixels, before it is scaled to fit the window
+    #[serde(default)]
+    pub width: Option<f64>,
+    /// Height of the presentation in pixels, before it is scaled to fit the window
     #[serde(default)]
-    pub include_files: Vec<PathBuf>,
+    pub height: Option<f64>,
+    /// Space around the slides, as a fraction of their size
+    #[serde(default)]
+    pub margin: Option<f64>,
+    /// Shows the number of the current slide, as `true` or in a format such as `c/t`
+    #[serde(default)]
+    pub slide_numbers: Option<SlideNumber>,
+    /// Shows the presentation as `slides` or as a page to `scroll` through
+    #[serde(default)]
+    pub view_mode: ViewMode,
+    /// Bundles the output directory into a zip archive named after `output_file`
+    #[serde(default)]
+    pub zip_output: bool,
+    /// Copies the rest of the images when one cannot be copied, and fails once everything else is written
+    #[serde(default)]
+    pub continue_on_copy_error: bool,
+    /// Makes a slideshow with one slide per image in `slide_dir`, or in `include_files`,
+    /// showing the image as its background
+    #[serde(default)]
+    pub image_slideshow: bool,
+    /// Renders a thumbnail of every slide with a headless browser, and an overview page of them
+    #[serde(default)]
+    pub thumbnails: bool,
     #[serde(skip)]
     /// Absolute path of the directory containing the config file
     pub working_dir: PathBuf,
+    #[serde(skip)]
+    /// Absolute path of the config file itself
+    pub config_path: PathBuf,
+    #[serde(skip)]
+    /// Slides read by an earlier build, which are reused if they have not changed
+    pub slide_cache: Option<Arc<SlideCache>>,
+}
+
+/// What to do when a key in `extra_context` has the same name
+/// as a variable that mkrevealslides provides to the template
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContextCollision {
+    /// Fail the build
+    Error,
+    /// Log a warning and keep the built-in value
+    #[default]
+    Warn,
+    /// Replace the built-in value with the one from `extra_context`
+    Override,
+}
+
+/// A slide listed in `include_files`, either as just its path
+/// or as a mapping of its path and options for the slide
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum IncludeFile {
+    Path(PathBuf),
+    WithOptions {
+        path: PathBuf,
+        /// Transition of the slide, such as `fade` or `zoom`
+        #[serde(default)]
+        transition: Option<String>,
+        /// Background color of the slide, as a hex color or a CSS color name
+        #[serde(default)]
+        background: Option<String>,
+    },
+}
+
+impl IncludeFile {
+    /// Returns the path of the slide, relative to the slide directory
+    pub fn path(&self) -> &Path {
+        match self {
+            IncludeFile::Path(path) => path,
+            IncludeFile::WithOptions { path, .. } => path,
+        }
+    }
+
+    /// Sets the options of this entry on the metadata of its slide,
+    /// taking precedence over the slide's front matter
+    pub fn apply_to(&self, meta: &mut SlideMeta) {
+        if let IncludeFile::WithOptions {
+            transition,
+            background,
+            ..
+        } = self
+        {
+            if transition.is_some() {
+                meta.transition.clone_from(transition);
+            }
+            if background.is_some() {
+                meta.background_color.clone_from(background);
+            }
+        }
+    }
+}
+
+/// A file that the slides are rendered into, with the template it is rendered with
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct OutputTarget {
+    /// Template file, relative to the directory of the config file once read and absolute
+    /// after that. The built-in template is used if this is not set
+    #[serde(default)]
+    pub template_file: Option<PathBuf>,
+    /// Output filename with extension, relative to the output directory
+    pub output_file: PathBuf,
+}
+
+/// Slides in `slides_html` are separated by a newline unless configured otherwise
+pub fn default_slide_join() -> String {
+    "\n".to_string()
+}
+
+/// Slide files are split at thematic breaks unless configured otherwise
+pub fn default_slide_separator() -> String {
+    "---".to_string()
+}
+
+/// The output directory is created when packaging unless configured otherwise
+pub fn default_create_output_dir() -> bool {
+    true
+}
+
+/// Only `.md` files are slides unless configured otherwise
+pub fn default_slide_extensions() -> Vec<String> {
+    SlideOptions::default().extensions
+}
+
+/// Presentations use the `black` theme of reveal.js unless configured otherwise
+pub fn default_theme() -> String {
+    DEFAULT_REVEAL_THEME.to_string()
+}
+
+/// reveal.js is pinned to a known version unless configured otherwise
+pub fn default_reveal_version() -> String {
+    DEFAULT_REVEAL_VERSION.to_string()
+}
+
+/// reveal.js is downloaded from its GitHub releases unless configured otherwise
+pub fn default_reveal_url() -> String {
+    DEFAULT_REVEAL_URL.to_string()
+}
+
+/// Names of the config file that is built when no command is given, in order of preference
+pub const DEFAULT_CONFIG_FILES: [&str; 2] = ["slides.yml", "slides.yaml"];
+
+/// Finds the config file that is built when no command is given
+///
+/// # Arguments
+/// * `dir` - Directory to look in, usually the current directory
+///
+/// # Returns
+/// The path to the first of `DEFAULT_CONFIG_FILES` in `dir`, if any
+pub fn find_default_config(dir: &Path) -> Option<PathBuf> {
+    DEFAULT_CONFIG_FILES
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+/// Formats that a config file can be written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ConfigFormat {
+    Yaml,
+    Toml,
+}
+
+impl ConfigFormat {
+    /// Determines the format of a config file from its extension
+    ///
+    /// # Errors
+    /// If the extension is not one of `.yml`, `.yaml` or `.toml`
+    fn from_path(path: &Path) -> Result<Self, ValidationError> {
+        let extension = path
+            .extension()
+            .map(|e| e.to_string_lossy().to_ascii_lowercase())
+            .unwrap_or_default();
+        match extension.as_str() {
+            "yml" | "yaml" => Ok(ConfigFormat::Yaml),
+            "toml" => Ok(ConfigFormat::Toml),
+            _ => Err(ValidationError::new(
+                &path.display().to_string(),
+                format!(
+                    "Unsupported config file extension `{}`, expected one of: yml, yaml, toml",
+                    extension
+                ),
+            )),
+        }
+    }
 }
 
 impl PresentationConfigFile {
-    /// Reads a YAML configuration file from the config file path
+    /// Reads a configuration file from the config file path.
+    /// `.yml` and `.yaml` files are read as YAML, `.toml` files as TOML
     ///
     /// # Arguments
     /// * `config_file_path` - The path to the configuration file
@@ -36,25 +346,205 @@ impl PresentationConfigFile {
     /// A PresentationConfigFile if the file is valid
     ///
     /// # Errors
-    /// - If the file is not valid YAML
+    /// - If the file has any other extension
+    /// - If the file is not valid YAML or TOML
     /// - If the parent directory of the file cannot be accessed
     pub fn read_config_file(config_file_path: PathBuf) -> Result<Self, anyhow::Error> {
         trace!(
             "Attempting to read config file: {}",
             config_file_path.display()
         );
+        let format = ConfigFormat::from_path(&config_file_path)?;
         let config_str = fs::read_to_string(&config_file_path)?;
         trace!("Config file read: {} bytes", config_str.len());
         let config_parent_dir = &config_file_path
             .parent()
             .with_context(|| "Could not find parent directory of config file")?;
 
-        let mut config: Self = serde_yaml::from_str(&config_str)?;
+        let mut config: Self = match format {
+            ConfigFormat::Yaml => serde_yaml::from_str(&config_str)?,
+            ConfigFormat::Toml => toml::from_str(&config_str)?,
+        };
 
-        let p_dir = fs::canonicalize(config_parent_dir)?;
+        let p_dir = canonicalize_retry(config_parent_dir)?;
+        config.config_path = p_dir.join(
+            config_file_path
+                .file_name()
+                .with_context(|| "Config file path does not contain a filename")?,
+        );
         config.working_dir = p_dir;
         Ok(config)
     }
+
+    /// Layers another config over this one. Each field of `override_with` that is set to
+    /// something other than its default replaces the field of `self`, except for
+    /// `extra_context`, whose keys are merged with those of `override_with` taking precedence
+    ///
+    /// The title, directories and paths of the config file always come from `override_with`,
+    /// since they cannot be left unset
+    ///
+    /// # Arguments
+    /// * `override_with` - The config whose fields take precedence
+    ///
+    /// # Returns
+    /// The merged config
+    pub fn merge(self, override_with: PresentationConfigFile) -> PresentationConfigFile {
+        // destructured so that a new field cannot be added without deciding how it is merged
+        let PresentationConfigFile {
+            title,
+            subtitle,
+            title_slide,
+            title_suffix,
+            title_separator,
+            slide_dir,
+            output_dir,
+            create_output_dir,
+            no_overwrite,
+            output_file,
+            template_file,
+            template_dir,
+            outputs,
+            include_files,
+            discover_remaining,
+            max_image_bytes,
+            static_assets,
+            themes,
+            theme,
+            slide_join,
+            locale,
+            mut extra_context,
+            context_collision,
+            strict,
+            include_drafts,
+            slide_extensions,
+            vertical_stacks,
+            split_slides,
+            slide_separator,
+            img_path_template,
+            image_root,
+            image_layout,
+            embed_images,
+            render_slides,
+            no_remote_images,
+            download_remote_images,
+            minify,
+            bundle_reveal,
+            reveal_version,
+            reveal_url,
+            width,
+            height,
+            margin,
+            slide_numbers,
+            view_mode,
+            zip_output,
+            continue_on_copy_error,
+            image_slideshow,
+            thumbnails,
+            working_dir,
+            config_path,
+            slide_cache,
+        } = override_with;
+        let mut base_context = self.extra_context;
+        base_context.append(&mut extra_context);
+        PresentationConfigFile {
+            title,
+            subtitle: subtitle.or(self.subtitle),
+            title_slide: title_slide || self.title_slide,
+            title_suffix: title_suffix.or(self.title_suffix),
+            title_separator: title_separator.or(self.title_separator),
+            slide_dir,
+            output_dir,
+            create_output_dir: create_output_dir && self.create_output_dir,
+            no_overwrite: no_overwrite || self.no_overwrite,
+            output_file: if output_file.as_os_str().is_empty() {
+                self.output_file
+            } else {
+                output_file
+            },
+            template_file: template_file.or(self.template_file),
+            template_dir: template_dir.or(self.template_dir),
+            outputs: if outputs.is_empty() { self.outputs } else { outputs },
+            include_files: if include_files.is_empty() {
+                self.include_files
+            } else {
+                include_files
+            },
+            discover_remaining: discover_remaining || self.discover_remaining,
+            max_image_bytes: max_image_bytes.or(self.max_image_bytes),
+            static_assets: if static_assets.is_empty() {
+                self.static_assets
+            } else {
+                static_assets
+            },
+            themes: if themes.is_empty() { self.themes } else { themes },
+            theme: if theme == default_theme() { self.theme } else { theme },
+            slide_join: if slide_join == default_slide_join() {
+                self.slide_join
+            } else {
+                slide_join
+            },
+            locale: locale.or(self.locale),
+            extra_context: base_context,
+            context_collision: if context_collision == ContextCollision::default() {
+                self.context_collision
+            } else {
+                context_collision
+            },
+            strict: strict || self.strict,
+            include_drafts: include_drafts || self.include_drafts,
+            slide_extensions: if slide_extensions == default_slide_extensions() {
+                self.slide_extensions
+            } else {
+                slide_extensions
+            },
+            vertical_stacks: vertical_stacks || self.vertical_stacks,
+            split_slides: split_slides || self.split_slides,
+            slide_separator: if slide_separator == default_slide_separator() {
+                self.slide_separator
+            } else {
+                slide_separator
+            },
+            img_path_template: img_path_template.or(self.img_path_template),
+            image_root: image_root.or(self.image_root),
+            image_layout: if image_layout == ImageLayout::default() {
+                self.image_layout
+            } else {
+                image_layout
+            },
+            embed_images: embed_images || self.embed_images,
+            render_slides: render_slides || self.render_slides,
+            no_remote_images: no_remote_images || self.no_remote_images,
+            download_remote_images: download_remote_images || self.download_remote_images,
+            minify: minify || self.minify,
+            bundle_reveal: bundle_reveal || self.bundle_reveal,
+            reveal_version: if reveal_version == default_reveal_version() {
+                self.reveal_version
+            } else {
+                reveal_version
+            },
+            reveal_url: if reveal_url == default_reveal_url() {
+                self.reveal_url
+            } else {
+                reveal_url
+            },
+            width: width.or(self.width),
+            height: height.or(self.height),
+            margin: margin.or(self.margin),
+            slide_numbers: slide_numbers.or(self.slide_numbers),
+            view_mode: if view_mode.is_slides() {
+                self.view_mode
+            } else {
+                view_mode
+            },
+            zip_output: zip_output || self.zip_output,
+            continue_on_copy_error: continue_on_copy_error || self.continue_on_copy_error,
+            image_slideshow: image_slideshow || self.image_slideshow,
+            thumbnails: thumbnails || self.thumbnails,
+            working_dir,
+            config_path,
+            slide_cache: slide_cache.or(self.slide_cache),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -74,17 +564,170 @@ output_dir: "output/"
 output_file: "index.html"
 template_file: "template.html"
         "#;
-        fs::create_dir(&tmp_dir.path().join("slides")).unwrap();
+        fs::create_dir(tmp_dir.path().join("slides")).unwrap();
         fs::write(&cfg_path, cfg_str).unwrap();
         let cfg = PresentationConfigFile::read_config_file(cfg_path).unwrap();
         assert_eq!(cfg.title, "Test Presentation");
         assert_eq!(cfg.slide_dir, PathBuf::from("slides"));
         assert_eq!(cfg.output_dir, PathBuf::from("output/"));
         assert_eq!(cfg.output_file, PathBuf::from("index.html"));
-        assert_eq!(cfg.template_file, PathBuf::from("template.html"));
+        assert_eq!(cfg.template_file, Some(PathBuf::from("template.html")));
         assert_eq!(
             cfg.working_dir,
             fs::canonicalize(tmp_dir.path()).unwrap()
         );
+        assert_eq!(
+            cfg.config_path,
+            fs::canonicalize(tmp_dir.path()).unwrap().join("config.yaml")
+        );
+    }
+
+    #[test]
+    fn test_include_files_with_options() {
+        let cfg_str = r##"
+title: "Test Presentation"
+slide_dir: "slides"
+output_dir: "output"
+output_file: "index.html"
+include_files:
+  - "1_intro.md"
+  - path: "2_demo.md"
+    transition: "zoom"
+    background: "#000"
+  - path: "3_end.md"
+"##;
+        let cfg: PresentationConfigFile = serde_yaml::from_str(cfg_str).unwrap();
+        assert_eq!(
+            cfg.include_files,
+            vec![
+                IncludeFile::Path(PathBuf::from("1_intro.md")),
+                IncludeFile::WithOptions {
+                    path: PathBuf::from("2_demo.md"),
+                    transition: Some("zoom".to_string()),
+                    background: Some("#000".to_string()),
+                },
+                IncludeFile::WithOptions {
+                    path: PathBuf::from("3_end.md"),
+                    transition: None,
+                    background: None,
+                },
+            ]
+        );
+        assert_eq!(cfg.include_files[1].path(), Path::new("2_demo.md"));
+
+        let mut meta = SlideMeta {
+            transition: Some("fade".to_string()),
+            background_color: Some("navy".to_string()),
+            ..SlideMeta::default()
+        };
+        cfg.include_files[2].apply_to(&mut meta);
+        assert_eq!(meta.transition.as_deref(), Some("fade"));
+        cfg.include_files[1].apply_to(&mut meta);
+        assert_eq!(meta.transition.as_deref(), Some("zoom"));
+        assert_eq!(meta.background_color.as_deref(), Some("#000"));
+    }
+
+    #[test]
+    fn test_read_toml_config_file() {
+        let tmp_dir = tempdir().unwrap();
+        let yaml_path = tmp_dir.path().join("config.yaml");
+        let yaml_str = r#"
+title: "Test Presentation"
+slide_dir: "slides"
+output_dir: "output/"
+output_file: "index.html"
+template_file: "template.html"
+themes: ["black", "white"]
+context_collision: override
+extra_context:
+  author: "Jane"
+  year: 2022
+"#;
+        let toml_path = tmp_dir.path().join("config.toml");
+        let toml_str = r#"
+title = "Test Presentation"
+slide_dir = "slides"
+output_dir = "output/"
+output_file = "index.html"
+template_file = "template.html"
+themes = ["black", "white"]
+context_collision = "override"
+
+[extra_context]
+author = "Jane"
+year = 2022
+"#;
+        fs::write(&yaml_path, yaml_str).unwrap();
+        fs::write(&toml_path, toml_str).unwrap();
+        let yaml_cfg = PresentationConfigFile::read_config_file(yaml_path).unwrap();
+        let mut toml_cfg = PresentationConfigFile::read_config_file(toml_path).unwrap();
+        assert_eq!(toml_cfg.config_path.file_name().unwrap(), "config.toml");
+        toml_cfg.config_path = yaml_cfg.config_path.clone();
+        assert_eq!(yaml_cfg, toml_cfg);
+    }
+
+    #[test]
+    fn test_merge() {
+        let base: PresentationConfigFile = serde_yaml::from_str(
+            r#"
+title: "Base"
+subtitle: "Base subtitle"
+slide_dir: "slides"
+output_dir: "output"
+output_file: "index.html"
+template_file: "base.html"
+themes: ["black"]
+minify: true
+reveal_version: "4.0.0"
+width: 1280
+extra_context:
+  author: "Jane"
+  year: 2022
+"#,
+        )
+        .unwrap();
+        let override_with: PresentationConfigFile = serde_yaml::from_str(
+            r#"
+title: "Override"
+slide_dir: "other_slides"
+output_dir: "dist"
+template_file: "override.html"
+create_output_dir: false
+strict: true
+width: 1920
+extra_context:
+  year: 2023
+"#,
+        )
+        .unwrap();
+        let merged = base.merge(override_with);
+        // set in the override
+        assert_eq!(merged.title, "Override");
+        assert_eq!(merged.slide_dir, PathBuf::from("other_slides"));
+        assert_eq!(merged.output_dir, PathBuf::from("dist"));
+        assert_eq!(merged.template_file, Some(PathBuf::from("override.html")));
+        assert!(!merged.create_output_dir);
+        assert!(merged.strict);
+        assert_eq!(merged.width, Some(1920.0));
+        // left at their defaults in the override
+        assert_eq!(merged.subtitle.as_deref(), Some("Base subtitle"));
+        assert_eq!(merged.output_file, PathBuf::from("index.html"));
+        assert_eq!(merged.themes, vec!["black".to_string()]);
+        assert!(merged.minify);
+        assert_eq!(merged.reveal_version, "4.0.0");
+        assert_eq!(merged.reveal_url, default_reveal_url());
+        assert_eq!(merged.height, None);
+        // merged key by key
+        assert_eq!(merged.extra_context["author"], serde_yaml::Value::from("Jane"));
+        assert_eq!(merged.extra_context["year"], serde_yaml::Value::from(2023));
+    }
+
+    #[test]
+    fn test_read_config_file_with_unsupported_extension() {
+        let tmp_dir = tempdir().unwrap();
+        let cfg_path = tmp_dir.path().join("config.json");
+        fs::write(&cfg_path, "{}").unwrap();
+        let err = PresentationConfigFile::read_config_file(cfg_path).unwrap_err();
+        assert!(err.to_string().contains("Unsupported config file extension `json`"));
     }
 }
@@ -0,0 +1,110 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context};
+use tracing::debug;
+
+/// Config file of a new project
+const STARTER_CONFIG: &str = r#"title: "My Presentation"
+slide_dir: "slides"
+output_dir: "output"
+output_file: "index.html"
+template_file: "template.html"
+"#;
+
+/// First slide of a new project
+const STARTER_SLIDE: &str = r#"# My Presentation
+
+Add more slides to `slides/`, such as `2_next.md`. They are shown in the order of their leading numbers.
+"#;
+
+/// Template of a new project, which places each slide in its own `<section>`
+const STARTER_TEMPLATE: &str = r#"<!doctype html>
+<html>
+<head>
+    <meta charset="utf-8">
+    <title>{{ slide_title }}</title>
+    <link rel="stylesheet" href="{{ reveal_path }}/dist/reveal.css">
+    <link rel="stylesheet" href="{{ reveal_path }}/dist/theme/black.css">
+</head>
+<body>
+<div class="reveal">
+    <div class="slides">
+        {% for fc in ingested_files %}
+        <section>
+            {{ fc }}
+        </section>
+        {% endfor %}
+    </div>
+</div>
+<script src="{{ reveal_path }}/dist/reveal.js"></script>
+<script>
+    Reveal.initialize({{ reveal_config }});
+</script>
+</body>
+</html>
+"#;
+
+/// The files of a new project, relative to its directory
+const STARTER_FILES: [(&str, &str); 3] = [
+    ("slides.yml", STARTER_CONFIG),
+    ("slides/1_intro.md", STARTER_SLIDE),
+    ("template.html", STARTER_TEMPLATE),
+];
+
+/// Creates a new project in the given directory, with a config file `slides.yml`,
+/// a `slides/` directory with a first slide and a `template.html`
+///
+/// # Arguments
+/// * `dir` - Directory to create the project in, created if it does not exist
+/// * `force` - Creates the project even if `dir` is not empty, overwriting its files
+///
+/// # Returns
+/// The paths of the files that were created
+///
+/// # Errors
+/// If `dir` is not empty and `force` is not set, or the files could not be written
+pub fn init_project(dir: &Path, force: bool) -> Result<Vec<PathBuf>, anyhow::Error> {
+    if dir.exists() && !force {
+        let is_empty = fs::read_dir(dir)
+            .with_context(|| format!("Could not read `{}`", dir.display()))?
+            .next()
+            .is_none();
+        if !is_empty {
+            bail!(
+                "`{}` is not empty, use `--force` to create the project anyway",
+                dir.display()
+            );
+        }
+    }
+    let mut created = Vec::new();
+    for (path, contents) in STARTER_FILES {
+        let path = dir.join(path);
+        fs::create_dir_all(path.parent().expect("file to have a parent"))?;
+        debug!("Writing `{}`", path.display());
+        fs::write(&path, contents).with_context(|| format!("Could not write `{}`", path.display()))?;
+        created.push(path);
+    }
+    Ok(created)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_init_project_refuses_non_empty_dir() {
+        let tmp_dir = tempdir().unwrap();
+        fs::write(tmp_dir.path().join("notes.txt"), "notes").unwrap();
+        let err = init_project(tmp_dir.path(), false).unwrap_err();
+        assert!(err.to_string().contains("is not empty"));
+        assert!(!tmp_dir.path().join("slides.yml").exists());
+
+        let created = init_project(tmp_dir.path(), true).unwrap();
+        assert_eq!(created.len(), 3);
+        assert!(created.iter().all(|f| f.is_file()));
+        assert!(tmp_dir.path().join("notes.txt").is_file());
+        tmp_dir.close().unwrap();
+    }
+}
@@ -1,2 +1,5 @@
+pub mod annotations;
 pub mod cli;
+pub mod color;
 pub mod conf;
+pub mod init;
@@ -0,0 +1,594 @@
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, SystemTime};
+
+use mkrevealslides::presentation::slide::hashed_dst_path;
+
+use tempfile::tempdir;
+
+const BIN: &str = env!("CARGO_BIN_EXE_mkrevealslides");
+
+#[test]
+fn test_build_summary_is_printed() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "Slide 1").unwrap();
+    fs::write(slide_dir.join("2_slide2.md"), "Slide 2").unwrap();
+    fs::write(slide_dir.join("3_slide3.md"), "Slide 3").unwrap();
+    let template_file = tmp_dir_pth.join("template.html");
+    fs::write(&template_file, "{{ slide_title }}").unwrap();
+
+    let output = Command::new(BIN)
+        .current_dir(&tmp_dir_pth)
+        .args(["from-cli", "slides", "--template-file", "template.html"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let summary = stdout.lines().find(|l| l.starts_with("Built 3 slides, 0 images, ")).unwrap();
+    assert!(summary.contains(" KB of HTML in ") && summary.ends_with('s'), "{}", summary);
+
+    let output = Command::new(BIN)
+        .current_dir(&tmp_dir_pth)
+        .args(["--quiet", "from-cli", "slides", "--template-file", "template.html"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_dry_run_writes_nothing() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::create_dir(tmp_dir_pth.join("images")).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "![img](../images/image.png)").unwrap();
+    fs::write(tmp_dir_pth.join("images/image.png"), "not really a png").unwrap();
+    fs::write(tmp_dir_pth.join("template.html"), "{{ slide_title }}{{ slides_html }}").unwrap();
+    let dry_run = || {
+        Command::new(BIN)
+            .current_dir(&tmp_dir_pth)
+            .args(["--dry-run", "from-cli", "slides", "--template-file", "template.html", "output"])
+            .output()
+            .unwrap()
+    };
+
+    let output = dry_run();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let img_dst = hashed_dst_path(&tmp_dir_pth.join("images/image.png")).unwrap();
+    assert!(stdout.contains(&format!("to `{}`", tmp_dir_pth.join("output/index.html").display())));
+    assert!(stdout.contains(&format!(
+        "Would copy `{}` -> `{}`",
+        tmp_dir_pth.join("images/image.png").display(),
+        tmp_dir_pth.join("output").join(img_dst).display()
+    )));
+    assert!(stdout.contains("Dry run of 1 slide, rendered "));
+    assert!(!tmp_dir_pth.join("output").exists());
+
+    // the presentation is still rendered, so template errors are caught
+    fs::write(tmp_dir_pth.join("template.html"), "{{ slide_title }}{{ slides_html }}{{ nope }}").unwrap();
+    let output = dry_run();
+    assert!(!output.status.success());
+    assert!(!tmp_dir_pth.join("output").exists());
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_output_dir_dash_streams_tar_to_stdout() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::create_dir(tmp_dir_pth.join("images")).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "![img](../images/image.png)").unwrap();
+    fs::write(tmp_dir_pth.join("images/image.png"), "not really a png").unwrap();
+    fs::write(tmp_dir_pth.join("template.html"), "{{ slide_title }}").unwrap();
+
+    let output = Command::new(BIN)
+        .current_dir(&tmp_dir_pth)
+        .args(["from-cli", "slides", "--template-file", "template.html", "-"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(!tmp_dir_pth.join("-").exists());
+
+    let mut archive = tar::Archive::new(output.stdout.as_slice());
+    let mut entries = archive
+        .entries()
+        .unwrap()
+        .map(|e| {
+            let mut e = e.unwrap();
+            let mut contents = String::new();
+            e.read_to_string(&mut contents).unwrap();
+            (e.path().unwrap().into_owned(), contents)
+        })
+        .collect::<Vec<_>>();
+    entries.sort();
+    assert_eq!(
+        entries,
+        vec![
+            (
+                hashed_dst_path(&tmp_dir_pth.join("images/image.png"))
+                    .unwrap()
+                    .strip_prefix("./")
+                    .unwrap()
+                    .to_path_buf(),
+                "not really a png".to_string()
+            ),
+            (PathBuf::from("index.html"), "Untitled Presentation".to_string()),
+        ]
+    );
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.lines().any(|l| l.starts_with("Built 1 slide, 1 image, ")));
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_threads_limits_thread_pool() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::create_dir(tmp_dir_pth.join("images")).unwrap();
+    for i in 1..=5 {
+        fs::write(
+            slide_dir.join(format!("{}_slide.md", i)),
+            format!("Slide {}\n\n![img](../images/image{}.png)", i, i),
+        )
+        .unwrap();
+        fs::write(tmp_dir_pth.join(format!("images/image{}.png", i)), format!("png {}", i)).unwrap();
+    }
+    fs::write(tmp_dir_pth.join("template.html"), "{{ slides_html }}").unwrap();
+
+    let output = Command::new(BIN)
+        .current_dir(&tmp_dir_pth)
+        .args(["-vv", "--threads", "1", "from-cli", "slides", "--template-file", "template.html"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Using 1 threads"));
+
+    let html = fs::read_to_string(tmp_dir_pth.join("output/index.html")).unwrap();
+    let positions = (1..=5)
+        .map(|i| html.find(&format!("Slide {}", i)).unwrap())
+        .collect::<Vec<usize>>();
+    assert!(positions.windows(2).all(|w| w[0] < w[1]));
+    for i in 1..=5 {
+        let img_dst_path = hashed_dst_path(&tmp_dir_pth.join(format!("images/image{}.png", i))).unwrap();
+        assert!(tmp_dir_pth.join("output").join(img_dst_path).is_file());
+    }
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_since_build_skips_unchanged_tree() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "Slide 1").unwrap();
+    fs::write(tmp_dir_pth.join("template.html"), "{{ slides_html }}").unwrap();
+    let stamp = tmp_dir_pth.join("last_build");
+    let output_file = tmp_dir_pth.join("output/index.html");
+    let build = || {
+        let output = Command::new(BIN)
+            .current_dir(&tmp_dir_pth)
+            .args(["--since-build", "last_build", "from-cli", "slides", "--template-file", "template.html"])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stderr).unwrap()
+    };
+
+    // there is no timestamp yet, so the first build always happens
+    build();
+    assert!(stamp.is_file());
+    assert!(fs::read_to_string(&output_file).unwrap().contains("Slide 1"));
+    let first_stamp = fs::read_to_string(&stamp).unwrap();
+
+    // mark the output, so that we can tell if it has been rebuilt
+    fs::write(&output_file, "not rebuilt").unwrap();
+    let stderr = build();
+    assert!(stderr.contains("has not changed since the last build, skipping"));
+    assert_eq!(fs::read_to_string(&output_file).unwrap(), "not rebuilt");
+    assert_eq!(fs::read_to_string(&stamp).unwrap(), first_stamp);
+
+    fs::write(slide_dir.join("1_slide1.md"), "Slide 1, edited").unwrap();
+    // file timestamps may lag behind the clock by a few milliseconds,
+    // so make sure the edit is seen as happening after the first build
+    fs::File::options()
+        .write(true)
+        .open(slide_dir.join("1_slide1.md"))
+        .unwrap()
+        .set_modified(SystemTime::now() + Duration::from_secs(1))
+        .unwrap();
+    build();
+    assert!(fs::read_to_string(&output_file).unwrap().contains("Slide 1, edited"));
+    assert_ne!(fs::read_to_string(&stamp).unwrap(), first_stamp);
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_from_cli_rejects_positional_template_file() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+    fs::create_dir(tmp_dir_pth.join("slides")).unwrap();
+    fs::write(tmp_dir_pth.join("slides/1_slide1.md"), "Slide 1").unwrap();
+    fs::write(tmp_dir_pth.join("template.html"), "{{ slides_html }}").unwrap();
+
+    // the template used to be the second positional argument
+    let output = Command::new(BIN)
+        .current_dir(&tmp_dir_pth)
+        .args(["from-cli", "slides", "template.html"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("`template.html` is a file, not an output directory"));
+    assert!(stderr.contains("--template-file"));
+    assert_eq!(fs::read_to_string(tmp_dir_pth.join("template.html")).unwrap(), "{{ slides_html }}");
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_github_annotations_log_warnings() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "Slide 1").unwrap();
+    fs::write(tmp_dir_pth.join("template.html"), "{{ slide_title }}").unwrap();
+    fs::write(
+        tmp_dir_pth.join("config.yaml"),
+        "title: Test\nslide_dir: slides\noutput_dir: output\noutput_file: index.html\ntemplate_file: template.html\n",
+    )
+    .unwrap();
+    let output = Command::new(BIN)
+        .current_dir(&tmp_dir_pth)
+        .args(["--annotations", "github", "from-config"])
+        .arg(tmp_dir_pth.join("config.yaml"))
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    // the template leaves out the slides, which is only a warning
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains(&format!("::warning file={}::", tmp_dir_pth.join("template.html").display())),
+        "{}",
+        stderr
+    );
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_validate_fast_catches_missing_image() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::create_dir(tmp_dir_pth.join("images")).unwrap();
+    fs::write(tmp_dir_pth.join("images/present.png"), "png").unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "![img](../images/present.png)").unwrap();
+    fs::write(slide_dir.join("2_slide2.md"), "![img](../images/missing.png)").unwrap();
+    fs::write(
+        tmp_dir_pth.join("config.yaml"),
+        "title: Test\nslide_dir: slides\noutput_dir: output\noutput_file: index.html\n",
+    )
+    .unwrap();
+    let validate = || {
+        Command::new(BIN)
+            .current_dir(&tmp_dir_pth)
+            .args(["validate", "--fast"])
+            .arg(tmp_dir_pth.join("config.yaml"))
+            .output()
+            .unwrap()
+    };
+
+    let output = validate();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("1 referenced images do not exist"));
+    assert!(stderr.contains("missing.png"));
+    assert!(!stderr.contains("present.png"));
+    assert!(!tmp_dir_pth.join("output").exists());
+
+    fs::write(tmp_dir_pth.join("images/missing.png"), "png").unwrap();
+    let output = validate();
+    assert!(output.status.success());
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_validate_fast_expands_glob_include_files() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+
+    let chapter_dir = tmp_dir_pth.join("slides/ch1");
+    fs::create_dir_all(&chapter_dir).unwrap();
+    fs::create_dir(tmp_dir_pth.join("images")).unwrap();
+    fs::write(tmp_dir_pth.join("images/present.png"), "png").unwrap();
+    fs::write(chapter_dir.join("1_slide1.md"), "![img](../../images/present.png)").unwrap();
+    fs::write(chapter_dir.join("2_slide2.md"), "![img](../../images/missing.png)").unwrap();
+    fs::write(
+        tmp_dir_pth.join("config.yaml"),
+        "title: Test\nslide_dir: slides\noutput_dir: output\noutput_file: index.html\ninclude_files:\n  - ch1/*.md\n",
+    )
+    .unwrap();
+    let validate = || {
+        Command::new(BIN)
+            .current_dir(&tmp_dir_pth)
+            .args(["validate", "--fast"])
+            .arg(tmp_dir_pth.join("config.yaml"))
+            .output()
+            .unwrap()
+    };
+
+    let output = validate();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(!stderr.contains("does not exist:"), "{}", stderr);
+    assert!(stderr.contains("1 referenced images do not exist"));
+    assert!(stderr.contains("missing.png"));
+
+    fs::write(tmp_dir_pth.join("images/missing.png"), "png").unwrap();
+    let output = validate();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_validate_require_unique_titles() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "# Results\n\nFirst").unwrap();
+    fs::write(slide_dir.join("2_slide2.md"), "# Results\n\nSecond").unwrap();
+    fs::write(
+        tmp_dir_pth.join("config.yaml"),
+        "title: Test\nslide_dir: slides\noutput_dir: output\noutput_file: index.html\n",
+    )
+    .unwrap();
+    let validate = || {
+        Command::new(BIN)
+            .current_dir(&tmp_dir_pth)
+            .args(["--require-unique-titles", "validate"])
+            .arg(tmp_dir_pth.join("config.yaml"))
+            .output()
+            .unwrap()
+    };
+
+    let output = validate();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("1 slide titles are used more than once"));
+    assert!(stderr.contains("`Results` in"));
+    assert!(stderr.contains("1_slide1.md"));
+    assert!(stderr.contains("2_slide2.md"));
+
+    fs::write(slide_dir.join("2_slide2.md"), "# Discussion\n\nSecond").unwrap();
+    let output = validate();
+    assert!(output.status.success());
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_emit_deps_lists_prerequisites() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::create_dir(tmp_dir_pth.join("images")).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "![img](../images/image.png)").unwrap();
+    fs::write(slide_dir.join("2_slide2.md"), "Slide 2").unwrap();
+    fs::write(tmp_dir_pth.join("images/image.png"), "png").unwrap();
+    fs::write(tmp_dir_pth.join("template.html"), "{{ slides_html }}").unwrap();
+
+    let output = Command::new(BIN)
+        .current_dir(&tmp_dir_pth)
+        .args(["--emit-deps", "slides.d", "from-cli", "slides", "--template-file", "template.html"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let deps = fs::read_to_string(tmp_dir_pth.join("slides.d")).unwrap();
+    let (targets, prerequisites) = deps.split_once(':').unwrap();
+    assert_eq!(targets, tmp_dir_pth.join("output/index.html").display().to_string());
+    let prerequisites = prerequisites
+        .split("\\\n")
+        .map(|p| p.trim())
+        .filter(|p| !p.is_empty())
+        .collect::<Vec<&str>>();
+    let expected = [
+        slide_dir.join("1_slide1.md"),
+        tmp_dir_pth.join("images/image.png"),
+        slide_dir.join("2_slide2.md"),
+        tmp_dir_pth.join("template.html"),
+    ];
+    assert_eq!(
+        prerequisites,
+        expected.iter().map(|p| p.to_str().unwrap()).collect::<Vec<&str>>()
+    );
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_no_color_omits_color_codes() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "Slide 1").unwrap();
+    fs::write(tmp_dir_pth.join("template.html"), "{{ slide_title }}").unwrap();
+
+    let build = || {
+        Command::new(BIN)
+            .current_dir(&tmp_dir_pth)
+            .args(["-v", "--no-color", "from-cli", "slides", "--template-file", "template.html"])
+            .output()
+            .unwrap()
+    };
+    assert!(build().status.success());
+    // building again warns that the output file is overwritten
+    let output = build();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stdout.contains("Built 1 slide, "));
+    assert!(stderr.contains("WARN"));
+    assert!(!stdout.contains('\x1b'));
+    assert!(!stderr.contains('\x1b'));
+    tmp_dir.close().unwrap();
+}
+
+/// Serves the presentation of the config file, and requests the page that is printed as served
+///
+/// # Returns
+/// The path of the page, and the response to the request
+fn serve_printed_page(cfg_file: &Path) -> (String, String) {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpStream;
+    use std::process::Stdio;
+
+    let mut child = Command::new(BIN)
+        .args(["serve", "--port", "0", "--no-open"])
+        .arg(cfg_file)
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stderr = BufReader::new(child.stderr.take().unwrap());
+    let url = stderr
+        .by_ref()
+        .lines()
+        .map(Result::unwrap)
+        .find_map(|l| l.split_once(" at http://").map(|(_, url)| url.to_string()))
+        .expect("the address to be printed");
+    let (addr, path) = url.split_once('/').unwrap();
+
+    let mut stream = TcpStream::connect(addr).unwrap();
+    write!(stream, "GET /{} HTTP/1.1\r\nHost: {}\r\n\r\n", path, addr).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    child.kill().unwrap();
+    child.wait().unwrap();
+    // keeps stderr open until the server is killed, as writing to a closed pipe ends it
+    drop(stderr);
+    (path.to_string(), response)
+}
+
+#[test]
+fn test_serve_injects_live_reload() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "Slide 1").unwrap();
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    fs::write(
+        &cfg_file,
+        "title: Test\nslide_dir: slides\noutput_dir: output\noutput_file: index.html\n",
+    )
+    .unwrap();
+
+    let (_, response) = serve_printed_page(&cfg_file);
+    assert!(response.starts_with("HTTP/1.1 200 OK"));
+    assert!(response.contains("Slide 1"));
+    assert!(response.contains("new EventSource(\"/__livereload\")"));
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_serve_opens_first_theme() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "Slide 1").unwrap();
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    fs::write(
+        &cfg_file,
+        "title: Test\nslide_dir: slides\noutput_dir: output\noutput_file: index.html\nthemes: [white, black]\n",
+    )
+    .unwrap();
+
+    let (path, response) = serve_printed_page(&cfg_file);
+    assert_eq!(path, "white/index.html");
+    assert!(response.starts_with("HTTP/1.1 200 OK"), "{}", response);
+    assert!(response.contains("Slide 1"));
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_init_scaffolds_buildable_project() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+    let project_dir = tmp_dir_pth.join("project");
+
+    let output = Command::new(BIN).arg("init").arg(&project_dir).output().unwrap();
+    assert!(output.status.success());
+    assert!(project_dir.join("slides/1_intro.md").is_file());
+
+    let output = Command::new(BIN)
+        .arg("from-config")
+        .arg(project_dir.join("slides.yml"))
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let presentation = fs::read_to_string(project_dir.join("output/index.html")).unwrap();
+    assert!(presentation.contains("<title>My Presentation</title>"));
+    assert!(presentation.contains("<h1>My Presentation</h1>"));
+
+    // the project directory is no longer empty
+    let output = Command::new(BIN).arg("init").arg(&project_dir).output().unwrap();
+    assert!(!output.status.success());
+    let output = Command::new(BIN).args(["init", "--force"]).arg(&project_dir).output().unwrap();
+    assert!(output.status.success());
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_no_command_builds_slides_yml() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+
+    // without a config file, it is a usage error
+    let output = Command::new(BIN).current_dir(&tmp_dir_pth).output().unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("No command given, and no `slides.yml`"));
+    assert!(stderr.contains("USAGE"));
+
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "Slide 1").unwrap();
+    fs::write(
+        tmp_dir_pth.join("slides.yml"),
+        "title: Test\nslide_dir: slides\noutput_dir: output\noutput_file: index.html\n",
+    )
+    .unwrap();
+
+    let output = Command::new(BIN).current_dir(&tmp_dir_pth).output().unwrap();
+    assert!(output.status.success());
+    assert!(tmp_dir_pth.join("output/index.html").is_file());
+    tmp_dir.close().unwrap();
+}
@@ -1,11 +1,21 @@
+use clap::Parser;
+use mkrevealslides::errors::PresentationError;
+use mkrevealslides::ui::cli::CliArgs;
 use mkrevealslides::ui::conf::PresentationConfigFile;
+use std::ffi::OsStr;
 use std::fs;
 use std::fs::File;
-use std::io::Write;
+use std::io::{Read, Write};
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 
 use tempfile::tempdir;
-use mkrevealslides::presentation::PresentationConfig;
+use mkrevealslides::presentation::batch::build_all;
+use mkrevealslides::presentation::builder::PresentationConfigBuilder;
+use mkrevealslides::presentation::{BuildEvent, PresentationConfig};
+use mkrevealslides::presentation::slide::hashed_dst_path;
+use mkrevealslides::presentation::thumbnails::find_browser;
 
 #[test]
 fn test_presentation_from_config() {
@@ -46,7 +56,11 @@ template_file: "template.html"
 
     let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
     let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
-    cfg.package().expect("package to succeed");
+    let report = cfg.package().expect("package to succeed");
+    assert_eq!(report.output_path, cfg.output_dir.join("output.html"));
+    assert_eq!(report.slides, 3);
+    assert_eq!(report.images_copied, 0);
+    assert_eq!(report.bytes_written, fs::metadata(&report.output_path).unwrap().len());
 
     tmp_dir.close().unwrap();
 }
@@ -101,7 +115,102 @@ template_file: "template.html"
 
     cfg.package().expect("package to succeed");
 
-    assert!(fs::read(tmp_dir_pth.join("output/img/1_slide1.md/1_img1.png")).is_ok());
+    let img_dst_path = hashed_dst_path(&img_file_1).unwrap();
+    assert!(fs::read(tmp_dir_pth.join("output").join(&img_dst_path)).is_ok());
+    let output = fs::read_to_string(tmp_dir_pth.join("output/output.html")).unwrap();
+    assert!(output.contains(&format!("src=\"{}\"", img_dst_path.display())));
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_continue_on_copy_error() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    let img_dir = tmp_dir_pth.join("img");
+    fs::create_dir(&img_dir).unwrap();
+    fs::write(img_dir.join("kept.png"), "kept").unwrap();
+    fs::write(img_dir.join("gone.png"), "gone").unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "![](../img/kept.png)").unwrap();
+    fs::write(slide_dir.join("2_slide2.md"), "![](../img/gone.png)").unwrap();
+    fs::write(tmp_dir_pth.join("template.html"), "{{ slide_title }}{{ slides_html }}").unwrap();
+
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    let cfg_str = r#"
+title: "Test Presentation"
+slide_dir: "slides"
+output_dir: "output"
+output_file: "index.html"
+template_file: "template.html"
+continue_on_copy_error: true
+"#;
+    fs::write(&cfg_file, cfg_str).unwrap();
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file.clone()).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    let kept_dst = hashed_dst_path(&img_dir.join("kept.png")).unwrap();
+    // the image can no longer be read once the slides have been
+    fs::remove_file(img_dir.join("gone.png")).unwrap();
+
+    let (result, warnings) = capture_warnings(|| cfg.package());
+    let err = result.unwrap_err();
+    assert_eq!(err.to_string(), "1 images could not be copied, see the warnings above");
+    assert!(warnings.contains("Could not copy"));
+    assert!(warnings.contains("gone.png"));
+    assert!(tmp_dir_pth.join("output").join(&kept_dst).is_file());
+    assert!(tmp_dir_pth.join("output/index.html").is_file());
+
+    // without the option, the first failure stops the build
+    fs::remove_dir_all(tmp_dir_pth.join("output")).unwrap();
+    fs::write(&cfg_file, cfg_str.replace("continue_on_copy_error: true", "")).unwrap();
+    fs::write(img_dir.join("gone.png"), "gone").unwrap();
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    fs::remove_file(img_dir.join("gone.png")).unwrap();
+    let err = cfg.package().unwrap_err();
+    assert!(format!("{:#}", err).contains("Could not copy"));
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_with_thousands_of_images() {
+    const IMAGES: usize = 3000;
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    let img_dir = tmp_dir_pth.join("img");
+    fs::create_dir(&img_dir).unwrap();
+
+    let mut slide = String::new();
+    for i in 0..IMAGES {
+        // different contents, so that every image is copied
+        fs::write(img_dir.join(format!("{}.png", i)), i.to_string()).unwrap();
+        slide.push_str(&format!("![](../img/{}.png)\n", i));
+    }
+    fs::write(slide_dir.join("1_slide1.md"), slide).unwrap();
+
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    let cfg_str = r#"
+title: "Test Presentation"
+slide_dir: "slides"
+output_dir: "output"
+output_file: "index.html"
+"#;
+    fs::write(&cfg_file, cfg_str).unwrap();
+
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    let mut copied = 0;
+    cfg.package_with_progress(|event| {
+        if let BuildEvent::CopyingImage { .. } = event {
+            copied += 1;
+        }
+    })
+    .expect("package to succeed");
+    assert_eq!(copied, IMAGES);
+    let copied_files = fs::read_dir(tmp_dir_pth.join("output/img")).unwrap().count();
+    assert_eq!(copied_files, IMAGES);
     tmp_dir.close().unwrap();
 }
 
@@ -132,15 +241,15 @@ fn test_presentation_from_config_with_image_in_subdirectory() {
     fs::create_dir(&img_dir).unwrap();
 
     let img_file_1 = img_dir.join(PathBuf::from("slide1/img1.png"));
-    fs::create_dir_all(&img_file_1.parent().unwrap()).unwrap();
+    fs::create_dir_all(img_file_1.parent().unwrap()).unwrap();
     File::create(&img_file_1).unwrap();
 
     let img_file_2 = img_dir.join(PathBuf::from("slide2/a/img2.png"));
-    fs::create_dir_all(&img_file_2.parent().unwrap()).unwrap();
+    fs::create_dir_all(img_file_2.parent().unwrap()).unwrap();
     File::create(&img_file_2).unwrap();
 
     let img_file_3 = img_dir.join(PathBuf::from("slide3/img3.png"));
-    fs::create_dir_all(&img_file_3.parent().unwrap()).unwrap();
+    fs::create_dir_all(img_file_3.parent().unwrap()).unwrap();
     File::create(&img_file_3).unwrap();
 
     let _output_file = tmp_dir.path().join("output.html");
@@ -167,8 +276,2761 @@ template_file: "template.html"
     let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
     cfg.package().expect("package to succeed");
 
-    assert!(fs::read(tmp_dir.path().join("output/img/1_slide1.md/img1.png")).is_ok());
-    assert!(fs::read(tmp_dir.path().join("output/img/2_slide2.md/img2.png")).is_ok());
-    assert!(fs::read(tmp_dir.path().join("output/img/3_slide3.md/img3.png")).is_ok());
+    for img_file in [img_file_1, img_file_2, img_file_3] {
+        let img_dst_path = hashed_dst_path(&img_file).unwrap();
+        assert!(fs::read(tmp_dir.path().join("output").join(img_dst_path)).is_ok());
+    }
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_from_config_with_oversized_image() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+
+    let slide_file_1 = slide_dir.join("1_slide1.md");
+    let mut h_slide_file_1 = File::create(&slide_file_1).unwrap();
+    h_slide_file_1.write_all(b"![](../img/huge.png)").unwrap();
+
+    let img_dir = tmp_dir_pth.join("img");
+    fs::create_dir(&img_dir).unwrap();
+    fs::write(img_dir.join("huge.png"), vec![0u8; 2048]).unwrap();
+
+    let template_file = tmp_dir_pth.join("template.html");
+    fs::write(&template_file, "{{ slide_title }}").unwrap();
+
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    let cfg_str = r#"
+title: "Test Presentation"
+slide_dir: "slides"
+output_dir: "output"
+output_file: "output.html"
+template_file: "template.html"
+max_image_bytes: 1024
+"#;
+    fs::write(&cfg_file, cfg_str).unwrap();
+
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    let err = PresentationConfig::try_from(cfg_file_obj).unwrap_err();
+    let msg = err.to_string();
+    assert!(msg.contains("max_image_bytes"));
+    assert!(msg.contains("huge.png"));
+    assert!(msg.contains("2048 bytes"));
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_print_config_reflects_cli_title() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "Slide 1").unwrap();
+    let template_file = tmp_dir_pth.join("template.html");
+    fs::write(&template_file, "{{ slide_title }}").unwrap();
+
+    let args = CliArgs::parse_from([
+        OsStr::new("mkrevealslides"),
+        OsStr::new("--print-config"),
+        OsStr::new("from-cli"),
+        OsStr::new("--title"),
+        OsStr::new("Overridden Title"),
+        slide_dir.as_os_str(),
+        OsStr::new("--template-file"),
+        template_file.as_os_str(),
+    ]);
+    assert!(args.print_config);
+    let cfg = PresentationConfig::try_from(args).unwrap();
+    let dump = cfg.to_yaml().unwrap();
+    assert!(dump.contains("title: Overridden Title"));
+    assert!(dump.contains(&slide_dir.join("1_slide1.md").display().to_string()));
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_with_theme() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "Slide 1").unwrap();
+    let template_file = tmp_dir_pth.join("template.html");
+    fs::write(&template_file, "{{ slide_title }} {{ theme }}").unwrap();
+
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    let cfg_str = r#"
+title: "Test Presentation"
+slide_dir: "slides"
+output_dir: "output"
+output_file: "index.html"
+template_file: "template.html"
+"#;
+    fs::write(&cfg_file, cfg_str).unwrap();
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file.clone()).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    assert_eq!(cfg.render_to_string().unwrap(), "Test Presentation black");
+
+    fs::write(&cfg_file, format!("{}theme: \"league\"\n", cfg_str)).unwrap();
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file.clone()).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    assert_eq!(cfg.render_to_string().unwrap(), "Test Presentation league");
+
+    // an unknown theme is rejected with the themes to choose from
+    fs::write(&cfg_file, format!("{}theme: \"neon\"\n", cfg_str)).unwrap();
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    let err = PresentationConfig::try_from(cfg_file_obj).unwrap_err().to_string();
+    assert!(err.contains("neon"), "{}", err);
+    assert!(err.contains("black, white, league"), "{}", err);
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_from_config_with_themes() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "![](../img/1_img1.png)").unwrap();
+
+    let img_dir = tmp_dir_pth.join("img");
+    fs::create_dir(&img_dir).unwrap();
+    File::create(img_dir.join("1_img1.png")).unwrap();
+
+    let template_file = tmp_dir_pth.join("template.html");
+    fs::write(&template_file, "{{ slide_title }} {{ theme }}").unwrap();
+
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    let cfg_str = r#"
+title: "Test Presentation"
+slide_dir: "slides"
+output_dir: "output"
+output_file: "index.html"
+template_file: "template.html"
+themes:
+  - "black"
+  - "white"
+"#;
+    fs::write(&cfg_file, cfg_str).unwrap();
+
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    let report = cfg.package().expect("package to succeed");
+    assert_eq!(report.output_path, tmp_dir_pth.join("output"));
+    assert_eq!(report.slides, 1);
+    // the image is copied once per theme
+    assert_eq!(report.images_copied, 2);
+    assert!(!report.dry_run);
+
+    for theme in ["black", "white"] {
+        let output_dir = tmp_dir_pth.join("output").join(theme);
+        let output = fs::read_to_string(output_dir.join("index.html")).unwrap();
+        assert_eq!(output, format!("Test Presentation {}", theme));
+        assert!(output_dir.join(hashed_dst_path(&img_dir.join("1_img1.png")).unwrap()).is_file());
+    }
+    assert!(!tmp_dir_pth.join("output/index.html").exists());
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_with_auto_slide_front_matter() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(
+        slide_dir.join("1_slide1.md"),
+        "---\nauto_slide_ms: 5000\n---\nSlide 1",
+    )
+    .unwrap();
+    fs::write(slide_dir.join("2_slide2.md"), "Slide 2").unwrap();
+
+    let template_file = tmp_dir_pth.join("template.html");
+    fs::write(
+        &template_file,
+        "{% for s in slides %}<section{{ s.attributes }}>{{ s.contents }}</section>{% endfor %}",
+    )
+    .unwrap();
+
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    let cfg_str = r#"
+title: "Test Presentation"
+slide_dir: "slides"
+output_dir: "output"
+output_file: "index.html"
+template_file: "template.html"
+"#;
+    fs::write(&cfg_file, cfg_str).unwrap();
+
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    cfg.package().expect("package to succeed");
+
+    let output = fs::read_to_string(tmp_dir_pth.join("output/index.html")).unwrap();
+    assert_eq!(
+        output,
+        "<section data-autoslide=\"5000\"><p>Slide 1</p>\n</section><section><p>Slide 2</p>\n</section>"
+    );
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_with_dot_slash_image() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir_all(slide_dir.join("img")).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "![](./img/x.png)").unwrap();
+    fs::write(slide_dir.join("img/x.png"), "not really a png").unwrap();
+
+    let template_file = tmp_dir_pth.join("template.html");
+    fs::write(&template_file, "{%for fc in ingested_files %}{{fc}}{%endfor%}").unwrap();
+
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    let cfg_str = r#"
+title: "Test Presentation"
+slide_dir: "slides"
+output_dir: "output"
+output_file: "index.html"
+template_file: "template.html"
+"#;
+    fs::write(&cfg_file, cfg_str).unwrap();
+
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    assert_eq!(cfg.slides[0].local_images[0].0, slide_dir.join("img/x.png"));
+    cfg.package().expect("package to succeed");
+
+    let output = fs::read_to_string(tmp_dir_pth.join("output/index.html")).unwrap();
+    let img_dst_path = hashed_dst_path(&slide_dir.join("img/x.png")).unwrap();
+    assert!(output.contains(&format!("src=\"{}\"", img_dst_path.display())));
+    assert_eq!(
+        fs::read_to_string(tmp_dir_pth.join("output").join(img_dst_path)).unwrap(),
+        "not really a png"
+    );
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_with_slides_html() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "Slide 1").unwrap();
+    fs::write(
+        slide_dir.join("2_slide2.md"),
+        "---\nauto_slide_ms: 100\n---\nSlide 2",
+    )
+    .unwrap();
+
+    let template_file = tmp_dir_pth.join("template.html");
+    fs::write(&template_file, "{{ slides_html | safe }}").unwrap();
+
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    let cfg_str = r#"
+title: "Test Presentation"
+slide_dir: "slides"
+output_dir: "output"
+output_file: "index.html"
+template_file: "template.html"
+slide_join: "<!-- next -->"
+"#;
+    fs::write(&cfg_file, cfg_str).unwrap();
+
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    cfg.package().expect("package to succeed");
+
+    let output = fs::read_to_string(tmp_dir_pth.join("output/index.html")).unwrap();
+    assert_eq!(
+        output,
+        "<section><p>Slide 1</p>\n</section><!-- next --><section data-autoslide=\"100\"><p>Slide 2</p>\n</section>"
+    );
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_with_output_inside_slide_dir() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir_all(slide_dir.join("img")).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "![](img/x.png)").unwrap();
+    fs::write(slide_dir.join("2_slide2.md"), "Slide 2").unwrap();
+    File::create(slide_dir.join("img/x.png")).unwrap();
+
+    let template_file = tmp_dir_pth.join("template.html");
+    fs::write(&template_file, "{{ slide_title }}").unwrap();
+
+    for output_dir in ["slides/output", "slides"] {
+        let cfg_file = tmp_dir_pth.join("config.yaml");
+        let cfg_str = format!(
+            r#"
+title: "Test Presentation"
+slide_dir: "slides"
+output_dir: "{}"
+output_file: "index.html"
+template_file: "template.html"
+"#,
+            output_dir
+        );
+        fs::write(&cfg_file, cfg_str).unwrap();
+
+        // build twice, so the second build sees the output of the first
+        for _ in 0..2 {
+            let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file.clone()).unwrap();
+            let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+            assert_eq!(cfg.slides.len(), 2);
+            cfg.package().expect("package to succeed");
+        }
+        assert!(tmp_dir_pth.join(output_dir).join("index.html").is_file());
+    }
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_with_title_suffix() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "Slide 1").unwrap();
+    fs::write(
+        tmp_dir_pth.join("template.html"),
+        "{{ page_title }}|{{ slide_title }}|{{ slides_html | safe }}",
+    )
+    .unwrap();
+
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    let cfg_str = r#"
+title: "Intro"
+slide_dir: "slides"
+output_dir: "output"
+output_file: "index.html"
+template_file: "template.html"
+title_suffix: "My Course"
+"#;
+    fs::write(&cfg_file, cfg_str).unwrap();
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file.clone()).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    cfg.package().expect("package to succeed");
+    let output = fs::read_to_string(tmp_dir_pth.join("output/index.html")).unwrap();
+    assert!(output.starts_with("Intro — My Course|Intro|"));
+
+    fs::write(&cfg_file, format!("{}title_separator: \" | \"\n", cfg_str)).unwrap();
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file.clone()).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    assert_eq!(cfg.page_title(), "Intro | My Course");
+    assert_eq!(cfg.title, "Intro");
+
+    // without a suffix, the separator is left out too
+    fs::write(&cfg_file, cfg_str.replace("title_suffix: \"My Course\"\n", "title_separator: \" | \"\n")).unwrap();
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    assert_eq!(cfg.page_title(), "Intro");
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_with_template_filters() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "Slide 1").unwrap();
+    fs::write(
+        tmp_dir_pth.join("template.html"),
+        "<h1 id=\"{{ slide_title | slugify }}\">{{ slide_title }}</h1>{{ subtitle | markdown }}{{ slides_html }}",
+    )
+    .unwrap();
+
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    let cfg_str = r#"
+title: "Rust & WebAssembly: An Intro"
+subtitle: "A *short* talk"
+slide_dir: "slides"
+output_dir: "output"
+output_file: "index.html"
+template_file: "template.html"
+"#;
+    fs::write(&cfg_file, cfg_str).unwrap();
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    cfg.package().expect("package to succeed");
+    let output = fs::read_to_string(tmp_dir_pth.join("output/index.html")).unwrap();
+    assert!(output.starts_with(
+        "<h1 id=\"rust-webassembly-an-intro\">Rust & WebAssembly: An Intro</h1><p>A <em>short</em> talk</p>\n"
+    ));
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_with_locale() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "Slide 1").unwrap();
+    let template_file = tmp_dir_pth.join("template.html");
+    fs::write(&template_file, "{{ build_date }}").unwrap();
+
+    for (locale, separator) in [(None, '-'), (Some("de_DE"), '.')] {
+        let mut cfg_str = r#"
+title: "Test Presentation"
+slide_dir: "slides"
+output_dir: "output"
+output_file: "index.html"
+template_file: "template.html"
+"#
+        .to_string();
+        if let Some(locale) = locale {
+            cfg_str.push_str(&format!("locale: \"{}\"\n", locale));
+        }
+        let cfg_file = tmp_dir_pth.join("config.yaml");
+        fs::write(&cfg_file, cfg_str).unwrap();
+
+        let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+        let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+        cfg.package().expect("package to succeed");
+
+        let output = fs::read_to_string(tmp_dir_pth.join("output/index.html")).unwrap();
+        assert_eq!(output.len(), 10);
+        assert_eq!(output.matches(separator).count(), 2);
+    }
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_from_config_with_slide_dir_override() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "Slide 1").unwrap();
+    let translated_dir = tmp_dir_pth.join("slides_fr");
+    fs::create_dir(&translated_dir).unwrap();
+    fs::write(translated_dir.join("1_diapo1.md"), "Diapo 1").unwrap();
+    fs::write(translated_dir.join("2_diapo2.md"), "Diapo 2").unwrap();
+
+    let template_file = tmp_dir_pth.join("template.html");
+    fs::write(&template_file, "{{ slide_title }}").unwrap();
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    let cfg_str = r#"
+title: "Test Presentation"
+slide_dir: "slides"
+output_dir: "output"
+output_file: "index.html"
+template_file: "template.html"
+"#;
+    fs::write(&cfg_file, cfg_str).unwrap();
+
+    let args = CliArgs::parse_from([
+        OsStr::new("mkrevealslides"),
+        OsStr::new("from-config"),
+        cfg_file.as_os_str(),
+        OsStr::new("--slide-dir"),
+        translated_dir.as_os_str(),
+    ]);
+    let cfg = PresentationConfig::try_from(args).unwrap();
+    assert_eq!(
+        cfg.slides.iter().map(|s| s.path.clone()).collect::<Vec<PathBuf>>(),
+        vec![
+            translated_dir.join("1_diapo1.md"),
+            translated_dir.join("2_diapo2.md")
+        ]
+    );
+    tmp_dir.close().unwrap();
+}
+
+/// Writes a deck with one slide and a template printing `slide_title` and `author`,
+/// using the given extra config, and returns the path to its config file
+fn write_extra_context_deck(tmp_dir_pth: &std::path::Path, extra_cfg: &str) -> PathBuf {
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir_all(&slide_dir).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "Slide 1").unwrap();
+    let template_file = tmp_dir_pth.join("template.html");
+    fs::write(&template_file, "{{ slide_title }} by {{ author }}").unwrap();
+
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    let cfg_str = format!(
+        r#"
+title: "Test Presentation"
+slide_dir: "slides"
+output_dir: "output"
+output_file: "index.html"
+template_file: "template.html"
+extra_context:
+  author: "Jane"
+  slide_title: "Overridden"
+{}
+"#,
+        extra_cfg
+    );
+    fs::write(&cfg_file, cfg_str).unwrap();
+    cfg_file
+}
+
+#[test]
+fn test_presentation_extra_context_collision_warn() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+    let cfg_file = write_extra_context_deck(&tmp_dir_pth, "");
+
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    cfg.package().expect("package to succeed");
+    let output = fs::read_to_string(tmp_dir_pth.join("output/index.html")).unwrap();
+    assert_eq!(output, "Test Presentation by Jane");
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_extra_context_collision_override() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+    let cfg_file = write_extra_context_deck(&tmp_dir_pth, "context_collision: override");
+
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    cfg.package().expect("package to succeed");
+    let output = fs::read_to_string(tmp_dir_pth.join("output/index.html")).unwrap();
+    assert_eq!(output, "Overridden by Jane");
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_extra_context_collision_error() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+    let cfg_file = write_extra_context_deck(&tmp_dir_pth, "context_collision: error");
+
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    let err = PresentationConfig::try_from(cfg_file_obj).unwrap_err();
+    assert!(err.to_string().contains("slide_title"));
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_with_extra() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir_all(&slide_dir).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "Slide 1").unwrap();
+    fs::write(
+        tmp_dir_pth.join("template.html"),
+        "{{ author }}, {{ course.code }} in {{ course.year }}:{% for tag in tags %} {{ tag }}{% endfor %}",
+    )
+    .unwrap();
+
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    fs::write(
+        &cfg_file,
+        r#"
+title: "Test Presentation"
+slide_dir: "slides"
+output_dir: "output"
+output_file: "index.html"
+template_file: "template.html"
+extra:
+  author: "Jane"
+  course:
+    code: "CS101"
+    year: 2024
+  tags: ["intro", "rust"]
+"#,
+    )
+    .unwrap();
+
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    cfg.package().expect("package to succeed");
+    let output = fs::read_to_string(tmp_dir_pth.join("output/index.html")).unwrap();
+    assert_eq!(output, "Jane, CS101 in 2024: intro rust");
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_from_config_has_config_path() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "Slide 1").unwrap();
+    let template_file = tmp_dir_pth.join("template.html");
+    fs::write(&template_file, "Built from {{ config_path }}").unwrap();
+
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    let cfg_str = r#"
+title: "Test Presentation"
+slide_dir: "slides"
+output_dir: "output"
+output_file: "index.html"
+template_file: "template.html"
+"#;
+    fs::write(&cfg_file, cfg_str).unwrap();
+
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file.clone()).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    cfg.package().expect("package to succeed");
+
+    let output = fs::read_to_string(tmp_dir_pth.join("output/index.html")).unwrap();
+    assert_eq!(output, format!("Built from {}", cfg_file.display()));
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_builder_with_slides() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(slide_dir.join("1_intro.md"), "Intro").unwrap();
+    fs::write(slide_dir.join("2_outro.md"), "Outro").unwrap();
+    fs::write(tmp_dir_pth.join("template.html"), "{{ slide_title }} by {{ author }}: {{ slides_html }}").unwrap();
+
+    let cfg = PresentationConfigBuilder::new("Built")
+        .add_slide(slide_dir.join("2_outro.md"))
+        .add_slide(slide_dir.join("1_intro.md"))
+        .template_file(tmp_dir_pth.join("template.html"))
+        .output_dir(tmp_dir_pth.join("dist"))
+        .context("author", "Jane")
+        .build()
+        .unwrap();
+    assert_eq!(cfg.title, "Built");
+    // explicit slides keep the order they were added in
+    let names = cfg
+        .slides
+        .iter()
+        .map(|s| s.path.file_name().unwrap().to_str().unwrap().to_string())
+        .collect::<Vec<String>>();
+    assert_eq!(names, vec!["2_outro.md", "1_intro.md"]);
+    cfg.package().unwrap();
+    let output = fs::read_to_string(tmp_dir_pth.join("dist/index.html")).unwrap();
+    assert!(output.starts_with("Built by Jane: "));
+    assert!(output.find("Outro").unwrap() < output.find("Intro").unwrap());
+
+    // validation still runs
+    let err = PresentationConfigBuilder::new("Built")
+        .add_slide(slide_dir.join("1_intro.md"))
+        .template_file(tmp_dir_pth.join("missing.html"))
+        .build()
+        .unwrap_err();
+    assert!(matches!(err, PresentationError::MissingTemplate { .. }));
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_builder_with_slide_dir() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(slide_dir.join("10_end.md"), "End").unwrap();
+    fs::write(slide_dir.join("2_middle.md"), "Middle").unwrap();
+    fs::write(slide_dir.join("3_draft.md"), "---\ndraft: true\n---\nDraft").unwrap();
+    let extra_slide = tmp_dir_pth.join("0_start.md");
+    fs::write(&extra_slide, "Start").unwrap();
+
+    let builder = PresentationConfigBuilder::new("Built")
+        .add_slide(&extra_slide)
+        .slides_from_dir(&slide_dir)
+        .output_dir(slide_dir.join("output"))
+        .output_file("slides.html");
+    let cfg = builder.clone().build().unwrap();
+    let names = cfg
+        .slides
+        .iter()
+        .map(|s| s.path.file_name().unwrap().to_str().unwrap().to_string())
+        .collect::<Vec<String>>();
+    assert_eq!(names, vec!["0_start.md", "2_middle.md", "10_end.md"]);
+    assert_eq!(cfg.output_dir, slide_dir.join("output"));
+    assert_eq!(cfg.output_filename, PathBuf::from("slides.html"));
+
+    let cfg = builder.include_drafts(true).build().unwrap();
+    assert_eq!(cfg.slides.len(), 4);
+
+    let err = PresentationConfigBuilder::new("Built")
+        .slides_from_dir(tmp_dir_pth.join("sildes"))
+        .build()
+        .unwrap_err();
+    assert!(err.to_string().contains("sildes"));
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_render_to_string() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::create_dir(tmp_dir_pth.join("images")).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "# Hello\n\n![img](../images/image.png)").unwrap();
+    fs::write(tmp_dir_pth.join("images/image.png"), "not really a png").unwrap();
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    fs::write(&cfg_file, "title: t\nslide_dir: slides\noutput_dir: output\noutput_file: index.html\n").unwrap();
+
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    let html = cfg.render_to_string().unwrap();
+    assert!(html.contains("<h1>Hello</h1>"));
+    let img_dst = hashed_dst_path(&tmp_dir_pth.join("images/image.png")).unwrap();
+    assert!(html.contains(&format!("src=\"{}\"", img_dst.display())));
+    // nothing is written, not even the images
+    assert!(!tmp_dir_pth.join("output").exists());
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_errors_can_be_matched() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    fs::write(&cfg_file, "title: t\nslide_dir: slides\noutput_dir: output\noutput_file: index.html\n").unwrap();
+    let load = || {
+        let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file.clone()).unwrap();
+        PresentationConfig::try_from(cfg_file_obj)
+    };
+
+    fs::write(slide_dir.join("1_slide1.md"), "![gone](./gone.png)").unwrap();
+    match load().unwrap_err() {
+        PresentationError::ImageNotFound { slide, image } => {
+            assert_eq!(slide, slide_dir.join("1_slide1.md"));
+            assert_eq!(image, slide_dir.join("gone.png"));
+        }
+        e => panic!("expected ImageNotFound, got {:?}", e),
+    }
+
+    fs::write(slide_dir.join("1_slide1.md"), "---\nauto_slide_ms: soon\n---\nSlide 1").unwrap();
+    match load().unwrap_err() {
+        PresentationError::InvalidSlide { path, reason } => {
+            assert_eq!(path, slide_dir.join("1_slide1.md"));
+            assert!(reason.contains("Invalid front matter"));
+        }
+        e => panic!("expected InvalidSlide, got {:?}", e),
+    }
+
+    fs::write(slide_dir.join("1_slide1.md"), "{{ nope }}").unwrap();
+    fs::write(&cfg_file, "title: t\nslide_dir: slides\noutput_dir: output\noutput_file: index.html\nrender_slides: true\n").unwrap();
+    let err = load().unwrap().package().unwrap_err();
+    assert!(matches!(err, PresentationError::Render(_)), "expected Render, got {:?}", err);
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_build_all_reports_failures_without_stopping() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+
+    let mut config_paths = Vec::new();
+    for deck in ["deck1", "deck2", "deck3"] {
+        let deck_dir = tmp_dir_pth.join(deck);
+        fs::create_dir_all(deck_dir.join("slides")).unwrap();
+        fs::write(deck_dir.join("slides/1_slide1.md"), "Slide 1").unwrap();
+        // deck2 is missing its template, so it will fail to build
+        if deck != "deck2" {
+            fs::write(deck_dir.join("template.html"), "{{ slide_title }}").unwrap();
+        }
+        let cfg_file = deck_dir.join("config.yaml");
+        let cfg_str = format!(
+            r#"
+title: "{}"
+slide_dir: "slides"
+output_dir: "output"
+output_file: "index.html"
+template_file: "template.html"
+"#,
+            deck
+        );
+        fs::write(&cfg_file, cfg_str).unwrap();
+        config_paths.push(cfg_file);
+    }
+
+    let failures = build_all(&config_paths, Some(2), |config_path| {
+        let cfg_file_obj = PresentationConfigFile::read_config_file(config_path.to_path_buf())?;
+        PresentationConfig::try_from(cfg_file_obj)?.package()?;
+        Ok(())
+    })
+    .unwrap();
+
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].config_path, config_paths[1]);
+    assert!(failures[0].error.to_string().contains("template_file"));
+    assert!(matches!(
+        failures[0].error.downcast_ref::<PresentationError>(),
+        Some(PresentationError::MissingTemplate { path }) if path == &tmp_dir_pth.join("deck2/template.html")
+    ));
+    assert_eq!(
+        fs::read_to_string(tmp_dir_pth.join("deck1/output/index.html")).unwrap(),
+        "deck1"
+    );
+    assert_eq!(
+        fs::read_to_string(tmp_dir_pth.join("deck3/output/index.html")).unwrap(),
+        "deck3"
+    );
+    assert!(!tmp_dir_pth.join("deck2/output").exists());
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_build_all_stays_within_thread_pool() {
+    let config_paths = (1..=4)
+        .map(|i| PathBuf::from(format!("deck{}.yaml", i)))
+        .collect::<Vec<PathBuf>>();
+    // stands in for the global pool that `--threads 2` sets up
+    let pool = rayon::ThreadPoolBuilder::new().num_threads(2).build().unwrap();
+    for jobs in [None, Some(1), Some(8)] {
+        let threads = Mutex::new(Vec::new());
+        let failures = pool
+            .install(|| {
+                build_all(&config_paths, jobs, |_| {
+                    threads.lock().unwrap().push(rayon::current_num_threads());
+                    Ok(())
+                })
+            })
+            .unwrap();
+        assert!(failures.is_empty());
+        let threads = threads.into_inner().unwrap();
+        assert_eq!(threads.len(), 4);
+        let expected = if jobs == Some(1) { 1 } else { 2 };
+        assert!(threads.iter().all(|n| *n == expected), "{:?} with {:?} jobs", threads, jobs);
+    }
+}
+
+/// Writer that appends everything to a shared buffer, for capturing logs
+#[derive(Clone, Default)]
+struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+impl Write for SharedBuf {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Runs `f`, returning its result and any warnings that were logged
+fn capture_warnings<T>(f: impl FnOnce() -> T) -> (T, String) {
+    let buf = SharedBuf::default();
+    let writer = buf.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::WARN)
+        .with_ansi(false)
+        .with_writer(move || writer.clone())
+        .finish();
+    let result = tracing::subscriber::with_default(subscriber, f);
+    let logs = String::from_utf8(buf.0.lock().unwrap().clone()).unwrap();
+    (result, logs)
+}
+
+#[test]
+fn test_presentation_with_empty_template() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "Slide 1").unwrap();
+    let template_file = tmp_dir_pth.join("template.html");
+    fs::write(&template_file, " \n\t\n").unwrap();
+
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    let cfg_str = r#"
+title: "Test Presentation"
+slide_dir: "slides"
+output_dir: "output"
+output_file: "index.html"
+template_file: "template.html"
+"#;
+    fs::write(&cfg_file, cfg_str).unwrap();
+
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file.clone()).unwrap();
+    let (cfg, logs) = capture_warnings(|| PresentationConfig::try_from(cfg_file_obj));
+    assert!(cfg.is_ok());
+    assert!(logs.contains("WARN"));
+    assert!(logs.contains("is empty"));
+
+    let mut cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    cfg_file_obj.strict = true;
+    let err = PresentationConfig::try_from(cfg_file_obj).unwrap_err();
+    assert!(err.to_string().contains("Template file is empty"));
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_with_existing_output_file() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "Slide 1").unwrap();
+    fs::create_dir(tmp_dir_pth.join("output")).unwrap();
+    fs::write(tmp_dir_pth.join("output/index.html"), "committed").unwrap();
+
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    let cfg_str = r#"
+title: "Test Presentation"
+slide_dir: "slides"
+output_dir: "output"
+output_file: "index.html"
+"#;
+    fs::write(&cfg_file, cfg_str).unwrap();
+
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file.clone()).unwrap();
+    let (cfg, logs) = capture_warnings(|| PresentationConfig::try_from(cfg_file_obj));
+    assert!(cfg.is_ok());
+    assert!(logs.contains("already exists, will overwrite"));
+
+    let mut cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    cfg_file_obj.no_overwrite = true;
+    let err = PresentationConfig::try_from(cfg_file_obj).unwrap_err();
+    assert!(err.to_string().contains("Output file already exists"));
+    assert_eq!(
+        fs::read_to_string(tmp_dir_pth.join("output/index.html")).unwrap(),
+        "committed"
+    );
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_with_template_missing_slides() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "Slide 1").unwrap();
+    let template_file = tmp_dir_pth.join("template.html");
+    fs::write(&template_file, "<title>{{ slide_title }}</title>").unwrap();
+
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    let cfg_str = r#"
+title: "Test Presentation"
+slide_dir: "slides"
+output_dir: "output"
+output_file: "index.html"
+template_file: "template.html"
+"#;
+    fs::write(&cfg_file, cfg_str).unwrap();
+
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file.clone()).unwrap();
+    let (cfg, logs) = capture_warnings(|| PresentationConfig::try_from(cfg_file_obj));
+    assert!(cfg.is_ok());
+    assert!(logs.contains("WARN"));
+    assert!(logs.contains("does not use the slides"));
+
+    let mut cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file.clone()).unwrap();
+    cfg_file_obj.strict = true;
+    let err = PresentationConfig::try_from(cfg_file_obj).unwrap_err();
+    assert!(err.to_string().contains("does not use the slides"));
+
+    fs::write(
+        &template_file,
+        "<title>{{ slide_title }}</title>{% for fc in ingested_files %}{{ fc }}{% endfor %}",
+    )
+    .unwrap();
+    let mut cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    cfg_file_obj.strict = true;
+    let (cfg, logs) = capture_warnings(|| PresentationConfig::try_from(cfg_file_obj));
+    assert!(cfg.is_ok());
+    assert!(!logs.contains("WARN"));
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_include_drafts() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "Slide 1").unwrap();
+    fs::write(slide_dir.join("2_slide2.md"), "---\ndraft: true\n---\nSlide 2").unwrap();
+    fs::write(slide_dir.join("3_slide3.md"), "Slide 3").unwrap();
+    let template_file = tmp_dir_pth.join("template.html");
+    fs::write(&template_file, "{{ slide_title }}").unwrap();
+
+    let args = CliArgs::parse_from([
+        OsStr::new("mkrevealslides"),
+        OsStr::new("from-cli"),
+        slide_dir.as_os_str(),
+        OsStr::new("--template-file"),
+        template_file.as_os_str(),
+    ]);
+    let cfg = PresentationConfig::try_from(args).unwrap();
+    assert_eq!(cfg.slides.len(), 2);
+    assert!(cfg.slides.iter().all(|s| !s.meta.draft));
+
+    let args = CliArgs::parse_from([
+        OsStr::new("mkrevealslides"),
+        OsStr::new("--include-drafts"),
+        OsStr::new("from-cli"),
+        slide_dir.as_os_str(),
+        OsStr::new("--template-file"),
+        template_file.as_os_str(),
+    ]);
+    let cfg = PresentationConfig::try_from(args).unwrap();
+    assert_eq!(cfg.slides.len(), 3);
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_with_default_template() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "# Hello there").unwrap();
+
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    let cfg_str = r#"
+title: "Test Presentation"
+slide_dir: "slides"
+output_dir: "output"
+output_file: "index.html"
+"#;
+    fs::write(&cfg_file, cfg_str).unwrap();
+
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    assert_eq!(cfg.template_file, None);
+    cfg.package().expect("package to succeed");
+
+    let output = fs::read_to_string(tmp_dir_pth.join("output/index.html")).unwrap();
+    assert!(output.contains("<title>Test Presentation</title>"));
+    assert!(output.contains("<h1>Hello there</h1>"));
+    assert!(output.contains("reveal.js"));
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_with_slide_extensions() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(slide_dir.join("1_slide1.mdx"), "Slide 1").unwrap();
+    fs::write(slide_dir.join("2_slide2.md"), "Slide 2").unwrap();
+
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    let cfg_str = r#"
+title: "Test Presentation"
+slide_dir: "slides"
+output_dir: "output"
+output_file: "index.html"
+slide_extensions: ["md", "mdx"]
+"#;
+    fs::write(&cfg_file, cfg_str).unwrap();
+
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    assert_eq!(
+        cfg.slides.iter().map(|s| s.path.clone()).collect::<Vec<PathBuf>>(),
+        vec![slide_dir.join("1_slide1.mdx"), slide_dir.join("2_slide2.md")]
+    );
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_with_render_slides() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+    let cfg_file = write_extra_context_deck(&tmp_dir_pth, "render_slides: true");
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::write(slide_dir.join("1_slide1.md"), "Slide by {{ author }}").unwrap();
+    fs::write(tmp_dir_pth.join("template.html"), "{{ slides_html }}").unwrap();
+
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file.clone()).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    cfg.package().expect("package to succeed");
+    let output = fs::read_to_string(tmp_dir_pth.join("output/index.html")).unwrap();
+    assert_eq!(output, "<section><p>Slide by Jane</p>\n</section>");
+
+    let bad_slide = slide_dir.join("2_slide2.md");
+    fs::write(&bad_slide, "Broken {{ author").unwrap();
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    let err = cfg.package().unwrap_err();
+    assert!(err.to_string().contains(&bad_slide.display().to_string()));
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_render_errors_name_their_file() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+    let cfg_file = write_extra_context_deck(&tmp_dir_pth, "render_slides: true");
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::write(slide_dir.join("1_slide1.md"), "Slide 1").unwrap();
+    let raw_slide = slide_dir.join("2_slide2.md");
+    fs::write(&raw_slide, "Use `{{` to open a tag").unwrap();
+    let template_file = tmp_dir_pth.join("template.html");
+    fs::write(&template_file, "{{ slide_title }}{{ slides_html }}").unwrap();
+
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file.clone()).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    let err = cfg.package().unwrap_err();
+    assert!(err.to_string().contains(&format!("Failed to render slide `{}`", raw_slide.display())));
+
+    fs::write(&raw_slide, "Slide 2").unwrap();
+    fs::write(&template_file, "{{ slide_title }}{% for s in slides %}{{ s.contents }}").unwrap();
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    let err = cfg.package().unwrap_err();
+    assert!(err
+        .to_string()
+        .contains(&format!("Failed to render template `{}`", template_file.display())));
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_with_slide_index_img_path_template() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+    let slide_dir = tmp_dir_pth.join("slides");
+    let img_dir = tmp_dir_pth.join("images");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::create_dir(&img_dir).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "Slide 1").unwrap();
+    fs::write(slide_dir.join("2_slide2.md"), "Slide 2").unwrap();
+    fs::write(slide_dir.join("3_slide3.md"), "![img](../images/image.png)").unwrap();
+    fs::write(img_dir.join("image.png"), "not really a png").unwrap();
+    fs::write(tmp_dir_pth.join("template.html"), "{{ slides_html }}").unwrap();
+
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    let cfg_str = r#"
+title: "Test Presentation"
+slide_dir: "slides"
+output_dir: "output"
+output_file: "index.html"
+template_file: "template.html"
+img_path_template: "./img/{slide_index}/{file_name}"
+"#;
+    fs::write(&cfg_file, cfg_str).unwrap();
+
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    cfg.package().expect("package to succeed");
+    assert!(tmp_dir_pth.join("output/img/03/image.png").is_file());
+    let output = fs::read_to_string(tmp_dir_pth.join("output/index.html")).unwrap();
+    assert!(output.contains("src=\"./img/03/image.png\""));
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_export_markdown() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+    let slide_dir = tmp_dir_pth.join("slides");
+    let img_dir = tmp_dir_pth.join("images");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::create_dir(&img_dir).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "---\ndraft: false\n---\n# Slide 1\n").unwrap();
+    fs::write(slide_dir.join("2_slide2.md"), "![diagram](../images/image.png \"A diagram\")\n").unwrap();
+    fs::write(img_dir.join("image.png"), "not really a png").unwrap();
+
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    let cfg_str = r#"
+title: "Test Presentation"
+slide_dir: "slides"
+output_dir: "output"
+output_file: "index.html"
+slide_join: "\n---\n\n"
+"#;
+    fs::write(&cfg_file, cfg_str).unwrap();
+
+    let args = CliArgs::parse_from([
+        OsStr::new("mkrevealslides"),
+        OsStr::new("export-md"),
+        cfg_file.as_os_str(),
+    ]);
+    let cfg = PresentationConfig::try_from(args).unwrap();
+    cfg.package_markdown(&PathBuf::from("slides.md"), false).expect("export to succeed");
+    let output = fs::read_to_string(tmp_dir_pth.join("output/slides.md")).unwrap();
+    let img_dst_path = hashed_dst_path(&img_dir.join("image.png")).unwrap();
+    assert_eq!(
+        output,
+        format!(
+            "# Slide 1\n\n---\n\n![diagram]({} \"A diagram\")\n",
+            img_dst_path.display()
+        )
+    );
+    assert!(tmp_dir_pth.join("output").join(img_dst_path).is_file());
+    assert!(!tmp_dir_pth.join("output/index.html").exists());
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_with_background_color_front_matter() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "---\nbackground_color: \"#1a1a1a\"\n---\nSlide 1").unwrap();
+    let bad_slide = slide_dir.join("2_slide2.md");
+    fs::write(&bad_slide, "---\nbackground_color: blurple\n---\nSlide 2").unwrap();
+    let template_file = tmp_dir_pth.join("template.html");
+    fs::write(&template_file, "{{ slides_html }}").unwrap();
+    let output_dir = tmp_dir_pth.join("output");
+
+    let args = CliArgs::parse_from([
+        OsStr::new("mkrevealslides"),
+        OsStr::new("from-cli"),
+        slide_dir.as_os_str(),
+        OsStr::new("--template-file"),
+        template_file.as_os_str(),
+        output_dir.as_os_str(),
+    ]);
+    let (cfg, warnings) = capture_warnings(|| PresentationConfig::try_from(args).unwrap());
+    assert!(warnings.contains("does not look like a color: `blurple`"));
+    assert!(warnings.contains(&bad_slide.display().to_string()));
+    assert!(!warnings.contains("#1a1a1a"));
+
+    cfg.package().expect("package to succeed");
+    let output = fs::read_to_string(output_dir.join("index.html")).unwrap();
+    assert!(output.contains("<section data-background-color=\"#1a1a1a\"><p>Slide 1</p>"));
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_with_background_and_transition_front_matter() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(
+        slide_dir.join("1_slide1.md"),
+        "---\nbackground: \"#222\"\ntransition: fade\n---\nSlide 1",
+    )
+    .unwrap();
+    fs::write(slide_dir.join("2_slide2.md"), "Slide 2").unwrap();
+    let template_file = tmp_dir_pth.join("template.html");
+    fs::write(&template_file, "{{ slides_html }}").unwrap();
+    let output_dir = tmp_dir_pth.join("output");
+
+    let args = CliArgs::parse_from([
+        OsStr::new("mkrevealslides"),
+        OsStr::new("from-cli"),
+        slide_dir.as_os_str(),
+        OsStr::new("--template-file"),
+        template_file.as_os_str(),
+        output_dir.as_os_str(),
+    ]);
+    let cfg = PresentationConfig::try_from(args).unwrap();
+    assert_eq!(cfg.slides[0].meta.background_color.as_deref(), Some("#222"));
+    cfg.package().expect("package to succeed");
+    let output = fs::read_to_string(output_dir.join("index.html")).unwrap();
+    assert!(output.contains(
+        "<section data-background-color=\"#222\" data-transition=\"fade\"><p>Slide 1</p>"
+    ));
+    assert!(output.contains("<section><p>Slide 2</p>"));
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_with_no_remote_images() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "Slide 1").unwrap();
+    let remote_slide = slide_dir.join("2_slide2.md");
+    fs::write(&remote_slide, "![logo](https://example.com/logo.png)").unwrap();
+    let template_file = tmp_dir_pth.join("template.html");
+    fs::write(&template_file, "{{ slides_html }}").unwrap();
+
+    let args = CliArgs::parse_from([
+        OsStr::new("mkrevealslides"),
+        OsStr::new("from-cli"),
+        slide_dir.as_os_str(),
+        OsStr::new("--template-file"),
+        template_file.as_os_str(),
+    ]);
+    let cfg = PresentationConfig::try_from(args).unwrap();
+    assert_eq!(cfg.slides[1].remote_images, vec!["https://example.com/logo.png"]);
+
+    let args = CliArgs::parse_from([
+        OsStr::new("mkrevealslides"),
+        OsStr::new("--no-remote-images"),
+        OsStr::new("from-cli"),
+        slide_dir.as_os_str(),
+        OsStr::new("--template-file"),
+        template_file.as_os_str(),
+    ]);
+    let err = PresentationConfig::try_from(args).unwrap_err().to_string();
+    assert!(err.contains("https://example.com/logo.png"));
+    assert!(err.contains(&remote_slide.display().to_string()));
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_with_same_named_images() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    let img_a = tmp_dir_pth.join("a/image.png");
+    let img_b = tmp_dir_pth.join("b/image.png");
+    for (img, contents) in [(&img_a, "first image"), (&img_b, "second image")] {
+        fs::create_dir_all(img.parent().unwrap()).unwrap();
+        fs::write(img, contents).unwrap();
+    }
+    fs::write(slide_dir.join("1_slide1.md"), "![](../a/image.png)").unwrap();
+    fs::write(slide_dir.join("2_slide2.md"), "![](../b/image.png) ![](../a/image.png)").unwrap();
+    let template_file = tmp_dir_pth.join("template.html");
+    fs::write(&template_file, "{{ slides_html }}").unwrap();
+    let output_dir = tmp_dir_pth.join("output");
+
+    let args = CliArgs::parse_from([
+        OsStr::new("mkrevealslides"),
+        OsStr::new("from-cli"),
+        slide_dir.as_os_str(),
+        OsStr::new("--template-file"),
+        template_file.as_os_str(),
+        output_dir.as_os_str(),
+    ]);
+    let cfg = PresentationConfig::try_from(args).unwrap();
+    cfg.package().expect("package to succeed");
+
+    let dst_a = hashed_dst_path(&img_a).unwrap();
+    let dst_b = hashed_dst_path(&img_b).unwrap();
+    assert_ne!(dst_a, dst_b);
+    assert_eq!(fs::read_to_string(output_dir.join(&dst_a)).unwrap(), "first image");
+    assert_eq!(fs::read_to_string(output_dir.join(&dst_b)).unwrap(), "second image");
+    // the image referenced by both slides is only copied once
+    assert_eq!(fs::read_dir(output_dir.join("img")).unwrap().count(), 2);
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_package_with_progress() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    let img = tmp_dir_pth.join("image.png");
+    fs::write(&img, "not really a png").unwrap();
+    let slide_1 = slide_dir.join("1_slide1.md");
+    let slide_2 = slide_dir.join("2_slide2.md");
+    fs::write(&slide_1, "![](../image.png)").unwrap();
+    fs::write(&slide_2, "Slide 2").unwrap();
+    let template_file = tmp_dir_pth.join("template.html");
+    fs::write(&template_file, "{{ slides_html }}").unwrap();
+    let output_dir = tmp_dir_pth.join("output");
+
+    let args = CliArgs::parse_from([
+        OsStr::new("mkrevealslides"),
+        OsStr::new("--quiet"),
+        OsStr::new("from-cli"),
+        slide_dir.as_os_str(),
+        OsStr::new("--template-file"),
+        template_file.as_os_str(),
+        output_dir.as_os_str(),
+    ]);
+    let cfg = PresentationConfig::try_from(args).unwrap();
+    let mut events = Vec::new();
+    cfg.package_with_progress(|e| events.push(e))
+        .expect("package to succeed");
+
+    let img_dst_path = hashed_dst_path(&img).unwrap();
+    assert_eq!(
+        events,
+        vec![
+            BuildEvent::ParsingSlide { path: slide_1 },
+            BuildEvent::ParsingSlide { path: slide_2 },
+            BuildEvent::RenderingDone { theme: None },
+            BuildEvent::CopyingImage {
+                src: img,
+                dst: img_dst_path,
+            },
+            BuildEvent::Finished {
+                images_copied: 1,
+                bytes_written: fs::metadata(output_dir.join("index.html")).unwrap().len() + 16,
+            },
+        ]
+    );
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_with_same_named_images_in_one_slide() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    for (dir, contents) in [("a", "first diagram"), ("b", "second diagram")] {
+        fs::create_dir(tmp_dir_pth.join(dir)).unwrap();
+        fs::write(tmp_dir_pth.join(dir).join("diagram.png"), contents).unwrap();
+    }
+    fs::write(
+        slide_dir.join("1_slide1.md"),
+        "![](../a/diagram.png) ![](../b/diagram.png)",
+    )
+    .unwrap();
+    fs::write(tmp_dir_pth.join("template.html"), "{{ slides_html }}").unwrap();
+
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    let cfg_str = r#"
+title: "Test Presentation"
+slide_dir: "slides"
+output_dir: "output"
+output_file: "index.html"
+template_file: "template.html"
+img_path_template: "./img/{slide_stem}/{file_name}"
+"#;
+    fs::write(&cfg_file, cfg_str).unwrap();
+
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    cfg.package().expect("package to succeed");
+
+    let images = &cfg.slides[0].local_images;
+    assert_eq!(images[0].1, PathBuf::from("./img/1_slide1/diagram.png"));
+    assert_ne!(images[0].1, images[1].1);
+    let output_dir = tmp_dir_pth.join("output");
+    assert_eq!(fs::read_to_string(output_dir.join(&images[0].1)).unwrap(), "first diagram");
+    assert_eq!(fs::read_to_string(output_dir.join(&images[1].1)).unwrap(), "second diagram");
+    tmp_dir.close().unwrap();
+}
+
+/// Serves `body` at `/pic.png` and 404s everything else, returning the address served on
+fn serve_image(body: &'static [u8]) -> std::net::SocketAddr {
+    serve_file("/pic.png", body.to_vec()).0
+}
+
+/// Serves `body` at `path` over HTTP on a local port.
+/// Returns the address of the server, and how many times the file has been requested
+fn serve_file(path: &'static str, body: Vec<u8>) -> (std::net::SocketAddr, Arc<AtomicUsize>) {
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpListener;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let hits = Arc::new(AtomicUsize::new(0));
+    let server_hits = hits.clone();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+            if request_line.starts_with(&format!("GET {} ", path)) {
+                server_hits.fetch_add(1, Ordering::SeqCst);
+                write!(stream, "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", body.len()).unwrap();
+                stream.write_all(&body).unwrap();
+            } else {
+                write!(stream, "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n").unwrap();
+            }
+        }
+    });
+    (addr, hits)
+}
+
+#[test]
+fn test_presentation_with_download_remote_images() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+    let addr = serve_image(b"remote png");
+
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    let missing_url = format!("http://{}/missing.png", addr);
+    let slide_2 = slide_dir.join("2_slide2.md");
+    fs::write(slide_dir.join("1_slide1.md"), format!("![](http://{}/pic.png)", addr)).unwrap();
+    fs::write(&slide_2, format!("![]({})", missing_url)).unwrap();
+    fs::write(tmp_dir_pth.join("template.html"), "{{ slides_html }}").unwrap();
+
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    let cfg_str = r#"
+title: "Test Presentation"
+slide_dir: "slides"
+output_dir: "output"
+output_file: "index.html"
+template_file: "template.html"
+download_remote_images: true
+"#;
+    fs::write(&cfg_file, cfg_str).unwrap();
+
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    let (result, warnings) = capture_warnings(|| cfg.package());
+    result.expect("package to succeed");
+    assert!(warnings.contains(&missing_url));
+    assert!(warnings.contains(&slide_2.display().to_string()));
+
+    let output_dir = tmp_dir_pth.join("output");
+    let img_dst_path = fs::read_dir(output_dir.join("img"))
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .collect::<Vec<PathBuf>>();
+    assert_eq!(img_dst_path.len(), 1);
+    assert_eq!(img_dst_path[0].extension().unwrap(), "png");
+    assert_eq!(fs::read(&img_dst_path[0]).unwrap(), b"remote png");
+
+    let output = fs::read_to_string(output_dir.join("index.html")).unwrap();
+    let file_name = img_dst_path[0].file_name().unwrap().to_str().unwrap();
+    assert!(output.contains(&format!("src=\"./img/{}\"", file_name)));
+    assert!(output.contains(&format!("src=\"{}\"", missing_url)));
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_with_embed_images() {
+    use base64::Engine;
+
+    // a 1x1 transparent PNG
+    const PNG: &[u8] = &[
+        0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48, 0x44,
+        0x52, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x08, 0x06, 0x00, 0x00, 0x00, 0x1f,
+        0x15, 0xc4, 0x89, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x44, 0x41, 0x54, 0x78, 0x9c, 0x63, 0x00,
+        0x01, 0x00, 0x00, 0x05, 0x00, 0x01, 0x0d, 0x0a, 0x2d, 0xb4, 0x00, 0x00, 0x00, 0x00, 0x49,
+        0x45, 0x4e, 0x44, 0xae, 0x42, 0x60, 0x82,
+    ];
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(tmp_dir_pth.join("pixel.png"), PNG).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "![pixel](../pixel.png)").unwrap();
+    fs::write(tmp_dir_pth.join("template.html"), "{{ slides_html }}").unwrap();
+
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    let cfg_str = r#"
+title: "Test Presentation"
+slide_dir: "slides"
+output_dir: "output"
+output_file: "index.html"
+template_file: "template.html"
+embed_images: true
+"#;
+    fs::write(&cfg_file, cfg_str).unwrap();
+
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    assert!(cfg.slides[0].local_images.is_empty());
+    cfg.package().expect("package to succeed");
+
+    let output_dir = tmp_dir_pth.join("output");
+    assert!(!output_dir.join("img").exists());
+    let output = fs::read_to_string(output_dir.join("index.html")).unwrap();
+    let src = output
+        .split("src=\"")
+        .nth(1)
+        .and_then(|rest| rest.split('"').next())
+        .unwrap();
+    let encoded = src.strip_prefix("data:image/png;base64,").unwrap();
+    let decoded = base64::engine::general_purpose::STANDARD.decode(encoded).unwrap();
+    assert_eq!(decoded, PNG);
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_export_handout() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "# Intro\n").unwrap();
+    fs::write(slide_dir.join("2_slide2.md"), "Some text").unwrap();
+    fs::write(slide_dir.join("3_slide3.md"), "# Outro\n\nThanks\n").unwrap();
+
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    let cfg_str = r#"
+title: "Test Presentation"
+slide_dir: "slides"
+output_dir: "output"
+output_file: "index.html"
+"#;
+    fs::write(&cfg_file, cfg_str).unwrap();
+
+    let args = CliArgs::parse_from([
+        OsStr::new("mkrevealslides"),
+        OsStr::new("export-md"),
+        cfg_file.as_os_str(),
+        OsStr::new("--handout"),
+    ]);
+    let cfg = PresentationConfig::try_from(args).unwrap();
+    cfg.package_markdown(&PathBuf::from("handout.md"), true).expect("export to succeed");
+    let output = fs::read_to_string(tmp_dir_pth.join("output/handout.md")).unwrap();
+    assert_eq!(
+        output,
+        "## Slide 1\n\n# Intro\n\n---\n\n## Slide 2\n\nSome text\n\n---\n\n## Slide 3\n\n# Outro\n\nThanks\n"
+    );
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_with_minify() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "# Intro\n\nSome   text\n").unwrap();
+    fs::write(
+        slide_dir.join("2_slide2.md"),
+        "```\nfn main() {\n    println!(\"hi\");\n}\n```\n",
+    )
+    .unwrap();
+
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    let cfg_str = r#"
+title: "Test Presentation"
+slide_dir: "slides"
+output_dir: "output"
+output_file: "index.html"
+"#;
+    fs::write(&cfg_file, cfg_str).unwrap();
+
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    let mut cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    assert!(!cfg.minify);
+    cfg.package().expect("package to succeed");
+    let unminified = fs::read_to_string(tmp_dir_pth.join("output/index.html")).unwrap();
+
+    cfg.minify = true;
+    cfg.package().expect("package to succeed");
+    let minified = fs::read_to_string(tmp_dir_pth.join("output/index.html")).unwrap();
+    assert!(minified.len() < unminified.len());
+    assert!(minified.contains("Intro"));
+    assert!(minified.contains("Some text"));
+    // whitespace in code blocks is kept
+    assert!(minified.contains("fn main() {\n    println!"));
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_with_zip_output() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::create_dir(tmp_dir_pth.join("images")).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "![img](../images/image.png)").unwrap();
+    fs::write(tmp_dir_pth.join("images/image.png"), "not really a png").unwrap();
+
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    let cfg_str = r#"
+title: "Test Presentation"
+slide_dir: "slides"
+output_dir: "output"
+output_file: "index.html"
+zip_output: true
+"#;
+    fs::write(&cfg_file, cfg_str).unwrap();
+
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    // packaging twice must not put the previous archive into the new one
+    cfg.package().expect("package to succeed");
+    cfg.package().expect("package to succeed");
+
+    let zip_file = fs::File::open(tmp_dir_pth.join("output/index.zip")).unwrap();
+    let mut archive = zip::ZipArchive::new(zip_file).unwrap();
+    let img_dst_path = hashed_dst_path(&tmp_dir_pth.join("images/image.png")).unwrap();
+    let img_name = img_dst_path.strip_prefix("./").unwrap().to_str().unwrap().to_string();
+    let mut names = archive.file_names().map(|n| n.unwrap().into_owned()).collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(names, vec![img_name, "index.html".to_string()]);
+    let mut index = String::new();
+    archive.by_name("index.html").unwrap().read_to_string(&mut index).unwrap();
+    assert_eq!(index, fs::read_to_string(tmp_dir_pth.join("output/index.html")).unwrap());
+    tmp_dir.close().unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_presentation_with_thumbnails_and_outputs() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "# One").unwrap();
+    fs::write(slide_dir.join("2_slide2.md"), "# Two").unwrap();
+    fs::write(tmp_dir_pth.join("deck.html"), "{{ slides_html }}").unwrap();
+
+    // stands in for a browser, writing each screenshot and recording its arguments
+    let browser = tmp_dir_pth.join("browser.sh");
+    let args_log = tmp_dir_pth.join("args.log");
+    fs::write(
+        &browser,
+        format!(
+            "#!/bin/sh\necho \"$@\" >> '{}'\nfor arg in \"$@\"; do\n  case \"$arg\" in --screenshot=*) echo png > \"${{arg#--screenshot=}}\";; esac\ndone\n",
+            args_log.display()
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&browser, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    let cfg_str = r#"
+title: "Test Presentation"
+slide_dir: "slides"
+output_dir: "output"
+thumbnails: true
+outputs:
+  - template_file: "deck.html"
+    output_file: "deck.html"
+"#;
+    fs::write(&cfg_file, cfg_str).unwrap();
+
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    std::env::set_var("MKREVEALSLIDES_BROWSER", &browser);
+    let packaged = cfg.package();
+    std::env::remove_var("MKREVEALSLIDES_BROWSER");
+    packaged.expect("package to succeed");
+
+    let output_dir = tmp_dir_pth.join("output");
+    assert!(output_dir.join("thumbnails/1.png").is_file());
+    assert!(output_dir.join("thumbnails/2.png").is_file());
+    assert!(output_dir.join("contact_sheet.png").is_file());
+    let overview = fs::read_to_string(output_dir.join("overview.html")).unwrap();
+    assert!(overview.contains("<a href=\"deck.html#/1/0\">"));
+    let args = fs::read_to_string(&args_log).unwrap();
+    assert_eq!(args.lines().count(), 3);
+    assert!(args.contains(&format!("{}#/0/0", output_dir.join("deck.html").display())));
+    assert!(args.contains(&format!("file://{}", output_dir.join("overview.html").display())));
+    assert!(!args.contains("--no-sandbox"));
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+#[ignore = "needs Chromium or Chrome, run with `cargo test -- --ignored`"]
+fn test_presentation_with_thumbnails() {
+    assert!(find_browser().is_some(), "no browser found");
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "# One").unwrap();
+    fs::write(slide_dir.join("2_slide2.md"), "# Two").unwrap();
+
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    let cfg_str = r#"
+title: "Test Presentation"
+slide_dir: "slides"
+output_dir: "output"
+output_file: "index.html"
+title_slide: true
+thumbnails: true
+"#;
+    fs::write(&cfg_file, cfg_str).unwrap();
+
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    assert_eq!(cfg.slide_positions(), vec![(0, 0), (1, 0), (2, 0)]);
+    cfg.package().expect("package to succeed");
+    for n in 1..=3 {
+        assert!(tmp_dir_pth.join(format!("output/thumbnails/{}.png", n)).is_file());
+    }
+    let overview = fs::read_to_string(tmp_dir_pth.join("output/overview.html")).unwrap();
+    assert!(overview.contains("thumbnails/3.png"));
+    assert!(tmp_dir_pth.join("output/contact_sheet.png").is_file());
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_with_reveal_size() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "# One").unwrap();
+    fs::write(tmp_dir_pth.join("template.html"), "{{ reveal_config }}").unwrap();
+
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    let cfg_str = r#"
+title: "Test Presentation"
+slide_dir: "slides"
+template_file: "template.html"
+output_dir: "output"
+output_file: "index.html"
+width: 1280
+height: 720
+margin: 0.1
+"#;
+    fs::write(&cfg_file, cfg_str).unwrap();
+
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file.clone()).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    cfg.package().expect("package to succeed");
+    let output = fs::read_to_string(tmp_dir_pth.join("output/index.html")).unwrap();
+    assert_eq!(output, r#"{"hash":true,"height":720.0,"margin":0.1,"width":1280.0}"#);
+
+    fs::write(&cfg_file, cfg_str.replace("margin: 0.1", "margin: -0.1")).unwrap();
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    let err = PresentationConfig::try_from(cfg_file_obj).unwrap_err();
+    assert!(err.to_string().contains("`margin` must be a positive number"));
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_with_slide_numbers() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "# One").unwrap();
+    fs::write(tmp_dir_pth.join("template.html"), "{{ reveal_config }}").unwrap();
+
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    let cfg_str = r#"
+title: "Test Presentation"
+slide_dir: "slides"
+template_file: "template.html"
+output_dir: "output"
+output_file: "index.html"
+slide_numbers: "c/t"
+"#;
+    fs::write(&cfg_file, cfg_str).unwrap();
+
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file.clone()).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    cfg.package().expect("package to succeed");
+    let output = fs::read_to_string(tmp_dir_pth.join("output/index.html")).unwrap();
+    assert_eq!(output, r#"{"hash":true,"slideNumber":"c/t"}"#);
+
+    fs::write(&cfg_file, cfg_str.replace("\"c/t\"", "true")).unwrap();
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file.clone()).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    assert_eq!(cfg.reveal_config.to_json(), r#"{"hash":true,"slideNumber":true}"#);
+
+    fs::write(&cfg_file, cfg_str.replace("c/t", "t/c")).unwrap();
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    let err = PresentationConfig::try_from(cfg_file_obj).unwrap_err();
+    assert!(err.to_string().contains("one of the formats: h.v, h/v, c, c/t"));
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_with_view_mode() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "# One").unwrap();
+    fs::write(tmp_dir_pth.join("template.html"), "{{ reveal_config }}").unwrap();
+
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    let cfg_str = r#"
+title: "Test Presentation"
+slide_dir: "slides"
+template_file: "template.html"
+output_dir: "output"
+output_file: "index.html"
+reveal_version: "5.1.0"
+view_mode: scroll
+"#;
+    fs::write(&cfg_file, cfg_str).unwrap();
+
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file.clone()).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    cfg.package().expect("package to succeed");
+    let output = fs::read_to_string(tmp_dir_pth.join("output/index.html")).unwrap();
+    assert_eq!(output, r#"{"hash":true,"view":"scroll"}"#);
+
+    fs::write(&cfg_file, cfg_str.replace("scroll", "slides")).unwrap();
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file.clone()).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    assert_eq!(cfg.reveal_config.to_json(), r#"{"hash":true}"#);
+
+    fs::write(&cfg_file, cfg_str.replace("scroll", "paged")).unwrap();
+    assert!(PresentationConfigFile::read_config_file(cfg_file.clone()).is_err());
+
+    // the default version of reveal.js predates the scroll view
+    fs::write(&cfg_file, cfg_str.replace("reveal_version: \"5.1.0\"\n", "")).unwrap();
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    let err = PresentationConfig::try_from(cfg_file_obj).unwrap_err();
+    assert!(err.to_string().contains("The scroll view needs reveal.js 5 or later"));
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_with_image_slideshow() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+    let photo_dir = tmp_dir_pth.join("photos");
+    fs::create_dir(&photo_dir).unwrap();
+    for (name, contents) in [("2.jpg", "two"), ("10.png", "ten"), ("1.png", "one")] {
+        fs::write(photo_dir.join(name), contents).unwrap();
+    }
+    fs::write(photo_dir.join("notes.txt"), "not an image").unwrap();
+
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    let cfg_str = r#"
+title: "Test Presentation"
+slide_dir: "photos"
+output_dir: "output"
+output_file: "index.html"
+image_slideshow: true
+"#;
+    fs::write(&cfg_file, cfg_str).unwrap();
+
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    assert_eq!(cfg.slides.len(), 3);
+    cfg.package().expect("package to succeed");
+
+    let output = fs::read_to_string(tmp_dir_pth.join("output/index.html")).unwrap();
+    let mut last_position = 0;
+    for name in ["1.png", "2.jpg", "10.png"] {
+        let img_dst_path = hashed_dst_path(&photo_dir.join(name)).unwrap();
+        let attribute = format!("data-background-image=\"{}\"", img_dst_path.display());
+        let position = output.find(&attribute).expect("each image to have a slide");
+        assert!(position > last_position, "slides to be in natural order");
+        last_position = position;
+        assert!(tmp_dir_pth.join("output").join(img_dst_path).is_file());
+    }
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_with_image_dir() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::create_dir(tmp_dir_pth.join("images")).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "![img](../images/image.png)").unwrap();
+    let img_path = tmp_dir_pth.join("images/image.png");
+    fs::write(&img_path, "not really a png").unwrap();
+    let img_name = hashed_dst_path(&img_path).unwrap().file_name().unwrap().to_owned();
+
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    let cfg_str = r#"
+title: "Test Presentation"
+slide_dir: "slides"
+output_dir: "output"
+output_file: "index.html"
+image_dir: "assets"
+"#;
+    fs::write(&cfg_file, cfg_str).unwrap();
+
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    let dst = PathBuf::from("./assets").join(&img_name);
+    assert_eq!(cfg.slides[0].local_images[0].1, dst);
+    assert!(cfg.slides[0].markdown.contains(&format!("![img]({})", dst.display())));
+    cfg.package().expect("package to succeed");
+    let output = fs::read_to_string(tmp_dir_pth.join("output/index.html")).unwrap();
+    assert!(output.contains(&format!("src=\"{}\"", dst.display())));
+    assert!(tmp_dir_pth.join("output").join(&dst).is_file());
+    assert!(!tmp_dir_pth.join("output/img").exists());
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_image_root_cli_override_wins() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::create_dir(tmp_dir_pth.join("images")).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "![img](../images/image.png)").unwrap();
+    let img_path = tmp_dir_pth.join("images/image.png");
+    fs::write(&img_path, "not really a png").unwrap();
+    let img_name = hashed_dst_path(&img_path).unwrap().file_name().unwrap().to_owned();
+
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    let cfg_str = r#"
+title: "Test Presentation"
+slide_dir: "slides"
+output_dir: "output"
+output_file: "index.html"
+image_root: "assets/images"
+"#;
+    fs::write(&cfg_file, cfg_str).unwrap();
+
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file.clone()).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    let configured_dst = PathBuf::from("./assets/images").join(&img_name);
+    assert_eq!(cfg.slides[0].local_images[0].1, configured_dst);
+
+    let args = CliArgs::parse_from([
+        OsStr::new("mkrevealslides"),
+        OsStr::new("--image-root"),
+        OsStr::new("static"),
+        OsStr::new("from-config"),
+        cfg_file.as_os_str(),
+    ]);
+    let cfg = PresentationConfig::try_from(args).unwrap();
+    let overridden_dst = PathBuf::from("./static").join(&img_name);
+    assert_eq!(cfg.slides[0].local_images[0].1, overridden_dst);
+    cfg.package().expect("package to succeed");
+    let output = fs::read_to_string(tmp_dir_pth.join("output/index.html")).unwrap();
+    assert!(output.contains(&format!("src=\"{}\"", overridden_dst.display())));
+    assert!(tmp_dir_pth.join("output").join(&overridden_dst).is_file());
+    assert!(!tmp_dir_pth.join("output/assets").exists());
+
+    let args = CliArgs::parse_from([
+        OsStr::new("mkrevealslides"),
+        OsStr::new("--image-root"),
+        OsStr::new("../outside"),
+        OsStr::new("from-config"),
+        cfg_file.as_os_str(),
+    ]);
+    assert!(PresentationConfig::try_from(args).is_err());
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_with_discover_remaining() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    for name in ["1_a.md", "2_b.md", "3_c.md", "4_d.md", "5_e.md"] {
+        fs::write(slide_dir.join(name), name).unwrap();
+    }
+
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    let cfg_str = r#"
+title: "Test Presentation"
+slide_dir: "slides"
+output_dir: "output"
+output_file: "index.html"
+include_files:
+  - "3_c.md"
+  - "./1_a.md"
+  - "5_e.md"
+discover_remaining: true
+"#;
+    fs::write(&cfg_file, cfg_str).unwrap();
+
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file.clone()).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    let names = cfg
+        .slides
+        .iter()
+        .map(|s| s.path.file_name().unwrap().to_str().unwrap().to_string())
+        .collect::<Vec<String>>();
+    assert_eq!(names, vec!["3_c.md", "1_a.md", "5_e.md", "2_b.md", "4_d.md"]);
+
+    // only the listed slides without it
+    fs::write(&cfg_file, cfg_str.replace("discover_remaining: true", "")).unwrap();
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    assert_eq!(cfg.slides.len(), 3);
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_with_include_file_globs() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir_all(slide_dir.join("chapter1")).unwrap();
+    for name in ["intro.md", "chapter1/10_c.md", "chapter1/2_b.md", "chapter1/1_a.md", "outro.md"] {
+        fs::write(slide_dir.join(name), name).unwrap();
+    }
+
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    fs::write(
+        &cfg_file,
+        r#"
+title: "Test Presentation"
+slide_dir: "slides"
+output_dir: "output"
+output_file: "index.html"
+include_files:
+  - "intro.md"
+  - path: "chapter1/*.md"
+    transition: "zoom"
+  - "outro.md"
+"#,
+    )
+    .unwrap();
+
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file.clone()).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    let names = cfg
+        .slides
+        .iter()
+        .map(|s| s.path.file_name().unwrap().to_str().unwrap().to_string())
+        .collect::<Vec<String>>();
+    assert_eq!(names, vec!["intro.md", "1_a.md", "2_b.md", "10_c.md", "outro.md"]);
+    // every slide of a glob takes the options of its entry
+    let transitions = cfg
+        .slides
+        .iter()
+        .map(|s| s.meta.transition.as_deref())
+        .collect::<Vec<Option<&str>>>();
+    assert_eq!(transitions, vec![None, Some("zoom"), Some("zoom"), Some("zoom"), None]);
+
+    fs::write(
+        &cfg_file,
+        "title: t\nslide_dir: slides\noutput_dir: output\ninclude_files: [\"chapter2/*.md\"]\n",
+    )
+    .unwrap();
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    let err = PresentationConfig::try_from(cfg_file_obj).unwrap_err();
+    assert!(err.to_string().contains("chapter2/*.md"));
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_skips_unreadable_slides() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(slide_dir.join("1_good.md"), "Good").unwrap();
+    fs::write(slide_dir.join("2_bad.md"), b"Bad \xff\xfe").unwrap();
+
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    let cfg_str = r#"
+title: "Test Presentation"
+slide_dir: "slides"
+output_dir: "output"
+output_file: "index.html"
+"#;
+    fs::write(&cfg_file, cfg_str).unwrap();
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file.clone()).unwrap();
+    let (cfg, warnings) = capture_warnings(|| PresentationConfig::try_from(cfg_file_obj));
+    let cfg = cfg.unwrap();
+    assert_eq!(cfg.slides.len(), 1);
+    assert_eq!(cfg.slides[0].path, slide_dir.join("1_good.md"));
+    assert!(warnings.contains(&format!("Skipping `{}`", slide_dir.join("2_bad.md").display())));
+
+    // a slide listed by name is not skipped
+    fs::write(&cfg_file, format!("{}include_files: [\"1_good.md\", \"2_bad.md\"]\n", cfg_str)).unwrap();
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    assert!(PresentationConfig::try_from(cfg_file_obj).is_err());
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_with_include_file_options() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(slide_dir.join("1_intro.md"), "---\ntransition: fade\n---\nIntro").unwrap();
+    fs::write(slide_dir.join("2_demo.md"), "Demo").unwrap();
+
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    let cfg_str = r##"
+title: "Test Presentation"
+slide_dir: "slides"
+output_dir: "output"
+output_file: "index.html"
+include_files:
+  - path: "1_intro.md"
+    transition: "zoom"
+  - path: "2_demo.md"
+    background: "#000"
+"##;
+    fs::write(&cfg_file, cfg_str).unwrap();
+
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    assert_eq!(cfg.slides[0].meta.transition.as_deref(), Some("zoom"));
+    assert_eq!(cfg.slides[1].meta.background_color.as_deref(), Some("#000"));
+    cfg.package().expect("package to succeed");
+    let html = fs::read_to_string(tmp_dir_pth.join("output").join("index.html")).unwrap();
+    assert!(html.contains("<section data-transition=\"zoom\">"));
+    assert!(html.contains("<section data-background-color=\"#000\">"));
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_with_multiple_outputs() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "Slide 1").unwrap();
+    fs::write(slide_dir.join("2_slide2.md"), "Slide 2").unwrap();
+    fs::write(
+        tmp_dir_pth.join("handout.html"),
+        "<h1>Handout</h1>{% for fc in ingested_files %}<article>{{ fc }}</article>{% endfor %}",
+    )
+    .unwrap();
+
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    let cfg_str = r#"
+title: "Test Presentation"
+slide_dir: "slides"
+output_dir: "output"
+outputs:
+  - output_file: "index.html"
+  - template_file: "handout.html"
+    output_file: "handout.html"
+"#;
+    fs::write(&cfg_file, cfg_str).unwrap();
+
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    assert_eq!(cfg.output_filename, PathBuf::from("index.html"));
+    cfg.package().expect("package to succeed");
+
+    let index = fs::read_to_string(tmp_dir_pth.join("output").join("index.html")).unwrap();
+    assert!(index.contains("reveal.js"));
+    assert!(index.contains("<p>Slide 2</p>"));
+    let handout = fs::read_to_string(tmp_dir_pth.join("output").join("handout.html")).unwrap();
+    assert!(handout.starts_with("<h1>Handout</h1>"));
+    assert!(handout.contains("<article><p>Slide 1</p>"));
+    assert!(handout.contains("<article><p>Slide 2</p>"));
+    assert!(!handout.contains("reveal.js"));
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_without_output_file() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "Slide 1").unwrap();
+
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    fs::write(&cfg_file, "title: Test\nslide_dir: slides\noutput_dir: output\n").unwrap();
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    let err = PresentationConfig::try_from(cfg_file_obj).unwrap_err();
+    assert!(err.to_string().contains("unless `outputs` is"));
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_with_source_comments() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(slide_dir.join("1_intro.md"), "Intro").unwrap();
+    fs::write(slide_dir.join("2_end.md"), "End").unwrap();
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    fs::write(
+        &cfg_file,
+        "title: Test\nslide_dir: slides\noutput_dir: output\noutput_file: index.html\nminify: true\n",
+    )
+    .unwrap();
+
+    let args = CliArgs::parse_from([
+        OsStr::new("mkrevealslides"),
+        OsStr::new("--source-comments"),
+        OsStr::new("from-config"),
+        cfg_file.as_os_str(),
+    ]);
+    let cfg = PresentationConfig::try_from(args).unwrap();
+    assert!(cfg.source_comments);
+    cfg.package().expect("package to succeed");
+    let html = fs::read_to_string(tmp_dir_pth.join("output").join("index.html")).unwrap();
+    let intro = html.find("<!-- slide: slides/1_intro.md -->").expect("a comment for the first slide");
+    let end = html.find("<!-- slide: slides/2_end.md -->").expect("a comment for the second slide");
+    assert!(intro < html.find("Intro").unwrap());
+    assert!(intro < end && end < html.find("End").unwrap());
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_without_create_output_dir() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "Slide 1").unwrap();
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    fs::write(
+        &cfg_file,
+        "title: Test\nslide_dir: slides\noutput_dir: ouptut\noutput_file: index.html\ncreate_output_dir: false\n",
+    )
+    .unwrap();
+
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    let err = cfg.package().unwrap_err();
+    assert!(err.to_string().contains("Output directory does not exist"));
+    assert!(!tmp_dir_pth.join("ouptut").exists());
+
+    fs::create_dir(tmp_dir_pth.join("ouptut")).unwrap();
+    cfg.package().expect("package to succeed");
+    assert!(tmp_dir_pth.join("ouptut").join("index.html").is_file());
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_with_misspelled_include_file() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(slide_dir.join("1_intro.md"), "Intro").unwrap();
+    fs::write(slide_dir.join("2_results.md"), "Results").unwrap();
+
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    let cfg_str = r#"
+title: "Test Presentation"
+slide_dir: "slides"
+output_dir: "output"
+output_file: "index.html"
+include_files:
+  - "1_intro.md"
+  - "2_resluts.md"
+"#;
+    fs::write(&cfg_file, cfg_str).unwrap();
+
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    let err = PresentationConfig::try_from(cfg_file_obj).unwrap_err().to_string();
+    assert!(err.contains("2_resluts.md"), "{}", err);
+    assert!(err.contains("did you mean `2_results.md`?"), "{}", err);
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_with_vertical_stacks() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir_all(slide_dir.join("2_details")).unwrap();
+    fs::write(slide_dir.join("1_intro.md"), "Intro").unwrap();
+    fs::write(slide_dir.join("2_details/1_first.md"), "First").unwrap();
+    fs::write(slide_dir.join("2_details/2_second.md"), "---\ndraft: true\n---\nSecond").unwrap();
+    fs::write(slide_dir.join("2_details/3_third.md"), "Third").unwrap();
+    fs::write(slide_dir.join("3_outro.md"), "Outro").unwrap();
+    fs::write(tmp_dir_pth.join("template.html"), "{{ slides_html }}").unwrap();
+
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    let cfg_str = r#"
+title: "Test Presentation"
+slide_dir: "slides"
+output_dir: "output"
+output_file: "index.html"
+template_file: "template.html"
+slide_join: ""
+"#;
+    fs::write(&cfg_file, cfg_str).unwrap();
+    let read_output = || fs::read_to_string(tmp_dir_pth.join("output/index.html")).unwrap();
+
+    // subdirectories are not slides by default
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file.clone()).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    assert!(cfg.stacks.is_empty());
+    cfg.package().expect("package to succeed");
+    assert_eq!(
+        read_output(),
+        "<section><p>Intro</p>\n</section><section><p>Outro</p>\n</section>"
+    );
+
+    fs::write(&cfg_file, format!("{}vertical_stacks: true\n", cfg_str)).unwrap();
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    assert_eq!(cfg.slides.len(), 4);
+    assert_eq!(cfg.stacks, vec![1..3]);
+    cfg.package().expect("package to succeed");
+    assert_eq!(
+        read_output(),
+        "<section><p>Intro</p>\n</section>\
+         <section><section><p>First</p>\n</section><section><p>Third</p>\n</section></section>\
+         <section><p>Outro</p>\n</section>"
+    );
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_with_subtitle() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "Slide 1").unwrap();
+    fs::write(
+        tmp_dir_pth.join("template.html"),
+        "[{{ subtitle }}]{{ slides_html }}",
+    )
+    .unwrap();
+
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    let cfg_str = r#"
+title: "Rust & You"
+subtitle: "A gentle introduction"
+slide_dir: "slides"
+output_dir: "output"
+output_file: "index.html"
+template_file: "template.html"
+slide_join: ""
+"#;
+    fs::write(&cfg_file, cfg_str).unwrap();
+    let read_output = || fs::read_to_string(tmp_dir_pth.join("output/index.html")).unwrap();
+
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file.clone()).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    cfg.package().expect("package to succeed");
+    assert_eq!(
+        read_output(),
+        "[A gentle introduction]<section><p>Slide 1</p>\n</section>"
+    );
+
+    fs::write(&cfg_file, format!("{}title_slide: true\n", cfg_str)).unwrap();
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    cfg.package().expect("package to succeed");
+    assert_eq!(
+        read_output(),
+        "[A gentle introduction]\
+         <section><h1>Rust &amp; You</h1>\n<p>A gentle introduction</p>\n</section>\
+         <section><p>Slide 1</p>\n</section>"
+    );
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_with_bundle_reveal() {
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    let mut tarball = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+    for (path, contents) in [
+        ("reveal.js-9.9.9/dist/reveal.js", "reveal"),
+        ("reveal.js-9.9.9/plugin/notes/notes.js", "notes"),
+        ("reveal.js-9.9.9/README.md", "readme"),
+    ] {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        tarball.append_data(&mut header, path, contents.as_bytes()).unwrap();
+    }
+    let tarball = tarball.into_inner().unwrap().finish().unwrap();
+    let (addr, hits) = serve_file("/9.9.9.tar.gz", tarball);
+
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "Slide 1").unwrap();
+    fs::write(tmp_dir_pth.join("template.html"), "{{ reveal_path }}/dist/reveal.js").unwrap();
+
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    let cfg_str = format!(
+        r#"
+title: "Test Presentation"
+slide_dir: "slides"
+output_dir: "output"
+output_file: "index.html"
+template_file: "template.html"
+bundle_reveal: true
+reveal_version: "9.9.9"
+reveal_url: "http://{}/{{version}}.tar.gz"
+"#,
+        addr
+    );
+    fs::write(&cfg_file, cfg_str).unwrap();
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+
+    let output_dir = tmp_dir_pth.join("output");
+    for _ in 0..2 {
+        cfg.package().expect("package to succeed");
+        assert_eq!(fs::read_to_string(output_dir.join("index.html")).unwrap(), "./dist/reveal.js");
+        assert_eq!(fs::read_to_string(output_dir.join("dist/reveal.js")).unwrap(), "reveal");
+        assert_eq!(fs::read_to_string(output_dir.join("plugin/notes/notes.js")).unwrap(), "notes");
+        assert!(!output_dir.join("README.md").exists());
+    }
+    // the second build uses the cached release
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+    let cached = fs::read_dir(tmp_dir_pth.join(".mkrevealslides-cache"))
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect::<Vec<String>>();
+    assert_eq!(cached.len(), 1);
+    assert!(cached[0].starts_with("reveal.js-9.9.9-") && cached[0].ends_with(".tar.gz"));
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_with_cross_directory_images() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+
+    let slide_dir = tmp_dir_pth.join("slides");
+    let intro_img = slide_dir.join("intro/img/x.png");
+    let body_img = slide_dir.join("body/img/x.png");
+    for (img, contents) in [(&intro_img, "intro image"), (&body_img, "body image")] {
+        fs::create_dir_all(img.parent().unwrap()).unwrap();
+        fs::write(img, contents).unwrap();
+    }
+    fs::write(slide_dir.join("intro/1_intro.md"), "![](img/x.png)").unwrap();
+    fs::write(slide_dir.join("body/2_body.md"), "![](./img/x.png) ![](../intro/img/x.png)").unwrap();
+    fs::write(tmp_dir_pth.join("template.html"), "{{ slides_html }}").unwrap();
+
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    let cfg_str = r#"
+title: "Test Presentation"
+slide_dir: "slides"
+output_dir: "output"
+output_file: "index.html"
+template_file: "template.html"
+slide_join: ""
+include_files:
+  - "intro/1_intro.md"
+  - "body/2_body.md"
+"#;
+    fs::write(&cfg_file, cfg_str).unwrap();
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file.clone()).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    cfg.package().expect("package to succeed");
+
+    let output_dir = tmp_dir_pth.join("output");
+    let intro_dst = hashed_dst_path(&intro_img).unwrap();
+    let body_dst = hashed_dst_path(&body_img).unwrap();
+    assert_eq!(fs::read_to_string(output_dir.join(&intro_dst)).unwrap(), "intro image");
+    assert_eq!(fs::read_to_string(output_dir.join(&body_dst)).unwrap(), "body image");
+    assert_eq!(
+        fs::read_to_string(output_dir.join("index.html")).unwrap(),
+        format!(
+            "<section><p><img src=\"{intro}\" alt=\"\" /></p>\n</section>\
+             <section><p><img src=\"{body}\" alt=\"\" /> <img src=\"{intro}\" alt=\"\" /></p>\n</section>",
+            intro = intro_dst.display(),
+            body = body_dst.display()
+        )
+    );
+
+    // a template that does not tell the slides apart places both images at `img/x.png`
+    fs::write(&cfg_file, format!("{}img_path_template: \"img/{{file_name}}\"\n", cfg_str)).unwrap();
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    let err = PresentationConfig::try_from(cfg_file_obj).unwrap_err().to_string();
+    assert!(err.contains(&intro_img.display().to_string()), "{}", err);
+    assert!(err.contains(&body_img.display().to_string()), "{}", err);
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_with_template_dir() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "Slide 1").unwrap();
+
+    let template_dir = tmp_dir_pth.join("templates");
+    fs::create_dir_all(template_dir.join("partials")).unwrap();
+    fs::write(
+        template_dir.join("base.html"),
+        "<html>{% include \"partials/header.html\" %}{% block content %}{% endblock content %}</html>",
+    )
+    .unwrap();
+    fs::write(template_dir.join("partials/header.html"), "<h1>{{ slide_title }}</h1>").unwrap();
+    fs::write(
+        template_dir.join("index.html"),
+        "{% extends \"base.html\" %}{% block content %}{{ slides_html }}{% endblock content %}",
+    )
+    .unwrap();
+
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    let cfg_str = r#"
+title: "Test Presentation"
+slide_dir: "slides"
+output_dir: "output"
+output_file: "index.html"
+template_dir: "templates"
+template_file: "index.html"
+strict: true
+"#;
+    fs::write(&cfg_file, cfg_str).unwrap();
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file.clone()).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    assert_eq!(cfg.template_file, Some(template_dir.join("index.html")));
+    cfg.package().expect("package to succeed");
+    assert_eq!(
+        fs::read_to_string(tmp_dir_pth.join("output/index.html")).unwrap(),
+        "<html><h1>Test Presentation</h1><section><p>Slide 1</p>\n</section></html>"
+    );
+
+    // the template to render must be one of those in the directory
+    fs::write(&cfg_file, cfg_str.replace("template_file: \"index.html\"\n", "")).unwrap();
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    let err = PresentationConfig::try_from(cfg_file_obj).unwrap_err().to_string();
+    assert!(err.contains("template_file"), "{}", err);
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_with_split_slides() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(slide_dir.join("1_intro.md"), "# Intro\n<!-- slide -->\n# Agenda\n").unwrap();
+    fs::write(slide_dir.join("2_end.md"), "# End\n\n---\n\nThanks\n").unwrap();
+    let template_file = tmp_dir_pth.join("template.html");
+    fs::write(&template_file, "{{ slide_title }}{{ slides_html }}").unwrap();
+
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    let cfg_str = r#"
+title: "Test Presentation"
+slide_dir: "slides"
+output_dir: "output"
+output_file: "index.html"
+template_file: "template.html"
+slide_join: ""
+split_slides: true
+"#;
+    fs::write(&cfg_file, format!("{}slide_separator: \"<!-- slide -->\"\n", cfg_str)).unwrap();
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file.clone()).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    assert_eq!(cfg.slides.len(), 3);
+    assert_eq!(
+        cfg.render_to_string().unwrap(),
+        "Test Presentation<section><h1>Intro</h1>\n</section><section><h1>Agenda</h1>\n</section>\
+         <section><h1>End</h1>\n<hr />\n<p>Thanks</p>\n</section>"
+    );
+
+    // the separator is `---` unless configured otherwise
+    fs::write(&cfg_file, cfg_str).unwrap();
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file.clone()).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    assert_eq!(cfg.slides.len(), 3);
+    assert_eq!(cfg.slides[2].contents, "<p>Thanks</p>\n");
+
+    fs::write(&cfg_file, format!("{}slide_separator: \" \"\n", cfg_str)).unwrap();
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    let err = PresentationConfig::try_from(cfg_file_obj).unwrap_err().to_string();
+    assert!(err.contains("slide_separator"), "{}", err);
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_with_static_assets() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "Slide 1").unwrap();
+    let template_file = tmp_dir_pth.join("template.html");
+    fs::write(&template_file, "{{ slide_title }}{{ slides_html }}").unwrap();
+    let fonts_dir = tmp_dir_pth.join("assets/fonts");
+    fs::create_dir_all(fonts_dir.join("bold")).unwrap();
+    fs::write(fonts_dir.join("regular.woff2"), "regular").unwrap();
+    fs::write(fonts_dir.join("bold/bold.woff2"), "bold").unwrap();
+    fs::write(tmp_dir_pth.join("favicon.ico"), "icon").unwrap();
+
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    let cfg_str = r#"
+title: "Test Presentation"
+slide_dir: "slides"
+output_dir: "output"
+output_file: "index.html"
+template_file: "template.html"
+static_assets:
+  - "assets/fonts/"
+  - "favicon.ico"
+"#;
+    fs::write(&cfg_file, cfg_str).unwrap();
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file.clone()).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    cfg.package().expect("package to succeed");
+
+    let output_dir = tmp_dir_pth.join("output");
+    assert_eq!(fs::read_to_string(output_dir.join("fonts/regular.woff2")).unwrap(), "regular");
+    assert_eq!(fs::read_to_string(output_dir.join("fonts/bold/bold.woff2")).unwrap(), "bold");
+    assert_eq!(fs::read_to_string(output_dir.join("favicon.ico")).unwrap(), "icon");
+
+    fs::write(&cfg_file, format!("{}  - \"missing.css\"\n", cfg_str)).unwrap();
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    let err = PresentationConfig::try_from(cfg_file_obj).unwrap_err().to_string();
+    assert!(err.contains("missing.css"), "{}", err);
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_presentation_skips_up_to_date_images() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_dir_pth = fs::canonicalize(tmp_dir.path()).expect("temp dir exists");
+
+    let slide_dir = tmp_dir_pth.join("slides");
+    fs::create_dir(&slide_dir).unwrap();
+    fs::write(slide_dir.join("1_slide1.md"), "![](../img/1_img1.png)").unwrap();
+    let img_dir = tmp_dir_pth.join("img");
+    fs::create_dir(&img_dir).unwrap();
+    fs::write(img_dir.join("1_img1.png"), "image").unwrap();
+
+    let cfg_file = tmp_dir_pth.join("config.yaml");
+    let cfg_str = r#"
+title: "Test Presentation"
+slide_dir: "slides"
+output_dir: "output"
+output_file: "index.html"
+img_path_template: "img/{file_name}"
+"#;
+    fs::write(&cfg_file, cfg_str).unwrap();
+    let cfg_file_obj = PresentationConfigFile::read_config_file(cfg_file).unwrap();
+    let cfg = PresentationConfig::try_from(cfg_file_obj).unwrap();
+    let package = |cfg: &PresentationConfig| {
+        let mut events = Vec::new();
+        cfg.package_with_progress(|event| match event {
+            BuildEvent::CopyingImage { .. } | BuildEvent::ImageUpToDate { .. } => events.push(event),
+            _ => {}
+        })
+        .expect("package to succeed");
+        events
+    };
+    let copy = BuildEvent::CopyingImage {
+        src: img_dir.join("1_img1.png"),
+        dst: PathBuf::from("img/1_img1.png"),
+    };
+    assert_eq!(package(&cfg), vec![copy.clone()]);
+    assert_eq!(
+        package(&cfg),
+        vec![BuildEvent::ImageUpToDate {
+            src: img_dir.join("1_img1.png"),
+            dst: PathBuf::from("img/1_img1.png"),
+        }]
+    );
+
+    // an image that has changed since is copied again
+    fs::write(img_dir.join("1_img1.png"), "changed image").unwrap();
+    assert_eq!(package(&cfg), vec![copy]);
+    assert_eq!(
+        fs::read_to_string(tmp_dir_pth.join("output/img/1_img1.png")).unwrap(),
+        "changed image"
+    );
+
+    let report = cfg.package().expect("package to succeed");
+    assert_eq!(report.output_path, tmp_dir_pth.join("output/index.html"));
+    assert_eq!(report.slides, 1);
+    assert_eq!(report.images_copied, 0);
+    // only the presentation itself is written
+    assert_eq!(report.bytes_written, report.html_bytes);
+    assert_eq!(
+        report.html_bytes,
+        fs::metadata(tmp_dir_pth.join("output/index.html")).unwrap().len()
+    );
     tmp_dir.close().unwrap();
 }